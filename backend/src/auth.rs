@@ -0,0 +1,331 @@
+// src/auth.rs
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use axum::extract::{FromRef, FromRequestParts, Query};
+use axum::http::header::{AUTHORIZATION, CACHE_CONTROL};
+use axum::http::request::Parts;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use tokio::sync::Mutex;
+use notepad_models::User;
+use crate::error::ApiError;
+
+/// Chaves públicas que assinam os tokens de ID do Firebase.
+const KEYS_URL: &str = "https://www.googleapis.com/service_accounts/v1/jwk/securetoken@system.gserviceaccount.com";
+
+/// Por quanto tempo guardar as chaves se a resposta não disser (`max-age`).
+const DEFAULT_KEYS_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Tolerância, em segundos, para relógios levemente adiantados.
+const LEEWAY_SECS: i64 = 60;
+
+/// Como o servidor identifica quem faz as requisições.
+#[derive(Clone)]
+pub enum Auth {
+    /// Tokens de ID do Firebase do projeto, verificados com as chaves
+    /// públicas do Google.
+    Firebase(FirebaseAuth),
+    /// Confia nos cabeçalhos `x-user-id` e `x-user-email`; só existe com a
+    /// feature `dev-auth`.
+    #[cfg(feature = "dev-auth")]
+    Trusted,
+}
+
+impl Auth {
+    /// `FIREBASE_PROJECT_ID` define o projeto cujos tokens são aceitos. Sem
+    /// ele, só um servidor compilado com `dev-auth` inicia.
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var("FIREBASE_PROJECT_ID") {
+            Ok(project_id) if !project_id.is_empty() => Ok(Auth::Firebase(FirebaseAuth::new(project_id))),
+            #[cfg(feature = "dev-auth")]
+            _ => Ok(Auth::Trusted),
+            #[cfg(not(feature = "dev-auth"))]
+            _ => Err("defina FIREBASE_PROJECT_ID (ou compile com --features dev-auth)".to_string()),
+        }
+    }
+}
+
+/// Usuário que fez a requisição.
+///
+/// O token de ID do Firebase vem no cabeçalho `Authorization: Bearer` ou no
+/// parâmetro `token`, já que o navegador não envia cabeçalhos próprios ao
+/// abrir um WebSocket. Com `Auth::Trusted`, o usuário é lido dos cabeçalhos
+/// `x-user-id` e `x-user-email` (ou dos parâmetros `uid` e `email`).
+pub struct Actor(pub User);
+
+impl<S> FromRequestParts<S> for Actor
+where
+    S: Send + Sync,
+    Auth: FromRef<S>,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(params) = Query::<HashMap<String, String>>::from_request_parts(parts, state)
+            .await
+            .unwrap_or_default();
+        match Auth::from_ref(state) {
+            Auth::Firebase(firebase) => {
+                let bearer = header(parts, AUTHORIZATION.as_str())
+                    .and_then(|v| v.strip_prefix("Bearer ").map(str::to_string));
+                let token = bearer.or_else(|| params.get("token").cloned())
+                    .ok_or(ApiError::Unauthorized)?;
+                firebase.verify(&token).await.map(Actor)
+            }
+            #[cfg(feature = "dev-auth")]
+            Auth::Trusted => {
+                let uid = header(parts, "x-user-id").or_else(|| params.get("uid").cloned());
+                let email = header(parts, "x-user-email").or_else(|| params.get("email").cloned());
+                match uid.filter(|u| !u.is_empty()) {
+                    Some(uid) => Ok(Actor(User { uid, email: email.unwrap_or_default() })),
+                    None => Err(ApiError::Unauthorized),
+                }
+            }
+        }
+    }
+}
+
+fn header(parts: &Parts, name: &str) -> Option<String> {
+    parts.headers.get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Verifica tokens de ID do Firebase de um projeto, guardando as chaves
+/// públicas do Google pelo tempo que a resposta permitir.
+#[derive(Clone)]
+pub struct FirebaseAuth {
+    project_id: String,
+    client: reqwest::Client,
+    keys: Arc<Mutex<Keys>>,
+}
+
+#[derive(Default)]
+struct Keys {
+    by_kid: HashMap<String, DecodingKey>,
+    expires_at: Option<Instant>,
+}
+
+#[derive(Deserialize)]
+struct Jwks {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Deserialize)]
+struct Jwk {
+    kid: String,
+    n: String,
+    e: String,
+}
+
+#[derive(Deserialize)]
+struct Claims {
+    sub: String,
+    #[serde(default)]
+    email: Option<String>,
+    /// Qualquer um cria uma conta com um e-mail que não é seu; só o e-mail
+    /// confirmado dá acesso às notas compartilhadas com ele.
+    #[serde(default)]
+    email_verified: bool,
+    iat: i64,
+    #[serde(default)]
+    auth_time: i64,
+}
+
+impl FirebaseAuth {
+    pub fn new(project_id: String) -> Self {
+        Self { project_id, client: reqwest::Client::new(), keys: Arc::default() }
+    }
+
+    pub async fn verify(&self, token: &str) -> Result<User, ApiError> {
+        let kid = decode_header(token).ok()
+            .and_then(|h| h.kid)
+            .ok_or(ApiError::Unauthorized)?;
+        let key = self.key(&kid).await?.ok_or(ApiError::Unauthorized)?;
+        verify_token(token, &key, &self.project_id, unix_now())
+    }
+
+    async fn key(&self, kid: &str) -> Result<Option<DecodingKey>, ApiError> {
+        let mut keys = self.keys.lock().await;
+        if keys.expires_at.is_none_or(|at| at <= Instant::now()) {
+            *keys = self.fetch_keys().await?;
+        }
+        Ok(keys.by_kid.get(kid).cloned())
+    }
+
+    async fn fetch_keys(&self) -> Result<Keys, ApiError> {
+        let response = self.client.get(KEYS_URL).send().await
+            .and_then(|r| r.error_for_status())
+            .map_err(ApiError::AuthKeys)?;
+        let ttl = response.headers().get(CACHE_CONTROL)
+            .and_then(|v| v.to_str().ok())
+            .and_then(max_age)
+            .unwrap_or(DEFAULT_KEYS_TTL);
+        let jwks: Jwks = response.json().await.map_err(ApiError::AuthKeys)?;
+        let by_kid = jwks.keys.into_iter()
+            .filter_map(|k| DecodingKey::from_rsa_components(&k.n, &k.e).ok().map(|key| (k.kid, key)))
+            .collect();
+        Ok(Keys { by_kid, expires_at: Some(Instant::now() + ttl) })
+    }
+}
+
+fn max_age(cache_control: &str) -> Option<Duration> {
+    cache_control.split(',')
+        .find_map(|d| d.trim().strip_prefix("max-age="))
+        .and_then(|s| s.parse().ok())
+        .map(Duration::from_secs)
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0)
+}
+
+/// Confere assinatura (RS256), projeto (`aud` e `iss`), validade e usuário
+/// (`sub`) de um token de ID, como descrito na documentação do Firebase.
+fn verify_token(token: &str, key: &DecodingKey, project_id: &str, now: i64) -> Result<User, ApiError> {
+    let mut validation = Validation::new(Algorithm::RS256);
+    validation.set_audience(&[project_id]);
+    validation.set_issuer(&[format!("https://securetoken.google.com/{}", project_id)]);
+    validation.set_required_spec_claims(&["exp", "aud", "iss", "sub"]);
+    let claims = decode::<Claims>(token, key, &validation)
+        .map_err(|_| ApiError::Unauthorized)?
+        .claims;
+    if claims.sub.is_empty() || claims.iat > now + LEEWAY_SECS || claims.auth_time > now + LEEWAY_SECS {
+        return Err(ApiError::Unauthorized);
+    }
+    let email = claims.email.filter(|_| claims.email_verified).unwrap_or_default();
+    Ok(User { uid: claims.sub, email })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use notepad_models::{FixedClock, Note, Role};
+    use serde_json::{json, Value};
+
+    const PROJECT: &str = "notepad-teste";
+    // par de chaves gerado só para os testes
+    const PRIVATE_KEY: &[u8] = include_bytes!("../testdata/firebase_test_key.pem");
+    const PUBLIC_KEY: &[u8] = include_bytes!("../testdata/firebase_test_key.pub.pem");
+
+    fn claims(now: i64) -> Value {
+        json!({
+            "iss": format!("https://securetoken.google.com/{}", PROJECT),
+            "aud": PROJECT,
+            "sub": "uid-1",
+            "email": "ana@example.com",
+            "email_verified": true,
+            "iat": now - 10,
+            "auth_time": now - 10,
+            "exp": now + 3600,
+        })
+    }
+
+    fn sign(claims: &Value) -> String {
+        let header = Header { kid: Some("chave-1".to_string()), ..Header::new(Algorithm::RS256) };
+        encode(&header, claims, &EncodingKey::from_rsa_pem(PRIVATE_KEY).unwrap()).unwrap()
+    }
+
+    fn check(claims: &Value) -> Result<User, ApiError> {
+        verify_token(&sign(claims), &DecodingKey::from_rsa_pem(PUBLIC_KEY).unwrap(), PROJECT, unix_now())
+    }
+
+    #[test]
+    fn accepts_valid_token() {
+        let user = check(&claims(unix_now())).unwrap();
+        assert_eq!(user.uid, "uid-1");
+        assert_eq!(user.email, "ana@example.com");
+    }
+
+    #[test]
+    fn unverified_email_gets_no_shared_access() {
+        let mut unverified = claims(unix_now());
+        unverified["email_verified"] = json!(false);
+        let user = check(&unverified).unwrap();
+        assert_eq!(user.email, "");
+
+        let mut note = Note::new_with_clock(&FixedClock(0), "t".to_string(), String::new(), "dono".to_string(),
+            String::new(), String::new(), String::new(), None);
+        note.share("ana@example.com", Role::Editor).unwrap();
+        assert_eq!(note.role_of(&user), None);
+        assert_eq!(note.role_of(&check(&claims(unix_now())).unwrap()), Some(Role::Editor));
+    }
+
+    #[test]
+    fn rejects_other_project() {
+        let mut other = claims(unix_now());
+        other["aud"] = json!("outro-projeto");
+        assert!(matches!(check(&other), Err(ApiError::Unauthorized)));
+
+        let mut other = claims(unix_now());
+        other["iss"] = json!("https://securetoken.google.com/outro-projeto");
+        assert!(matches!(check(&other), Err(ApiError::Unauthorized)));
+    }
+
+    #[test]
+    fn rejects_expired_and_future_tokens() {
+        let now = unix_now();
+        let mut expired = claims(now);
+        expired["exp"] = json!(now - 3600);
+        assert!(matches!(check(&expired), Err(ApiError::Unauthorized)));
+
+        let mut future = claims(now);
+        future["iat"] = json!(now + 3600);
+        assert!(matches!(check(&future), Err(ApiError::Unauthorized)));
+    }
+
+    #[test]
+    fn rejects_missing_subject() {
+        let mut anonymous = claims(unix_now());
+        anonymous["sub"] = json!("");
+        assert!(matches!(check(&anonymous), Err(ApiError::Unauthorized)));
+    }
+
+    #[test]
+    fn rejects_tampered_payload() {
+        let token = sign(&claims(unix_now()));
+        let mut parts: Vec<&str> = token.split('.').collect();
+        let mut forged = claims(unix_now());
+        forged["sub"] = json!("uid-do-dono");
+        let payload = sign(&forged);
+        parts[1] = payload.split('.').nth(1).unwrap();
+        let key = DecodingKey::from_rsa_pem(PUBLIC_KEY).unwrap();
+        assert!(matches!(verify_token(&parts.join("."), &key, PROJECT, unix_now()), Err(ApiError::Unauthorized)));
+    }
+
+    #[test]
+    fn parses_max_age() {
+        assert_eq!(max_age("public, max-age=19773, must-revalidate"), Some(Duration::from_secs(19773)));
+        assert_eq!(max_age("no-cache"), None);
+    }
+
+    #[tokio::test]
+    async fn firebase_mode_ignores_identity_headers() {
+        let auth = Auth::Firebase(FirebaseAuth::new(PROJECT.to_string()));
+        let (mut parts, _) = Request::builder()
+            .uri("/api/notes?uid=uid-1&email=ana@example.com")
+            .header("x-user-id", "uid-1")
+            .header("x-user-email", "ana@example.com")
+            .body(())
+            .unwrap()
+            .into_parts();
+        let result = Actor::from_request_parts(&mut parts, &auth).await;
+        assert!(matches!(result, Err(ApiError::Unauthorized)));
+    }
+
+    #[cfg(feature = "dev-auth")]
+    #[tokio::test]
+    async fn trusted_mode_reads_headers() {
+        let (mut parts, _) = Request::builder()
+            .header("x-user-id", "uid-1")
+            .header("x-user-email", "ana@example.com")
+            .body(())
+            .unwrap()
+            .into_parts();
+        let Actor(user) = Actor::from_request_parts(&mut parts, &Auth::Trusted).await.unwrap();
+        assert_eq!((user.uid.as_str(), user.email.as_str()), ("uid-1", "ana@example.com"));
+    }
+}
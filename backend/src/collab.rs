@@ -0,0 +1,168 @@
+// src/collab.rs
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, State};
+use axum::response::Response;
+use tokio::sync::broadcast::{self, error::RecvError};
+use notepad_models::{CollabMessage, Presence};
+use crate::auth::Actor;
+use crate::db::Db;
+use crate::error::ApiError;
+
+/// Mensagens ainda não entregues a um participante lento antes de ele
+/// precisar receber todas as changes de novo.
+const ROOM_CAPACITY: usize = 256;
+
+struct Room {
+    tx: broadcast::Sender<(u64, CollabMessage)>,
+    presences: HashMap<String, Presence>,
+    members: usize,
+}
+
+/// Salas de edição colaborativa, uma por nota com participantes conectados.
+///
+/// As changes são gravadas no banco; a presença (cursores) fica só em memória.
+#[derive(Clone, Default)]
+pub struct Rooms {
+    rooms: Arc<Mutex<HashMap<String, Room>>>,
+    next_member: Arc<AtomicU64>,
+}
+
+impl Rooms {
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Room>> {
+        self.rooms.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn enter(&self, note_id: &str) -> (u64, broadcast::Receiver<(u64, CollabMessage)>, Vec<Presence>) {
+        let member = self.next_member.fetch_add(1, Ordering::Relaxed);
+        let mut rooms = self.lock();
+        let room = rooms.entry(note_id.to_string()).or_insert_with(|| Room {
+            tx: broadcast::channel(ROOM_CAPACITY).0,
+            presences: HashMap::new(),
+            members: 0,
+        });
+        room.members += 1;
+        (member, room.tx.subscribe(), room.presences.values().cloned().collect())
+    }
+
+    /// Repassa a mensagem aos demais participantes, atualizando a presença.
+    fn publish(&self, note_id: &str, from: u64, message: CollabMessage) {
+        let mut rooms = self.lock();
+        let Some(room) = rooms.get_mut(note_id) else { return };
+        match &message {
+            CollabMessage::Presence(p) => {
+                room.presences.insert(p.session.clone(), p.clone());
+            }
+            CollabMessage::Leave { session } => {
+                room.presences.remove(session);
+            }
+            CollabMessage::Changes { .. } => {}
+        }
+        // sem outros participantes o envio falha, e tudo bem
+        let _ = room.tx.send((from, message));
+    }
+
+    fn leave(&self, note_id: &str, member: u64, sessions: &[String]) {
+        for session in sessions {
+            self.publish(note_id, member, CollabMessage::Leave { session: session.clone() });
+        }
+        let mut rooms = self.lock();
+        if let Some(room) = rooms.get_mut(note_id) {
+            room.members -= 1;
+            if room.members == 0 {
+                rooms.remove(note_id);
+            }
+        }
+    }
+}
+
+/// `GET /api/notes/{id}/collab`: canal WebSocket da edição colaborativa.
+/// Leitores recebem as alterações, mas as que enviam são ignoradas.
+pub async fn join(
+    ws: WebSocketUpgrade,
+    State(db): State<Db>,
+    State(rooms): State<Rooms>,
+    Actor(user): Actor,
+    Path(id): Path<String>,
+) -> Result<Response, ApiError> {
+    let note = db.get(&id)?.ok_or(ApiError::NotFound)?;
+    let role = note.role_of(&user).ok_or(ApiError::Forbidden)?;
+    Ok(ws.on_upgrade(move |socket| session(socket, db, rooms, id, role.can_edit())))
+}
+
+async fn send(socket: &mut WebSocket, message: &CollabMessage) -> Result<(), ()> {
+    let text = serde_json::to_string(message).map_err(|_| ())?;
+    socket.send(Message::Text(text.into())).await.map_err(|_| ())
+}
+
+/// Envia todas as changes gravadas, como primeira mensagem ou após perder
+/// mensagens da sala.
+async fn send_all_changes(socket: &mut WebSocket, db: &Db, note_id: &str) -> Result<(), ()> {
+    let changes = db.changes(note_id).map_err(|e| eprintln!("Erro ao ler changes: {:?}", e))?;
+    send(socket, &CollabMessage::Changes { changes }).await
+}
+
+async fn session(mut socket: WebSocket, db: Db, rooms: Rooms, note_id: String, can_edit: bool) {
+    let (member, mut rx, presences) = rooms.enter(&note_id);
+    // presenças anunciadas por esta conexão, removidas quando ela fechar
+    let mut sessions: Vec<String> = Vec::new();
+
+    let mut connected = send_all_changes(&mut socket, &db, &note_id).await.is_ok();
+    for presence in presences {
+        if !connected {
+            break;
+        }
+        connected = send(&mut socket, &CollabMessage::Presence(presence)).await.is_ok();
+    }
+
+    while connected {
+        tokio::select! {
+            incoming = socket.recv() => {
+                let text = match incoming {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                };
+                let message = match serde_json::from_str::<CollabMessage>(text.as_str()) {
+                    Ok(message) => message,
+                    Err(e) => {
+                        eprintln!("Mensagem colaborativa inválida: {}", e);
+                        continue;
+                    }
+                };
+                match message {
+                    CollabMessage::Changes { .. } if !can_edit => {}
+                    CollabMessage::Changes { changes } => match db.add_changes(&note_id, &changes) {
+                        Ok(fresh) if !fresh.is_empty() => {
+                            rooms.publish(&note_id, member, CollabMessage::Changes { changes: fresh });
+                        }
+                        Ok(_) => {}
+                        Err(e) => eprintln!("Erro ao gravar changes: {:?}", e),
+                    },
+                    CollabMessage::Presence(presence) => {
+                        if !sessions.contains(&presence.session) {
+                            sessions.push(presence.session.clone());
+                        }
+                        rooms.publish(&note_id, member, CollabMessage::Presence(presence));
+                    }
+                    CollabMessage::Leave { session } => {
+                        sessions.retain(|s| *s != session);
+                        rooms.publish(&note_id, member, CollabMessage::Leave { session });
+                    }
+                }
+            }
+            outgoing = rx.recv() => {
+                connected = match outgoing {
+                    Ok((from, _)) if from == member => true,
+                    Ok((_, message)) => send(&mut socket, &message).await.is_ok(),
+                    Err(RecvError::Lagged(_)) => send_all_changes(&mut socket, &db, &note_id).await.is_ok(),
+                    Err(RecvError::Closed) => false,
+                };
+            }
+        }
+    }
+
+    rooms.leave(&note_id, member, &sessions);
+}
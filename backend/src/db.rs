@@ -0,0 +1,353 @@
+// src/db.rs
+use std::sync::{Arc, Mutex, MutexGuard};
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::error::ApiError;
+use notepad_models::{CollabChange, Note, Notebook};
+
+/// Conexão SQLite compartilhada entre os handlers.
+///
+/// A nota inteira é gravada como JSON na coluna `data`; `user_id` e
+/// `updated_at` ficam em colunas próprias para filtro e ordenação. As changes
+/// da edição colaborativa ficam em `note_changes`, uma linha por hash, e os
+/// cadernos em `notebooks`, também como JSON.
+#[derive(Clone)]
+pub struct Db {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl Db {
+    pub fn open(path: &str) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS notes (
+                id TEXT PRIMARY KEY,
+                user_id TEXT NOT NULL,
+                updated_at INTEGER NOT NULL,
+                data TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS notes_user_id ON notes (user_id);
+            CREATE TABLE IF NOT EXISTS note_changes (
+                note_id TEXT NOT NULL,
+                hash TEXT NOT NULL,
+                data BLOB NOT NULL,
+                seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                UNIQUE (note_id, hash)
+            );
+            CREATE TABLE IF NOT EXISTS notebooks (
+                id TEXT PRIMARY KEY,
+                user_id TEXT NOT NULL,
+                data TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS notebooks_user_id ON notebooks (user_id);",
+        )?;
+        Ok(Self { conn: Arc::new(Mutex::new(conn)) })
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        // um handler que entrou em pânico não invalida a conexão
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn create(&self, note: &Note) -> Result<String, ApiError> {
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        let mut stored = note.clone().upgrade()?;
+        stored.id = Some(id.clone());
+
+        self.conn().execute(
+            "INSERT INTO notes (id, user_id, updated_at, data) VALUES (?1, ?2, ?3, ?4)",
+            params![id, stored.user_id, stored.updated_at, serde_json::to_string(&stored)?],
+        )?;
+        Ok(id)
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<Note>, ApiError> {
+        let data: Option<String> = self.conn()
+            .query_row("SELECT data FROM notes WHERE id = ?1", params![id], |row| row.get(0))
+            .optional()?;
+        match data {
+            Some(d) => Ok(Some(serde_json::from_str::<Note>(&d)?.upgrade()?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn list(&self, user_id: &str) -> Result<Vec<Note>, ApiError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT data FROM notes WHERE user_id = ?1 ORDER BY updated_at DESC",
+        )?;
+        let rows = stmt.query_map(params![user_id], |row| row.get::<_, String>(0))?;
+
+        let mut notes = Vec::new();
+        for data in rows {
+            notes.push(serde_json::from_str::<Note>(&data?)?.upgrade()?);
+        }
+        Ok(notes)
+    }
+
+    /// Notas compartilhadas com `email` (como editor ou leitor).
+    pub fn list_shared(&self, email: &str) -> Result<Vec<Note>, ApiError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT data FROM notes
+             WHERE EXISTS (SELECT 1 FROM json_each(data, '$.editors') WHERE value = ?1)
+                OR EXISTS (SELECT 1 FROM json_each(data, '$.viewers') WHERE value = ?1)
+             ORDER BY updated_at DESC",
+        )?;
+        let rows = stmt.query_map(params![email], |row| row.get::<_, String>(0))?;
+
+        let mut notes = Vec::new();
+        for data in rows {
+            notes.push(serde_json::from_str::<Note>(&data?)?.upgrade()?);
+        }
+        Ok(notes)
+    }
+
+    /// Grava a nota se `note.revision` ainda for a revisão armazenada e
+    /// retorna a versão gravada; `None` se a nota não existe e
+    /// `ApiError::Conflict` se outra gravação chegou antes. `authorize`
+    /// recebe a versão armazenada e pode recusar a gravação.
+    pub fn update(
+        &self,
+        id: &str,
+        note: &Note,
+        authorize: impl FnOnce(&Note) -> Result<(), ApiError>,
+    ) -> Result<Option<Note>, ApiError> {
+        let mut stored = note.clone().upgrade()?;
+        stored.id = Some(id.to_string());
+        stored.revision = note.revision + 1;
+
+        // a leitura e a escrita acontecem sob o mesmo lock
+        let conn = self.conn();
+        let current: Option<String> = conn
+            .query_row("SELECT data FROM notes WHERE id = ?1", params![id], |row| row.get(0))
+            .optional()?;
+        let Some(current) = current else {
+            return Ok(None);
+        };
+        let current = serde_json::from_str::<Note>(&current)?.upgrade()?;
+        authorize(&current)?;
+        if current.revision != note.revision {
+            return Err(ApiError::Conflict);
+        }
+
+        conn.execute(
+            "UPDATE notes SET user_id = ?2, updated_at = ?3, data = ?4 WHERE id = ?1",
+            params![id, stored.user_id, stored.updated_at, serde_json::to_string(&stored)?],
+        )?;
+        Ok(Some(stored))
+    }
+
+    /// Retorna `false` se a nota não existe.
+    pub fn delete(&self, id: &str) -> Result<bool, ApiError> {
+        let conn = self.conn();
+        conn.execute("DELETE FROM note_changes WHERE note_id = ?1", params![id])?;
+        let changed = conn.execute("DELETE FROM notes WHERE id = ?1", params![id])?;
+        Ok(changed > 0)
+    }
+
+    /// Changes da edição colaborativa da nota, na ordem em que chegaram.
+    pub fn changes(&self, note_id: &str) -> Result<Vec<CollabChange>, ApiError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT hash, data FROM note_changes WHERE note_id = ?1 ORDER BY seq",
+        )?;
+        let rows = stmt.query_map(params![note_id], |row| {
+            Ok(CollabChange { hash: row.get(0)?, data: row.get(1)? })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Grava as changes ainda desconhecidas e retorna apenas essas.
+    pub fn add_changes(&self, note_id: &str, changes: &[CollabChange]) -> Result<Vec<CollabChange>, ApiError> {
+        let conn = self.conn();
+        let mut fresh = Vec::new();
+        for change in changes {
+            let inserted = conn.execute(
+                "INSERT OR IGNORE INTO note_changes (note_id, hash, data) VALUES (?1, ?2, ?3)",
+                params![note_id, change.hash, change.data],
+            )?;
+            if inserted > 0 {
+                fresh.push(change.clone());
+            }
+        }
+        Ok(fresh)
+    }
+
+    pub fn create_notebook(&self, notebook: &Notebook) -> Result<String, ApiError> {
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        let mut stored = notebook.clone();
+        stored.id = Some(id.clone());
+
+        self.conn().execute(
+            "INSERT INTO notebooks (id, user_id, data) VALUES (?1, ?2, ?3)",
+            params![id, stored.user_id, serde_json::to_string(&stored)?],
+        )?;
+        Ok(id)
+    }
+
+    pub fn get_notebook(&self, id: &str) -> Result<Option<Notebook>, ApiError> {
+        let data: Option<String> = self.conn()
+            .query_row("SELECT data FROM notebooks WHERE id = ?1", params![id], |row| row.get(0))
+            .optional()?;
+        Ok(data.map(|d| serde_json::from_str(&d)).transpose()?)
+    }
+
+    pub fn list_notebooks(&self, user_id: &str) -> Result<Vec<Notebook>, ApiError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT data FROM notebooks WHERE user_id = ?1")?;
+        let rows = stmt.query_map(params![user_id], |row| row.get::<_, String>(0))?;
+
+        let mut notebooks = Vec::new();
+        for data in rows {
+            notebooks.push(serde_json::from_str(&data?)?);
+        }
+        Ok(notebooks)
+    }
+
+    /// Retorna `false` se o caderno não existe.
+    pub fn update_notebook(&self, id: &str, notebook: &Notebook) -> Result<bool, ApiError> {
+        let mut stored = notebook.clone();
+        stored.id = Some(id.to_string());
+        let changed = self.conn().execute(
+            "UPDATE notebooks SET user_id = ?2, data = ?3 WHERE id = ?1",
+            params![id, stored.user_id, serde_json::to_string(&stored)?],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// Exclui o caderno sem perder o conteúdo: os cadernos e as notas dentro
+    /// dele sobem para o caderno pai, na mesma transação. Retorna as notas
+    /// movidas, já com a nova revisão; `None` se o caderno não existe.
+    pub fn delete_notebook(&self, id: &str) -> Result<Option<Vec<Note>>, ApiError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+
+        let data: Option<String> = tx
+            .query_row("SELECT data FROM notebooks WHERE id = ?1", params![id], |row| row.get(0))
+            .optional()?;
+        let Some(data) = data else {
+            return Ok(None);
+        };
+        let notebook: Notebook = serde_json::from_str(&data)?;
+
+        let children: Vec<(String, String)> = {
+            let mut stmt = tx.prepare("SELECT id, data FROM notebooks WHERE user_id = ?1")?;
+            let rows = stmt.query_map(params![notebook.user_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.collect::<Result<_, _>>()?
+        };
+        for (child_id, data) in children {
+            let mut child: Notebook = serde_json::from_str(&data)?;
+            if child.parent_id.as_deref() != Some(id) {
+                continue;
+            }
+            child.parent_id = notebook.parent_id.clone();
+            tx.execute(
+                "UPDATE notebooks SET data = ?2 WHERE id = ?1",
+                params![child_id, serde_json::to_string(&child)?],
+            )?;
+        }
+
+        let notes: Vec<String> = {
+            let mut stmt = tx.prepare("SELECT data FROM notes WHERE user_id = ?1")?;
+            let rows = stmt.query_map(params![notebook.user_id], |row| row.get(0))?;
+            rows.collect::<Result<_, _>>()?
+        };
+        let mut moved = Vec::new();
+        for data in notes {
+            let mut note = serde_json::from_str::<Note>(&data)?.upgrade()?;
+            if note.notebook_id.as_deref() != Some(id) {
+                continue;
+            }
+            note.notebook_id = notebook.parent_id.clone();
+            // quem ainda tem a revisão anterior recebe conflito em vez de
+            // devolver a nota ao caderno excluído
+            note.revision += 1;
+            tx.execute(
+                "UPDATE notes SET data = ?2 WHERE id = ?1",
+                params![note.id, serde_json::to_string(&note)?],
+            )?;
+            moved.push(note);
+        }
+
+        tx.execute("DELETE FROM notebooks WHERE id = ?1", params![id])?;
+        tx.commit()?;
+        Ok(Some(moved))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notepad_models::FixedClock;
+
+    fn note(user_id: &str) -> Note {
+        Note::new_with_clock(&FixedClock(1_000), "Título".to_string(), "texto".to_string(),
+            user_id.to_string(), "Arial".to_string(), "black".to_string(), "white".to_string(), None)
+    }
+
+    #[test]
+    fn update_bumps_revision() {
+        let db = Db::open(":memory:").unwrap();
+        let id = db.create(&note("u1")).unwrap();
+        let mut stored = db.get(&id).unwrap().unwrap();
+        stored.content = "novo".to_string();
+
+        let saved = db.update(&id, &stored, |_| Ok(())).unwrap().unwrap();
+        assert_eq!(saved.revision, 1);
+        assert_eq!(db.get(&id).unwrap().unwrap().content, "novo");
+    }
+
+    #[test]
+    fn stale_revision_is_a_conflict() {
+        let db = Db::open(":memory:").unwrap();
+        let id = db.create(&note("u1")).unwrap();
+        let read = db.get(&id).unwrap().unwrap();
+
+        let mut first = read.clone();
+        first.content = "primeira".to_string();
+        db.update(&id, &first, |_| Ok(())).unwrap();
+
+        let mut second = read;
+        second.content = "segunda".to_string();
+        assert!(matches!(db.update(&id, &second, |_| Ok(())), Err(ApiError::Conflict)));
+        assert_eq!(db.get(&id).unwrap().unwrap().content, "primeira");
+    }
+
+    #[test]
+    fn update_of_missing_note_is_none() {
+        let db = Db::open(":memory:").unwrap();
+        assert!(db.update("nada", &note("u1"), |_| Ok(())).unwrap().is_none());
+    }
+
+    #[test]
+    fn delete_notebook_moves_contents_to_parent() {
+        let db = Db::open(":memory:").unwrap();
+        let root = db.create_notebook(&Notebook::new("Raiz".to_string(), "u1".to_string(), None, 0)).unwrap();
+        let doomed = db.create_notebook(&Notebook::new("Velho".to_string(), "u1".to_string(), Some(root.clone()), 0)).unwrap();
+        let child = db.create_notebook(&Notebook::new("Filho".to_string(), "u1".to_string(), Some(doomed.clone()), 0)).unwrap();
+
+        let mut inside = note("u1");
+        inside.notebook_id = Some(doomed.clone());
+        let inside = db.create(&inside).unwrap();
+        let mut outside = note("u1");
+        outside.notebook_id = Some(child.clone());
+        let outside = db.create(&outside).unwrap();
+
+        let moved = db.delete_notebook(&doomed).unwrap().unwrap();
+        assert_eq!(moved.len(), 1);
+        assert_eq!(moved[0].id.as_deref(), Some(inside.as_str()));
+
+        assert!(db.get_notebook(&doomed).unwrap().is_none());
+        assert_eq!(db.get_notebook(&child).unwrap().unwrap().parent_id, Some(root.clone()));
+        let stored = db.get(&inside).unwrap().unwrap();
+        assert_eq!(stored.notebook_id, Some(root));
+        assert_eq!(stored.revision, 1);
+        assert_eq!(db.get(&outside).unwrap().unwrap().notebook_id, Some(child));
+    }
+
+    #[test]
+    fn delete_of_missing_notebook_is_none() {
+        let db = Db::open(":memory:").unwrap();
+        assert!(db.delete_notebook("nada").unwrap().is_none());
+    }
+}
@@ -0,0 +1,69 @@
+// src/error.rs
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde_json::json;
+use notepad_models::SchemaError;
+
+#[derive(Debug)]
+pub enum ApiError {
+    NotFound,
+    NotebookNotFound,
+    /// A requisição não identifica o usuário.
+    Unauthorized,
+    /// O usuário não tem permissão para a operação.
+    Forbidden,
+    /// A revisão enviada não é mais a armazenada.
+    Conflict,
+    BadRequest(String),
+    Database(rusqlite::Error),
+    Serialization(serde_json::Error),
+    Schema(SchemaError),
+    /// As chaves para verificar o token de ID não puderam ser obtidas.
+    AuthKeys(reqwest::Error),
+}
+
+impl From<rusqlite::Error> for ApiError {
+    fn from(e: rusqlite::Error) -> Self {
+        ApiError::Database(e)
+    }
+}
+
+impl From<serde_json::Error> for ApiError {
+    fn from(e: serde_json::Error) -> Self {
+        ApiError::Serialization(e)
+    }
+}
+
+impl From<SchemaError> for ApiError {
+    fn from(e: SchemaError) -> Self {
+        ApiError::Schema(e)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::NotFound => (StatusCode::NOT_FOUND, "Nota não encontrada".to_string()),
+            ApiError::NotebookNotFound => (StatusCode::NOT_FOUND, "Caderno não encontrado".to_string()),
+            ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "Usuário não identificado".to_string()),
+            ApiError::Forbidden => (StatusCode::FORBIDDEN, "Sem permissão para esta nota".to_string()),
+            ApiError::Conflict => (StatusCode::CONFLICT, "A nota foi alterada por outra sessão".to_string()),
+            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            ApiError::Database(e) => {
+                eprintln!("Erro no banco: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Erro no banco de dados".to_string())
+            }
+            ApiError::Serialization(e) => {
+                eprintln!("Erro de serialização: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Documento de nota inválido".to_string())
+            }
+            ApiError::Schema(e) => (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()),
+            ApiError::AuthKeys(e) => {
+                eprintln!("Erro ao obter as chaves do Firebase: {}", e);
+                (StatusCode::SERVICE_UNAVAILABLE, "Não foi possível verificar a sessão".to_string())
+            }
+        };
+        (status, Json(json!({ "error": message }))).into_response()
+    }
+}
@@ -0,0 +1,42 @@
+// src/main.rs
+use std::net::SocketAddr;
+use tower_http::cors::CorsLayer;
+
+mod auth;
+mod collab;
+mod db;
+mod error;
+mod routes;
+
+use auth::Auth;
+use db::Db;
+
+#[tokio::main]
+async fn main() {
+    let db_path = std::env::var("NOTEPAD_DB").unwrap_or_else(|_| "notes.db".to_string());
+    let port: u16 = std::env::var("PORT")
+        .ok()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(3000);
+
+    let db = Db::open(&db_path).expect("Erro ao abrir o banco SQLite");
+    let auth = Auth::from_env().unwrap_or_else(|e| panic!("Autenticação não configurada: {}", e));
+    if !matches!(auth, Auth::Firebase(_)) {
+        println!("Aviso: identidade lida dos cabeçalhos x-user-*, sem verificação (dev-auth)");
+    }
+
+    // O frontend roda em outra origem (trunk serve em :8080)
+    let app = routes::router(db, auth).layer(CorsLayer::permissive());
+
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    let listener = tokio::net::TcpListener::bind(addr).await
+        .expect("Erro ao abrir a porta");
+    println!("Servidor de notas em http://{} (banco: {})", addr, db_path);
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await
+        .expect("Erro no servidor");
+}
@@ -0,0 +1,398 @@
+// src/routes.rs
+use std::collections::HashSet;
+use axum::extract::{FromRef, Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::{get, put};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use crate::auth::{Actor, Auth};
+use crate::collab::{self, Rooms};
+use crate::db::Db;
+use crate::error::ApiError;
+use notepad_models::{normalize_email, Note, Notebook, Role};
+
+#[derive(Clone)]
+struct AppState {
+    db: Db,
+    rooms: Rooms,
+    auth: Auth,
+}
+
+impl FromRef<AppState> for Db {
+    fn from_ref(state: &AppState) -> Self {
+        state.db.clone()
+    }
+}
+
+impl FromRef<AppState> for Rooms {
+    fn from_ref(state: &AppState) -> Self {
+        state.rooms.clone()
+    }
+}
+
+impl FromRef<AppState> for Auth {
+    fn from_ref(state: &AppState) -> Self {
+        state.auth.clone()
+    }
+}
+
+pub fn router(db: Db, auth: Auth) -> Router {
+    Router::new()
+        .route("/api/notes", get(list_notes).post(create_note))
+        .route("/api/notes/{id}", get(get_note).put(update_note).delete(delete_note))
+        .route("/api/notes/{id}/collab", get(collab::join))
+        .route("/api/notebooks", get(list_notebooks).post(create_notebook))
+        .route("/api/notebooks/{id}", put(update_notebook).delete(delete_notebook))
+        .with_state(AppState { db, rooms: Rooms::default(), auth })
+}
+
+#[derive(Deserialize)]
+struct ListQuery {
+    user_id: Option<String>,
+    shared_with: Option<String>,
+}
+
+/// `?user_id=` lista as notas do próprio usuário; `?shared_with=` as
+/// compartilhadas com o e-mail dele.
+async fn list_notes(
+    State(db): State<Db>,
+    Actor(user): Actor,
+    Query(q): Query<ListQuery>,
+) -> Result<Json<Vec<Note>>, ApiError> {
+    match (q.user_id, q.shared_with) {
+        (Some(uid), None) if uid == user.uid => Ok(Json(db.list(&uid)?)),
+        (None, Some(email)) if normalize_email(&email) == normalize_email(&user.email) => {
+            Ok(Json(db.list_shared(&normalize_email(&email))?))
+        }
+        (None, None) | (Some(_), Some(_)) => {
+            Err(ApiError::BadRequest("Informe user_id ou shared_with".to_string()))
+        }
+        _ => Err(ApiError::Forbidden),
+    }
+}
+
+async fn create_note(
+    State(db): State<Db>,
+    Actor(user): Actor,
+    Json(note): Json<Note>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    if note.user_id.is_empty() {
+        return Err(ApiError::BadRequest("Usuário da nota é obrigatório".to_string()));
+    }
+    if note.user_id != user.uid {
+        return Err(ApiError::Forbidden);
+    }
+    if let Some(notebook_id) = &note.notebook_id {
+        own_notebook(&db, &user.uid, notebook_id)?;
+    }
+    let id = db.create(&note)?;
+    Ok((StatusCode::CREATED, Json(json!({ "id": id }))))
+}
+
+async fn get_note(State(db): State<Db>, Actor(user): Actor, Path(id): Path<String>) -> Result<Json<Note>, ApiError> {
+    let note = db.get(&id)?.ok_or(ApiError::NotFound)?;
+    note.role_of(&user).ok_or(ApiError::Forbidden)?;
+    Ok(Json(note))
+}
+
+/// O dono pode alterar tudo menos o próprio dono; editores alteram o conteúdo,
+/// mas não o compartilhamento nem o caderno (os cadernos são do dono).
+async fn update_note(
+    State(db): State<Db>,
+    Actor(user): Actor,
+    Path(id): Path<String>,
+    Json(note): Json<Note>,
+) -> Result<Json<Note>, ApiError> {
+    // lido antes: `authorize` roda com o banco travado
+    let target = match &note.notebook_id {
+        Some(notebook_id) => db.get_notebook(notebook_id)?,
+        None => None,
+    };
+    let authorize = |current: &Note| match current.role_of(&user) {
+        Some(Role::Owner) if note.user_id == current.user_id => match (&note.notebook_id, &target) {
+            (None, _) => Ok(()),
+            (Some(_), None) => Err(ApiError::NotebookNotFound),
+            (Some(_), Some(notebook)) if notebook.user_id == current.user_id => Ok(()),
+            _ => Err(ApiError::Forbidden),
+        },
+        Some(Role::Editor)
+            if current.same_acl(&note) && current.same_status(&note) && current.notebook_id == note.notebook_id =>
+        {
+            Ok(())
+        }
+        _ => Err(ApiError::Forbidden),
+    };
+    db.update(&id, &note, authorize)?.map(Json).ok_or(ApiError::NotFound)
+}
+
+async fn delete_note(State(db): State<Db>, Actor(user): Actor, Path(id): Path<String>) -> Result<StatusCode, ApiError> {
+    let note = db.get(&id)?.ok_or(ApiError::NotFound)?;
+    if note.role_of(&user) != Some(Role::Owner) {
+        return Err(ApiError::Forbidden);
+    }
+    if db.delete(&id)? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::NotFound)
+    }
+}
+
+#[derive(Deserialize)]
+struct NotebookQuery {
+    user_id: String,
+}
+
+/// Cadernos são sempre do próprio usuário.
+async fn list_notebooks(
+    State(db): State<Db>,
+    Actor(user): Actor,
+    Query(q): Query<NotebookQuery>,
+) -> Result<Json<Vec<Notebook>>, ApiError> {
+    if q.user_id != user.uid {
+        return Err(ApiError::Forbidden);
+    }
+    Ok(Json(db.list_notebooks(&q.user_id)?))
+}
+
+async fn create_notebook(
+    State(db): State<Db>,
+    Actor(user): Actor,
+    Json(notebook): Json<Notebook>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    if notebook.user_id != user.uid {
+        return Err(ApiError::Forbidden);
+    }
+    if notebook.name.trim().is_empty() {
+        return Err(ApiError::BadRequest("Nome do caderno é obrigatório".to_string()));
+    }
+    check_parent(&db, &user.uid, None, notebook.parent_id.as_deref())?;
+    let id = db.create_notebook(&notebook)?;
+    Ok((StatusCode::CREATED, Json(json!({ "id": id }))))
+}
+
+/// Checa se o caderno existe e é do usuário.
+fn own_notebook(db: &Db, user_id: &str, id: &str) -> Result<Notebook, ApiError> {
+    let notebook = db.get_notebook(id)?.ok_or(ApiError::NotebookNotFound)?;
+    if notebook.user_id != user_id {
+        return Err(ApiError::Forbidden);
+    }
+    Ok(notebook)
+}
+
+/// O caderno pai tem de ser do usuário e não pode estar dentro do próprio
+/// caderno `id` (nem ser ele), senão a árvore vira um ciclo.
+fn check_parent(db: &Db, user_id: &str, id: Option<&str>, parent_id: Option<&str>) -> Result<(), ApiError> {
+    let mut seen = HashSet::new();
+    let mut next = parent_id.map(str::to_string);
+    while let Some(current) = next {
+        if Some(current.as_str()) == id || !seen.insert(current.clone()) {
+            return Err(ApiError::BadRequest("Um caderno não pode ficar dentro de si mesmo".to_string()));
+        }
+        next = own_notebook(db, user_id, &current)?.parent_id;
+    }
+    Ok(())
+}
+
+async fn update_notebook(
+    State(db): State<Db>,
+    Actor(user): Actor,
+    Path(id): Path<String>,
+    Json(notebook): Json<Notebook>,
+) -> Result<StatusCode, ApiError> {
+    own_notebook(&db, &user.uid, &id)?;
+    if notebook.user_id != user.uid {
+        return Err(ApiError::Forbidden);
+    }
+    check_parent(&db, &user.uid, Some(&id), notebook.parent_id.as_deref())?;
+    if db.update_notebook(&id, &notebook)? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::NotebookNotFound)
+    }
+}
+
+/// Responde com as notas que subiram para o caderno pai.
+async fn delete_notebook(State(db): State<Db>, Actor(user): Actor, Path(id): Path<String>) -> Result<Json<Vec<Note>>, ApiError> {
+    own_notebook(&db, &user.uid, &id)?;
+    db.delete_notebook(&id)?.map(Json).ok_or(ApiError::NotebookNotFound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notepad_models::{FixedClock, User};
+
+    fn user(uid: &str, email: &str) -> User {
+        User { uid: uid.to_string(), email: email.to_string() }
+    }
+
+    /// Nota do "dono", compartilhada com uma editora e um leitor.
+    fn shared_note(db: &Db) -> String {
+        let mut note = Note::new_with_clock(&FixedClock(0), "t".to_string(), "c".to_string(), "dono".to_string(),
+            String::new(), String::new(), String::new(), None);
+        note.owner_email = "dono@example.com".to_string();
+        note.share("editora@example.com", Role::Editor).unwrap();
+        note.share("leitor@example.com", Role::Viewer).unwrap();
+        db.create(&note).unwrap()
+    }
+
+    fn editora() -> User {
+        user("u-editora", "editora@example.com")
+    }
+
+    fn leitor() -> User {
+        user("u-leitor", "leitor@example.com")
+    }
+
+    fn estranho() -> User {
+        user("u-estranho", "estranho@example.com")
+    }
+
+    async fn edit(db: &Db, as_user: User, id: &str, change: impl FnOnce(&mut Note)) -> Result<Json<Note>, ApiError> {
+        let mut note = db.get(id).unwrap().unwrap();
+        change(&mut note);
+        update_note(State(db.clone()), Actor(as_user), Path(id.to_string()), Json(note)).await
+    }
+
+    #[tokio::test]
+    async fn get_requires_a_role() {
+        let db = Db::open(":memory:").unwrap();
+        let id = shared_note(&db);
+        assert!(get_note(State(db.clone()), Actor(leitor()), Path(id.clone())).await.is_ok());
+        let result = get_note(State(db.clone()), Actor(estranho()), Path(id)).await;
+        assert!(matches!(result, Err(ApiError::Forbidden)));
+    }
+
+    #[tokio::test]
+    async fn only_owner_and_editors_update() {
+        let db = Db::open(":memory:").unwrap();
+        let id = shared_note(&db);
+
+        let saved = edit(&db, editora(), &id, |n| n.content = "da editora".to_string()).await.unwrap();
+        assert_eq!(saved.content, "da editora");
+        let result = edit(&db, leitor(), &id, |n| n.content = "do leitor".to_string()).await;
+        assert!(matches!(result, Err(ApiError::Forbidden)));
+        let result = edit(&db, estranho(), &id, |n| n.content = "de fora".to_string()).await;
+        assert!(matches!(result, Err(ApiError::Forbidden)));
+        assert_eq!(db.get(&id).unwrap().unwrap().content, "da editora");
+    }
+
+    #[tokio::test]
+    async fn editors_cannot_change_sharing_status_or_owner() {
+        let db = Db::open(":memory:").unwrap();
+        let id = shared_note(&db);
+
+        let result = edit(&db, editora(), &id, |n| n.share("amigo@example.com", Role::Editor).unwrap()).await;
+        assert!(matches!(result, Err(ApiError::Forbidden)));
+        let result = edit(&db, editora(), &id, |n| n.archived = true).await;
+        assert!(matches!(result, Err(ApiError::Forbidden)));
+        let result = edit(&db, editora(), &id, |n| n.user_id = "u-editora".to_string()).await;
+        assert!(matches!(result, Err(ApiError::Forbidden)));
+        let result = edit(&db, editora(), &id, |n| n.notebook_id = Some("caderno-alheio".to_string())).await;
+        assert!(matches!(result, Err(ApiError::Forbidden)));
+        // nem o dono passa a nota para outra conta
+        let result = edit(&db, user("dono", "dono@example.com"), &id, |n| n.user_id = "u-editora".to_string()).await;
+        assert!(matches!(result, Err(ApiError::Forbidden)));
+    }
+
+    #[tokio::test]
+    async fn only_owner_deletes() {
+        let db = Db::open(":memory:").unwrap();
+        let id = shared_note(&db);
+
+        for other in [editora(), leitor(), estranho()] {
+            let result = delete_note(State(db.clone()), Actor(other), Path(id.clone())).await;
+            assert!(matches!(result, Err(ApiError::Forbidden)));
+        }
+        let result = delete_note(State(db.clone()), Actor(user("dono", "")), Path(id.clone())).await;
+        assert_eq!(result.unwrap(), StatusCode::NO_CONTENT);
+        assert!(db.get(&id).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn create_and_list_are_for_the_actor_only() {
+        let db = Db::open(":memory:").unwrap();
+        shared_note(&db);
+
+        let forged = Note::new_with_clock(&FixedClock(0), "t".to_string(), String::new(), "dono".to_string(),
+            String::new(), String::new(), String::new(), None);
+        let result = create_note(State(db.clone()), Actor(estranho()), Json(forged)).await;
+        assert!(matches!(result, Err(ApiError::Forbidden)));
+
+        let query = ListQuery { user_id: Some("dono".to_string()), shared_with: None };
+        let result = list_notes(State(db.clone()), Actor(estranho()), Query(query)).await;
+        assert!(matches!(result, Err(ApiError::Forbidden)));
+
+        let query = ListQuery { user_id: None, shared_with: Some("Leitor@example.com".to_string()) };
+        let Json(shared) = list_notes(State(db.clone()), Actor(leitor()), Query(query)).await.unwrap();
+        assert_eq!(shared.len(), 1);
+    }
+
+    fn notebook(db: &Db, user_id: &str, parent_id: Option<&str>) -> String {
+        db.create_notebook(&Notebook::new("c".to_string(), user_id.to_string(), parent_id.map(str::to_string), 0)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn notes_only_go_into_the_owners_notebooks() {
+        let db = Db::open(":memory:").unwrap();
+        let mine = notebook(&db, "dono", None);
+        let theirs = notebook(&db, "u-estranho", None);
+
+        let mut note = Note::new_with_clock(&FixedClock(0), "t".to_string(), String::new(), "dono".to_string(),
+            String::new(), String::new(), String::new(), None);
+        note.notebook_id = Some(theirs.clone());
+        let result = create_note(State(db.clone()), Actor(user("dono", "")), Json(note.clone())).await;
+        assert!(matches!(result, Err(ApiError::Forbidden)));
+        note.notebook_id = Some("nada".to_string());
+        let result = create_note(State(db.clone()), Actor(user("dono", "")), Json(note.clone())).await;
+        assert!(matches!(result, Err(ApiError::NotebookNotFound)));
+        note.notebook_id = Some(mine.clone());
+        assert!(create_note(State(db.clone()), Actor(user("dono", "")), Json(note)).await.is_ok());
+
+        let id = shared_note(&db);
+        let result = edit(&db, user("dono", ""), &id, |n| n.notebook_id = Some(theirs)).await;
+        assert!(matches!(result, Err(ApiError::Forbidden)));
+        let saved = edit(&db, user("dono", ""), &id, |n| n.notebook_id = Some(mine.clone())).await.unwrap();
+        assert_eq!(saved.notebook_id, Some(mine));
+    }
+
+    #[tokio::test]
+    async fn notebooks_only_nest_under_the_users_notebooks() {
+        let db = Db::open(":memory:").unwrap();
+        let theirs = notebook(&db, "u-estranho", None);
+
+        let child = Notebook::new("c".to_string(), "dono".to_string(), Some(theirs.clone()), 0);
+        let result = create_notebook(State(db.clone()), Actor(user("dono", "")), Json(child)).await;
+        assert!(matches!(result, Err(ApiError::Forbidden)));
+
+        let mine = notebook(&db, "dono", None);
+        let mut moved = db.get_notebook(&mine).unwrap().unwrap();
+        moved.parent_id = Some(theirs);
+        let result = update_notebook(State(db.clone()), Actor(user("dono", "")), Path(mine), Json(moved)).await;
+        assert!(matches!(result, Err(ApiError::Forbidden)));
+    }
+
+    #[tokio::test]
+    async fn notebooks_cannot_move_into_their_own_subtree() {
+        let db = Db::open(":memory:").unwrap();
+        let root = notebook(&db, "dono", None);
+        let child = notebook(&db, "dono", Some(&root));
+        let grandchild = notebook(&db, "dono", Some(&child));
+
+        for parent in [&root, &grandchild] {
+            let mut moved = db.get_notebook(&root).unwrap().unwrap();
+            moved.parent_id = Some(parent.clone());
+            let result = update_notebook(State(db.clone()), Actor(user("dono", "")), Path(root.clone()), Json(moved)).await;
+            assert!(matches!(result, Err(ApiError::BadRequest(_))));
+        }
+        assert_eq!(db.get_notebook(&root).unwrap().unwrap().parent_id, None);
+
+        // subir um caderno para fora da própria árvore continua valendo
+        let mut moved = db.get_notebook(&grandchild).unwrap().unwrap();
+        moved.parent_id = Some(root.clone());
+        let result = update_notebook(State(db.clone()), Actor(user("dono", "")), Path(grandchild.clone()), Json(moved)).await;
+        assert_eq!(result.unwrap(), StatusCode::NO_CONTENT);
+        assert_eq!(db.get_notebook(&grandchild).unwrap().unwrap().parent_id, Some(root));
+    }
+}
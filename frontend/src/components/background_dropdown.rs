@@ -8,14 +8,12 @@ pub struct BackgroundDropdownProps {
 
 #[function_component(BackgroundDropdown)]
 pub fn background_dropdown(props: &BackgroundDropdownProps) -> Html {
-    let colors = vec![
-        ("white", "Branco"),
-        ("lightgray", "Cinza Claro"),
-        ("black", "Preto"),
-        ("lightblue", "Azul Claro"),
-        ("lightcoral", "Vermelho Claro"),
-        ("lightgreen", "Verde Claro"),
-    ];
+    let colors = [("white", "Branco"),
+        ("lightgray", "Cinza Claro"),
+        ("black", "Preto"),
+        ("lightblue", "Azul Claro"),
+        ("lightcoral", "Vermelho Claro"),
+        ("lightgreen", "Verde Claro")];
 
     let is_open = use_state(|| false);
     
@@ -50,7 +48,7 @@ pub fn background_dropdown(props: &BackgroundDropdownProps) -> Html {
                             <button
                                 onclick={Callback::from(move |_| on_select.emit(color_code.to_string()))}
                                 class="dropdown-item"
-                                style={format!("display:flex;align-items:center;gap:8px;")}
+                                style={"display:flex;align-items:center;gap:8px;".to_string()}
                             >
                                 <span class="swatch" style={format!("background:{};", color_code)}></span>
                                 <span>{ color_name }</span>
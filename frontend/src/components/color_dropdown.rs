@@ -8,14 +8,12 @@ pub struct ColorDropdownProps {
 
 #[function_component(ColorDropdown)]
 pub fn color_dropdown(props: &ColorDropdownProps) -> Html {
-    let colors = vec![
-        ("black", "Preto"),
-        ("gray", "Cinza"),
-        ("white", "Branco"),
-        ("blue", "Azul"),
-        ("red", "Vermelho"),
-        ("green", "Verde"),
-    ];
+    let colors = [("black", "Preto"),
+        ("gray", "Cinza"),
+        ("white", "Branco"),
+        ("blue", "Azul"),
+        ("red", "Vermelho"),
+        ("green", "Verde")];
 
     let is_open = use_state(|| false);
     
@@ -50,7 +48,7 @@ pub fn color_dropdown(props: &ColorDropdownProps) -> Html {
                             <button
                                 onclick={Callback::from(move |_| on_select.emit(color_code.to_string()))}
                                 class="dropdown-item"
-                                style={format!("display:flex;align-items:center;gap:8px;")}
+                                style={"display:flex;align-items:center;gap:8px;".to_string()}
                             >
                                 <span class="swatch" style={format!("background:{};", color_code)}></span>
                                 <span>{ color_name }</span>
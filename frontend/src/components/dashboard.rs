@@ -1,23 +1,164 @@
 // src/components/dashboard.rs
+use std::collections::{BTreeSet, HashMap};
+use std::rc::Rc;
 use yew::prelude::*;
-use pulldown_cmark::{Parser, Options, html};
-use ammonia::clean;
+use pulldown_cmark::{CowStr, Event, Parser, html};
 use yew_router::prelude::*;
 use wasm_bindgen_futures::spawn_local;
-use crate::services::{auth::AuthService, notes::NotesService};
-use crate::models::note::Note;
-use crate::components::note_editor::NoteEditor;
+use crate::services::{auth::AuthService, error::ServiceError, notebooks::NotebooksService, notes::NotesService, store::NoteChange};
+use crate::services::sync::{SyncService, SyncState, SyncStatus};
+use notepad_models::{Clock, Note, NoteStatus, Notebook, NotebookTree, Role, SystemClock, TagMatch, User, TRASH_RETENTION_DAYS};
+use crate::components::note_editor::{clicked_note, clicked_task, NoteEditor, SaveRequest};
+use crate::components::notebook_list::{NotebookList, NOTE_DRAG_FORMAT};
+use crate::text::markdown;
+use crate::text::search::{self, SearchIndex};
+use crate::text::wiki::{self, NoteRef};
 use crate::Route;
 
+/// Notas do usuário, mantidas pelas alterações recebidas da assinatura.
+#[derive(Default, PartialEq)]
+struct NoteList {
+    notes: Vec<Note>,
+}
+
+impl Reducible for NoteList {
+    type Action = Vec<NoteChange>;
+
+    fn reduce(self: Rc<Self>, changes: Self::Action) -> Rc<Self> {
+        let mut notes = self.notes.clone();
+        for change in changes {
+            match change {
+                NoteChange::Added(note) | NoteChange::Modified(note) => {
+                    match notes.iter().position(|n| n.id == note.id) {
+                        Some(pos) => notes[pos] = note,
+                        None => notes.push(note),
+                    }
+                }
+                NoteChange::Removed(id) => notes.retain(|n| n.id.as_deref() != Some(id.as_str())),
+            }
+        }
+        notes.sort_by_key(|n| std::cmp::Reverse(n.updated_at));
+        Rc::new(NoteList { notes })
+    }
+}
+
+/// Ações sobre uma nota do próprio usuário na barra lateral.
+#[derive(Clone, Copy, PartialEq)]
+enum NoteAction {
+    Pin,
+    Unpin,
+    Archive,
+    Unarchive,
+    Trash,
+    Restore,
+    Purge,
+}
+
+impl NoteAction {
+    /// Ações disponíveis na lista onde a nota aparece.
+    fn for_note(note: &Note) -> &'static [NoteAction] {
+        match note.status() {
+            NoteStatus::Active if note.pinned => &[NoteAction::Unpin, NoteAction::Archive, NoteAction::Trash],
+            NoteStatus::Active => &[NoteAction::Pin, NoteAction::Archive, NoteAction::Trash],
+            NoteStatus::Archived => &[NoteAction::Unarchive, NoteAction::Trash],
+            NoteStatus::Trashed => &[NoteAction::Restore, NoteAction::Purge],
+        }
+    }
+
+    fn icon(self) -> &'static str {
+        match self {
+            NoteAction::Pin | NoteAction::Unpin => "📌",
+            NoteAction::Archive | NoteAction::Unarchive => "🗄️",
+            NoteAction::Trash => "🗑️",
+            NoteAction::Restore => "♻️",
+            NoteAction::Purge => "❌",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            NoteAction::Pin => "Fixar no topo",
+            NoteAction::Unpin => "Desafixar",
+            NoteAction::Archive => "Arquivar",
+            NoteAction::Unarchive => "Desarquivar",
+            NoteAction::Trash => "Mover para a lixeira",
+            NoteAction::Restore => "Restaurar",
+            NoteAction::Purge => "Excluir para sempre",
+        }
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct DashboardProps {
+    /// Caderno aberto (`/dashboard/notebook/:id`); `None` mostra todas as notas.
+    #[prop_or_default]
+    pub notebook_id: Option<String>,
+    /// Lista mostrada: notas, arquivadas (`/dashboard/archived`) ou lixeira
+    /// (`/dashboard/trash`).
+    #[prop_or_default]
+    pub view: NoteStatus,
+    /// Nota aberta no editor (`/dashboard/note/:id`).
+    #[prop_or_default]
+    pub note_id: Option<String>,
+    /// Editor de uma nota nova (`/dashboard/new`).
+    #[prop_or_default]
+    pub new_note: bool,
+}
+
 #[function_component(Dashboard)]
-pub fn dashboard() -> Html {
+pub fn dashboard(props: &DashboardProps) -> Html {
     let navigator = use_navigator().unwrap();
-    let notes = use_state(|| Vec::<Note>::new());
-    let selected_note = use_state(|| None::<Note>);
+    let notes = use_reducer(NoteList::default);
+    let shared_notes = use_reducer(NoteList::default);
+    let notebooks = use_state(Vec::<Notebook>::new);
+    // busca: o índice acompanha as alterações recebidas das assinaturas
+    let index = use_mut_ref(SearchIndex::default);
+    let query = use_state(String::new);
+    let tag_filter = use_state(Vec::<String>::new);
+    let tag_match = use_state(TagMatch::default);
     let loading = use_state(|| true);
-    let user = use_state(|| AuthService::get_current_user());
-    let show_editor = use_state(|| false);
-    
+    let user = use_state(AuthService::get_current_user);
+    let sync_state = use_state(SyncService::state);
+    // versão remota que impediu o último salvamento
+    let conflict = use_state(|| None::<Note>);
+
+    // Com o editor aberto a barra lateral continua na lista de onde a nota
+    // foi aberta (caderno e view da última rota de lista)
+    let editing = props.note_id.is_some() || props.new_note;
+    let list_context = use_state(|| (props.notebook_id.clone(), props.view));
+    {
+        let list_context = list_context.clone();
+        let context = (props.notebook_id.clone(), props.view);
+        use_effect_with((context, editing), move |(context, editing)| {
+            if !*editing && *list_context != *context {
+                list_context.set(context.clone());
+            }
+            || ()
+        });
+    }
+    let (notebook_id, view) = if editing {
+        (*list_context).clone()
+    } else {
+        (props.notebook_id.clone(), props.view)
+    };
+    let list_route = list_route(notebook_id.clone(), view);
+
+    // o conflito pendente é da nota que estava aberta
+    {
+        let conflict = conflict.clone();
+        use_effect_with(props.note_id.clone(), move |_| {
+            conflict.set(None);
+            || ()
+        });
+    }
+
+    // A nota da rota, como está na lista; o editor guarda a própria cópia ao abrir
+    let selected_note = props.note_id.as_deref().and_then(|id| {
+        notes.notes.iter()
+            .chain(shared_notes.notes.iter())
+            .find(|n| n.id.as_deref() == Some(id))
+            .cloned()
+    });
     
     // Verificar autenticação
     {
@@ -31,35 +172,223 @@ pub fn dashboard() -> Html {
         });
     }
     
-    // Carregar notas
+    // Acompanhar notas em tempo real; a assinatura é cancelada ao desmontar
     {
         let notes = notes.clone();
         let loading = loading.clone();
         let user = user.clone();
+        let index = index.clone();
         
         use_effect_with((), move |_| {
-            if let Some(current_user) = (*user).as_ref() {
-                let user_id = current_user.uid.clone();
-                let notes = notes.clone();
-                let loading = loading.clone();
-                
-                spawn_local(async move {
-                    match NotesService::get_user_notes(&user_id).await {
-                        Ok(mut user_notes) => {
-                            user_notes.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
-                            notes.set(user_notes);
+            let subscription = (*user).as_ref().map(|current_user| {
+                NotesService::subscribe_user_notes(&current_user.uid, move |changes| {
+                    match changes {
+                        Ok(changes) => {
+                            index_changes(&mut index.borrow_mut(), &changes);
+                            notes.dispatch(changes);
                         }
                         Err(e) => {
                             web_sys::console::error_1(&format!("Erro ao carregar notas: {}", e).into());
                         }
                     }
                     loading.set(false);
+                })
+            });
+            move || drop(subscription)
+        });
+    }
+
+    // Notas que outros usuários compartilharam com este
+    {
+        let shared_notes = shared_notes.clone();
+        let user = user.clone();
+        let index = index.clone();
+
+        use_effect_with((), move |_| {
+            let subscription = (*user).as_ref().map(|current_user| {
+                NotesService::subscribe_shared_notes(&current_user.email, move |changes| {
+                    match changes {
+                        Ok(changes) => {
+                            index_changes(&mut index.borrow_mut(), &changes);
+                            shared_notes.dispatch(changes);
+                        }
+                        Err(e) => {
+                            web_sys::console::error_1(&format!("Erro ao carregar notas compartilhadas: {}", e).into());
+                        }
+                    }
+                })
+            });
+            move || drop(subscription)
+        });
+    }
+
+    // Cadernos do usuário
+    {
+        let notebooks = notebooks.clone();
+        let user = user.clone();
+
+        use_effect_with((), move |_| {
+            let subscription = (*user).as_ref().map(|current_user| {
+                NotebooksService::subscribe(&current_user.uid, move |result| match result {
+                    Ok(list) => notebooks.set(list),
+                    Err(e) => {
+                        web_sys::console::error_1(&format!("Erro ao carregar cadernos: {}", e).into());
+                    }
+                })
+            });
+            move || drop(subscription)
+        });
+    }
+
+    // Notas que passaram do prazo na lixeira são excluídas de vez ao abrir
+    {
+        let notes = notes.clone();
+        use_effect_with(*loading, move |loading| {
+            if !*loading {
+                let all_notes = notes.notes.clone();
+                spawn_local(async move {
+                    if let Err(e) = NotesService::purge_expired(&all_notes).await {
+                        web_sys::console::error_1(&format!("Erro ao esvaziar a lixeira: {}", e).into());
+                    }
                 });
             }
             || ()
         });
     }
+
+    // Indicador de sincronização
+    {
+        let sync_state = sync_state.clone();
+        use_effect_with((), move |_| {
+            let subscription = SyncService::subscribe_status(move |state| sync_state.set(state));
+            move || drop(subscription)
+        });
+    }
+
+    // A nota aberta foi criada offline e acabou de receber o id definitivo
+    {
+        let navigator = navigator.clone();
+        use_effect_with(props.note_id.clone(), move |open_id| {
+            let open_id = open_id.clone();
+            let subscription = open_id.is_some().then(|| SyncService::subscribe_remap(move |temp_id, server_id| {
+                if open_id.as_deref() == Some(temp_id) {
+                    navigator.replace(&Route::Note { id: server_id.to_string() });
+                }
+            }));
+            move || drop(subscription)
+        });
+    }
+
+    // Alterações recusadas pelo backend: descartar ou tentar de novo
+    let on_rejected = Callback::from(move |_: MouseEvent| {
+        let reasons = SyncService::rejected();
+        let message = format!(
+            "O servidor recusou {} alteração(ões) feitas offline:\n\n{}\n\nDescartá-las? Cancelar mantém as alterações e tenta enviá-las de novo.",
+            reasons.len(),
+            reasons.join("\n"),
+        );
+        if confirm(&message) {
+            spawn_local(SyncService::discard_rejected());
+        } else {
+            spawn_local(SyncService::replay());
+        }
+    });
     
+    let on_search = {
+        let query = query.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            query.set(input.value());
+        })
+    };
+
+    let on_toggle_tag = {
+        let tag_filter = tag_filter.clone();
+        Callback::from(move |tag: String| {
+            let mut updated = (*tag_filter).clone();
+            match updated.iter().position(|t| *t == tag) {
+                Some(pos) => { updated.remove(pos); }
+                None => updated.push(tag),
+            }
+            tag_filter.set(updated);
+        })
+    };
+
+    let on_toggle_match = {
+        let tag_match = tag_match.clone();
+        Callback::from(move |_: MouseEvent| {
+            tag_match.set(match *tag_match {
+                TagMatch::All => TagMatch::Any,
+                TagMatch::Any => TagMatch::All,
+            });
+        })
+    };
+
+    let on_create_notebook = {
+        let user = user.clone();
+        Callback::from(move |parent_id: Option<String>| {
+            let Some(current_user) = (*user).clone() else { return };
+            let Some(name) = prompt("Nome do caderno:", "").filter(|n| !n.trim().is_empty()) else { return };
+            spawn_local(async move {
+                if let Err(e) = NotebooksService::create(&name, &current_user.uid, parent_id).await {
+                    alert(&format!("Erro ao criar caderno: {}", e));
+                }
+            });
+        })
+    };
+
+    let on_rename_notebook = Callback::from(move |notebook: Notebook| {
+        let Some(name) = prompt("Novo nome do caderno:", &notebook.name)
+            .filter(|n| !n.trim().is_empty() && *n != notebook.name) else { return };
+        spawn_local(async move {
+            if let Err(e) = NotebooksService::rename(&notebook, &name).await {
+                alert(&format!("Erro ao renomear caderno: {}", e));
+            }
+        });
+    });
+
+    let on_delete_notebook = {
+        let notebooks = notebooks.clone();
+        let navigator = navigator.clone();
+        let selected_notebook = notebook_id.clone();
+        Callback::from(move |notebook: Notebook| {
+            let message = format!(
+                "Excluir o caderno \"{}\"? As notas e os cadernos dentro dele passam para o caderno acima.",
+                notebook.name
+            );
+            if !confirm(&message) {
+                return;
+            }
+            // o caderno aberto deixaria de existir (ou de conter o que era mostrado)
+            let id = notebook.id.clone().unwrap_or_default();
+            let showing = selected_notebook.as_deref()
+                .is_some_and(|selected| NotebookTree::new(&notebooks).subtree(&id).contains(selected));
+            if showing {
+                navigator.push(&Route::Dashboard);
+            }
+            spawn_local(async move {
+                if let Err(e) = NotebooksService::delete(&notebook).await {
+                    alert(&format!("Erro ao excluir caderno: {}", e));
+                }
+            });
+        })
+    };
+
+    let on_move_note = {
+        let notes = notes.clone();
+        Callback::from(move |(note_id, notebook_id): (String, Option<String>)| {
+            let Some(note) = notes.notes.iter().find(|n| n.id.as_deref() == Some(note_id.as_str())).cloned() else { return };
+            if note.notebook_id == notebook_id {
+                return;
+            }
+            spawn_local(async move {
+                if let Err(e) = NotesService::move_note(&note, notebook_id).await {
+                    alert(&format!("Erro ao mover nota: {}", e));
+                }
+            });
+        })
+    };
+
     let on_logout = {
         let navigator = navigator.clone();
         Callback::from(move |_| {
@@ -72,82 +401,140 @@ pub fn dashboard() -> Html {
     };
     
     let on_new_note = {
-        let show_editor = show_editor.clone();
-        let selected_note = selected_note.clone();
-        Callback::from(move |_| {
-            show_editor.set(true);
-            selected_note.set(None);
-        })
+        let navigator = navigator.clone();
+        Callback::from(move |_| navigator.push(&Route::NewNote))
     };
     
-    let on_select_note = {
-        let selected_note = selected_note.clone();
-        let show_editor = show_editor.clone();
-        Callback::from(move |note: Note| {
-            selected_note.set(Some(note.clone()));
-            show_editor.set(true);
+    let on_open_note = {
+        let navigator = navigator.clone();
+        Callback::from(move |id: String| navigator.push(&Route::Note { id }))
+    };
+
+    let on_note_action = {
+        let navigator = navigator.clone();
+        let list_route = list_route.clone();
+        let open_id = props.note_id.clone();
+        Callback::from(move |(note, action): (Note, NoteAction)| {
+            if action == NoteAction::Purge
+                && !confirm("Excluir a nota para sempre? Esta ação não pode ser desfeita.")
+            {
+                return;
+            }
+            // a nota aberta sai da lista mostrada
+            let leaves_list = !matches!(action, NoteAction::Pin | NoteAction::Unpin);
+            if leaves_list && open_id.is_some() && open_id == note.id {
+                navigator.push(&list_route);
+            }
+            spawn_local(async move {
+                let result = match action {
+                    NoteAction::Pin => NotesService::set_pinned(&note, true).await.map(drop),
+                    NoteAction::Unpin => NotesService::set_pinned(&note, false).await.map(drop),
+                    NoteAction::Archive => NotesService::set_archived(&note, true).await.map(drop),
+                    NoteAction::Unarchive => NotesService::set_archived(&note, false).await.map(drop),
+                    NoteAction::Trash => NotesService::trash_note(&note).await.map(drop),
+                    NoteAction::Restore => NotesService::restore_note(&note).await.map(drop),
+                    NoteAction::Purge => match &note.id {
+                        Some(id) => NotesService::delete_note(id).await,
+                        None => Ok(()),
+                    },
+                };
+                if let Err(e) = result {
+                    alert(&format!("Erro ao atualizar nota: {}", e));
+                }
+            });
         })
     };
 
-    
-    let on_delete_note = {
-        let show_editor = show_editor.clone();
-        let selected_note = selected_note.clone();
+    // tarefas marcadas no trecho da lista; a nota é gravada na hora
+    let on_toggle_task = Callback::from(move |(note, index): (Note, usize)| {
+        let Some(content) = markdown::toggle_task(&note.content, index) else { return };
+        spawn_local(async move {
+            if let Err(e) = NotesService::set_content(&note, content).await {
+                alert(&format!("Erro ao atualizar tarefa: {}", e));
+            }
+        });
+    });
+
+    let on_empty_trash = {
         let notes = notes.clone();
-        Callback::from(move |note_id: String| {
-            let notes = notes.clone();
-            show_editor.set(false);
-            selected_note.set(None);
+        let navigator = navigator.clone();
+        let list_route = list_route.clone();
+        let open_trashed = selected_note.as_ref().is_some_and(|n| n.status() == NoteStatus::Trashed);
+        Callback::from(move |_: MouseEvent| {
+            if !confirm("Excluir para sempre todas as notas da lixeira?") {
+                return;
+            }
+            if open_trashed {
+                navigator.push(&list_route);
+            }
+            let trashed: Vec<String> = notes.notes.iter()
+                .filter(|n| n.status() == NoteStatus::Trashed)
+                .filter_map(|n| n.id.clone())
+                .collect();
             spawn_local(async move {
-                if let Ok(_) = NotesService::delete_note(&note_id).await {
-                    notes.set(notes.iter().filter(|n| n.id.as_ref() != Some(&note_id)).cloned().collect());
+                for id in trashed {
+                    if let Err(e) = NotesService::delete_note(&id).await {
+                        alert(&format!("Erro ao esvaziar a lixeira: {}", e));
+                        return;
+                    }
                 }
             });
         })
     };
     
     let on_close_editor = {
-        let show_editor = show_editor.clone();
-        let selected_note = selected_note.clone();
+        let navigator = navigator.clone();
+        let list_route = list_route.clone();
+        let conflict = conflict.clone();
         Callback::from(move |_| {
-            show_editor.set(false);
-            selected_note.set(None);
+            conflict.set(None);
+            navigator.push(&list_route);
         })
     };
+
+    let on_dismiss_conflict = {
+        let conflict = conflict.clone();
+        Callback::from(move |_| conflict.set(None))
+    };
     
+    // A lista é atualizada pela assinatura, não aqui
     let on_save_note = {
-        let notes = notes.clone();
-        let show_editor = show_editor.clone();
-        let selected_note = selected_note.clone();
+        let navigator = navigator.clone();
+        let list_route = list_route.clone();
+        let conflict = conflict.clone();
         
-        Callback::from(move |note: Note| {
-            let notes = notes.clone();
-            let show_editor = show_editor.clone();
-            let selected_note = selected_note.clone();
+        Callback::from(move |SaveRequest { note, on_saved }: SaveRequest| {
+            let navigator = navigator.clone();
+            let list_route = list_route.clone();
+            let conflict = conflict.clone();
             
             spawn_local(async move {
-                if note.id.is_some() {
+                let result = match &note.id {
                     // Atualizar nota existente
-                    if let Ok(_) = NotesService::update_note(&note).await {
-                        let mut updated_notes = (*notes).clone();
-                        if let Some(pos) = updated_notes.iter().position(|n| n.id == note.id) {
-                            updated_notes[pos] = note;
-                        }
-                        updated_notes.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
-                        notes.set(updated_notes);
-                    }
-                } else {
+                    Some(_) => NotesService::update_note(&note).await,
                     // Criar nova nota
-                    if let Ok(id) = NotesService::create_note(&note).await {
-                        let mut new_note = note;
-                        new_note.id = Some(id);
-                        let mut updated_notes = (*notes).clone();
-                        updated_notes.insert(0, new_note);
-                        notes.set(updated_notes);
+                    None => NotesService::create_note(&note).await
+                        .map(|id| Note { id: Some(id), ..note.clone() }),
+                };
+                match result {
+                    // outra gravação chegou antes: o editor continua aberto
+                    // para o usuário escolher entre as duas versões
+                    Err(ServiceError::Conflict) => {
+                        let id = note.id.clone().unwrap_or_default();
+                        match NotesService::get_note(&id).await {
+                            Ok(Some(remote)) => {
+                                conflict.set(Some(remote));
+                                return;
+                            }
+                            Ok(None) => alert("A nota foi excluída em outro dispositivo."),
+                            Err(e) => alert(&format!("Erro ao salvar nota: {}", e)),
+                        }
                     }
+                    Err(e) => alert(&format!("Erro ao salvar nota: {}", e)),
+                    Ok(saved) => on_saved.emit(saved),
                 }
-                show_editor.set(false);
-                selected_note.set(None);
+                conflict.set(None);
+                navigator.push(&list_route);
             });
         })
     };
@@ -171,6 +558,67 @@ pub fn dashboard() -> Html {
     }
     
     let user_email = user.as_ref().unwrap().email.clone();
+    let current_user = User { uid: user.as_ref().unwrap().uid.clone(), email: user_email.clone() };
+
+    let terms = search::query_terms(&query);
+    let ranked = (!terms.is_empty()).then(|| index.borrow().search(&query));
+    let mut own_notes = ranked_notes(&notes.notes, ranked.as_deref());
+    let mut shared = ranked_notes(&shared_notes.notes, ranked.as_deref());
+    own_notes.retain(|n| n.status() == view && n.matches_tags(&tag_filter, *tag_match));
+    // a lixeira e as arquivadas de quem compartilhou não aparecem para os outros
+    shared.retain(|n| n.status() == NoteStatus::Active && n.matches_tags(&tag_filter, *tag_match));
+    if view != NoteStatus::Active {
+        shared.clear();
+    }
+    // fixadas no topo, exceto quando a ordem é a da relevância da busca
+    if ranked.is_none() {
+        own_notes.sort_by_key(|n| !n.pinned);
+    }
+
+    // dentro de um caderno: as notas dele e dos cadernos dentro dele
+    let tree = NotebookTree::new(&notebooks);
+    let notebook_path = notebook_id.as_deref().map(|id| tree.path(id));
+    if let Some(id) = notebook_id.as_deref() {
+        let subtree = tree.subtree(id);
+        own_notes.retain(|n| n.notebook_id.as_ref().is_some_and(|nb| subtree.contains(nb)));
+        shared.clear();
+    }
+    let searching = ranked.is_some() || !tag_filter.is_empty();
+    let (empty_text, empty_hint) = empty_message(view, searching, notebook_id.is_some());
+
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let active_notes = notes.notes.iter().filter(|n| n.status() == NoteStatus::Active);
+    for id in active_notes.filter_map(|n| n.notebook_id.clone()) {
+        *counts.entry(id).or_default() += 1;
+    }
+    let archived_count = notes.notes.iter().filter(|n| n.status() == NoteStatus::Archived).count();
+    let trash_count = notes.notes.iter().filter(|n| n.status() == NoteStatus::Trashed).count();
+
+    // `[[links]]`: as notas fora da lixeira, e as que apontam para a aberta
+    let linked_notes: Vec<&Note> = notes.notes.iter()
+        .chain(shared_notes.notes.iter())
+        .filter(|n| n.status() != NoteStatus::Trashed && n.id.is_some())
+        .collect();
+    let linkable: Vec<NoteRef> = linked_notes.iter()
+        .map(|n| NoteRef { id: n.id.clone().unwrap_or_default(), title: n.title.clone() })
+        .collect();
+    let backlinks: Vec<NoteRef> = match selected_note.as_ref().and_then(|n| n.id.as_deref()) {
+        Some(id) => linked_notes.iter()
+            .filter(|n| n.id.as_deref() != Some(id))
+            .filter(|n| wiki::links(&n.content).iter()
+                .any(|title| wiki::resolve(&linkable, title).is_some_and(|target| target.id == id)))
+            .map(|n| NoteRef { id: n.id.clone().unwrap_or_default(), title: n.title.clone() })
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let all_tags: Vec<String> = notes.notes.iter()
+        .chain(shared_notes.notes.iter())
+        .filter(|n| n.status() != NoteStatus::Trashed)
+        .flat_map(|n| n.tags.iter().cloned())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
     
     html! {
         <div class="dashboard">
@@ -178,6 +626,7 @@ pub fn dashboard() -> Html {
                 <div class="header-content">
                     <h1>{ "📝 Minhas Notas" }</h1>
                     <div class="user-info">
+                        { sync_indicator(*sync_state, on_rejected) }
                         <span>{ user_email }</span>
                         <button onclick={on_logout} class="btn-secondary">
                             { "Sair" }
@@ -191,69 +640,134 @@ pub fn dashboard() -> Html {
                     <button onclick={on_new_note} class="btn-primary btn-new-note" disabled={selected_note.is_some()}> 
                         { "+ Nova Nota" }
                     </button>
+                    <nav class="view-tabs">
+                        <Link<Route> to={Route::Dashboard} classes={classes!("view-tab", (view == NoteStatus::Active).then_some("active"))}>
+                            { "Notas" }
+                        </Link<Route>>
+                        <Link<Route> to={Route::Archived} classes={classes!("view-tab", (view == NoteStatus::Archived).then_some("active"))}>
+                            { view_label("Arquivadas", archived_count) }
+                        </Link<Route>>
+                        <Link<Route> to={Route::Trash} classes={classes!("view-tab", (view == NoteStatus::Trashed).then_some("active"))}>
+                            { view_label("Lixeira", trash_count) }
+                        </Link<Route>>
+                    </nav>
+                    if view == NoteStatus::Active {
+                        <NotebookList
+                            notebooks={(*notebooks).clone()}
+                            selected={notebook_id.clone()}
+                            counts={counts}
+                            on_create={on_create_notebook}
+                            on_rename={on_rename_notebook}
+                            on_delete={on_delete_notebook}
+                            on_move_note={on_move_note}
+                        />
+                        if let Some(path) = notebook_path.filter(|p| !p.is_empty()) {
+                            <div class="notebook-path">
+                                { path.iter().map(|n| n.name.as_str()).collect::<Vec<_>>().join(" / ") }
+                            </div>
+                        }
+                    }
+                    if view == NoteStatus::Trashed && trash_count > 0 {
+                        <div class="trash-bar">
+                            <span>{ format!("Excluídas de vez após {} dias", TRASH_RETENTION_DAYS) }</span>
+                            <button class="btn-secondary" onclick={on_empty_trash}>{ "Esvaziar lixeira" }</button>
+                        </div>
+                    }
+                    <input
+                        type="search"
+                        class="search-input"
+                        placeholder="Buscar notas..."
+                        value={(*query).clone()}
+                        oninput={on_search}
+                    />
+                    if !all_tags.is_empty() {
+                        <div class="tag-filter">
+                            { for all_tags.iter().map(|tag| {
+                                let on_toggle = on_toggle_tag.clone();
+                                let active = tag_filter.contains(tag);
+                                let label = format!("#{}", tag);
+                                let tag = tag.clone();
+                                html! {
+                                    <button
+                                        class={classes!("tag-chip", active.then_some("active"))}
+                                        onclick={Callback::from(move |_| on_toggle.emit(tag.clone()))}
+                                    >
+                                        { label }
+                                    </button>
+                                }
+                            }) }
+                            if tag_filter.len() > 1 {
+                                <button
+                                    class="tag-match"
+                                    onclick={on_toggle_match}
+                                    title="Como combinar as etiquetas selecionadas"
+                                >
+                                    { match *tag_match {
+                                        TagMatch::All => "Todas (E)",
+                                        TagMatch::Any => "Qualquer uma (OU)",
+                                    } }
+                                </button>
+                            }
+                        </div>
+                    }
                     if *loading {
                         <div class="loading">{ "Carregando notas..." }</div>
-                    } else if notes.is_empty() {
+                    } else if own_notes.is_empty() && (shared.is_empty() || !searching) {
                         <div class="empty-state">
-                            <p>{ "Nenhuma nota ainda" }</p>
-                            <p class="empty-hint">{ "Clique em 'Nova Nota' para começar" }</p>
+                            <p>{ empty_text }</p>
+                            if let Some(hint) = empty_hint {
+                                <p class="empty-hint">{ hint }</p>
+                            }
                         </div>
                     } else {
                         <div class="notes-list">
-                            { for notes.iter().map(|note| {
-                                let note_clone = note.clone();
-                                let note_clone2 = note.clone();
-                                let on_select = on_select_note.clone();
-                                let on_delete = on_delete_note.clone();
-                                
-                                html! {
-                                    <div class="note-item">
-                                        <div
-                                            class="note-content"
-                                            onclick={Callback::from(move |_| on_select.emit(note_clone.clone()))}
-                                        >
-                                            <h3>{ &note.title }</h3>
-                                            { (|| {
-                                                let snippet = truncate_chars(&note.content, 200);
-                                                let mut options = Options::empty();
-                                                options.insert(Options::ENABLE_TABLES);
-                                                options.insert(Options::ENABLE_FOOTNOTES);
-                                                options.insert(Options::ENABLE_STRIKETHROUGH);
-                                                options.insert(Options::ENABLE_TASKLISTS);
-                                                let parser = Parser::new_ext(&snippet, options);
-                                                let mut html_out = String::new();
-                                                html::push_html(&mut html_out, parser);
-                                                let safe = clean(&html_out);
-                                                html! { <p>{ Html::from_html_unchecked(AttrValue::from(safe)) }</p> }
-                                            })() }
-                                            <small>{ format_date(note.updated_at) }</small>
-                                        </div>
-                                        <button
-                                            class="btn-delete"
-                                            onclick={Callback::from(move |e: MouseEvent| {
-                                                e.stop_propagation();
-                                                if let Some(id) = &note_clone2.id {
-                                                    on_delete.emit(id.clone());
-                                                }
-                                            })}
-                                        >
-                                            { "🗑️" }
-                                        </button>
-                                    </div>
-                                }
+                            { for own_notes.iter().map(|note| {
+                                let on_task = (note.status() != NoteStatus::Trashed).then_some(&on_toggle_task);
+                                note_item(note, None, &terms, &linkable, &on_open_note, Some(&on_note_action), on_task)
+                            }) }
+                        </div>
+                    }
+                    if !shared.is_empty() {
+                        <h2 class="sidebar-section">{ "Compartilhadas comigo" }</h2>
+                        <div class="notes-list">
+                            { for shared.iter().map(|note| {
+                                // fixar, arquivar e excluir são só do dono
+                                let on_task = note.role_of(&current_user).is_some_and(Role::can_edit).then_some(&on_toggle_task);
+                                note_item(note, Some(&note.owner_email), &terms, &linkable, &on_open_note, None, on_task)
                             }) }
                         </div>
                     }
                 </div>
                 
                 <div class="notes-content">
-                    if *show_editor {
+                    if props.new_note || selected_note.is_some() {
+                        // cada nota tem o próprio editor: voltar/avançar entre
+                        // notas não mistura o estado de uma com a outra
                         <NoteEditor
-                            note={(*selected_note).clone()}
+                            key={props.note_id.clone().unwrap_or_else(|| "new".to_string())}
+                            note={selected_note.clone()}
                             user_id={user.as_ref().unwrap().uid.clone()}
+                            user_email={user.as_ref().unwrap().email.clone()}
+                            conflict={(*conflict).clone()}
+                            known_tags={all_tags.clone()}
+                            linkable={linkable.clone()}
+                            backlinks={backlinks}
+                            on_open_note={on_open_note.clone()}
+                            notebook_id={notebook_id.clone()}
+                            read_only={selected_note.as_ref().is_some_and(|n| n.status() == NoteStatus::Trashed)}
                             on_save={on_save_note}
                             on_close={on_close_editor}
+                            on_dismiss_conflict={on_dismiss_conflict}
                         />
+                    } else if props.note_id.is_some() && *loading {
+                        <div class="welcome-message">
+                            <h2>{ "⏳ Carregando nota..." }</h2>
+                        </div>
+                    } else if props.note_id.is_some() {
+                        <div class="welcome-message">
+                            <h2>{ "Nota não encontrada" }</h2>
+                            <p>{ "Ela pode ter sido excluída ou não estar compartilhada com você." }</p>
+                        </div>
                     } else {
                         <div class="welcome-message">
                             <h2>{ "Bem-vindo ao Notepad!" }</h2>
@@ -266,6 +780,274 @@ pub fn dashboard() -> Html {
     }
 }
 
+/// Rota da lista mostrada na barra lateral, para onde o editor volta ao fechar.
+fn list_route(notebook_id: Option<String>, view: NoteStatus) -> Route {
+    match (view, notebook_id) {
+        (NoteStatus::Archived, _) => Route::Archived,
+        (NoteStatus::Trashed, _) => Route::Trash,
+        (NoteStatus::Active, Some(id)) => Route::Notebook { id },
+        (NoteStatus::Active, None) => Route::Dashboard,
+    }
+}
+
+fn index_changes(index: &mut SearchIndex, changes: &[NoteChange]) {
+    for change in changes {
+        match change {
+            NoteChange::Added(note) | NoteChange::Modified(note) => {
+                if let Some(id) = &note.id {
+                    index.upsert(id, &note.title, &note.content);
+                }
+            }
+            NoteChange::Removed(id) => index.remove(id),
+        }
+    }
+}
+
+/// Notas encontradas pela busca, na ordem de relevância; todas, sem busca.
+fn ranked_notes<'a>(notes: &'a [Note], ranked: Option<&[String]>) -> Vec<&'a Note> {
+    let Some(ranked) = ranked else {
+        return notes.iter().collect();
+    };
+    ranked.iter()
+        .filter_map(|id| notes.iter().find(|n| n.id.as_ref() == Some(id)))
+        .collect()
+}
+
+/// Item da barra lateral; `owner` é mostrado nas notas compartilhadas e
+/// as palavras que casam com `terms` aparecem destacadas. As notas do
+/// usuário (com `on_action`) têm as ações da lista onde estão e, fora da
+/// lixeira e do arquivo, podem ser arrastadas para um caderno. Com
+/// `on_task`, as caixas de tarefa do trecho podem ser marcadas; os
+/// `[[links]]` do trecho (para as `notes`) abrem a nota apontada.
+fn note_item(
+    note: &Note,
+    owner: Option<&str>,
+    terms: &[String],
+    notes: &[NoteRef],
+    on_open: &Callback<String>,
+    on_action: Option<&Callback<(Note, NoteAction)>>,
+    on_task: Option<&Callback<(Note, usize)>>,
+) -> Html {
+    let draggable = on_action.is_some() && note.status() == NoteStatus::Active;
+    let on_select = {
+        let on_open = on_open.clone();
+        let id = note.id.clone();
+        Callback::from(move |_| {
+            if let Some(id) = id.clone() {
+                on_open.emit(id);
+            }
+        })
+    };
+    let on_drag_start = {
+        let note_id = note.id.clone().unwrap_or_default();
+        Callback::from(move |e: DragEvent| {
+            if let Some(dt) = e.data_transfer() {
+                let _ = dt.set_data(NOTE_DRAG_FORMAT, &note_id);
+                dt.set_effect_allowed("move");
+            }
+        })
+    };
+    let (start, snippet) = snippet(&note.content, terms, 200);
+    let first_task = if start == 0 { 0 } else { markdown::tasks_before(&note.content, start) };
+    let events = markdown::task_checkboxes(Parser::new_ext(&snippet, markdown::options()), first_task);
+    let events = markdown::render_extensions(wiki::render_links(events, notes), false)
+        .into_iter()
+        .flat_map(|event| match event {
+            Event::Text(text) => highlight_events(&text, terms),
+            other => vec![other],
+        });
+    let mut html_out = String::new();
+    html::push_html(&mut html_out, events);
+    let safe = markdown::sanitize(&html_out);
+    let on_snippet_click = {
+        let on_task = on_task.cloned();
+        let on_open = on_open.clone();
+        let note = note.clone();
+        Callback::from(move |e: MouseEvent| {
+            if let Some(id) = clicked_note(&e) {
+                e.prevent_default();
+                e.stop_propagation();
+                on_open.emit(id);
+                return;
+            }
+            let Some(index) = clicked_task(&e) else { return };
+            // a caixa só muda quando a nota for gravada; sem permissão, o
+            // clique abre a nota como no resto do trecho
+            e.prevent_default();
+            if let Some(on_task) = &on_task {
+                e.stop_propagation();
+                on_task.emit((note.clone(), index));
+            }
+        })
+    };
+    let progress = markdown::task_progress(&note.content);
+
+    html! {
+        <div class="note-item" draggable={draggable.to_string()} ondragstart={on_drag_start}>
+            <div class="note-content" onclick={on_select}>
+                <h3>
+                    if note.pinned && note.status() == NoteStatus::Active {
+                        <span class="pin-mark" title="Fixada">{ "📌 " }</span>
+                    }
+                    { highlight(&note.title, terms) }
+                </h3>
+                <p onclick={on_snippet_click}>{ Html::from_html_unchecked(AttrValue::from(safe)) }</p>
+                <small>
+                    if let Some((done, total)) = progress {
+                        <span class={classes!("task-badge", (done == total).then_some("done"))}>
+                            { format!("☑ {}/{} tarefas", done, total) }
+                        </span>
+                    }
+                    { format_date(note.updated_at) }
+                    if let Some(owner) = owner.filter(|o| !o.is_empty()) {
+                        { format!(" · de {}", owner) }
+                    }
+                    if let Some(days) = note.days_until_purge(SystemClock.now_millis()) {
+                        { format!(" · excluída de vez em {} dia(s)", days) }
+                    }
+                </small>
+                if !note.tags.is_empty() {
+                    <div class="note-tags">
+                        { for note.tags.iter().map(|tag| html! {
+                            <span class="tag-chip">{ format!("#{}", tag) }</span>
+                        }) }
+                    </div>
+                }
+            </div>
+            if let Some(on_action) = on_action {
+                <div class="note-actions">
+                    { for NoteAction::for_note(note).iter().map(|&action| {
+                        let on_action = on_action.clone();
+                        let note = note.clone();
+                        html! {
+                            <button
+                                class={classes!("note-action", (action == NoteAction::Unpin).then_some("active"))}
+                                title={action.label()}
+                                onclick={Callback::from(move |e: MouseEvent| {
+                                    e.stop_propagation();
+                                    on_action.emit((note.clone(), action));
+                                })}
+                            >
+                                { action.icon() }
+                            </button>
+                        }
+                    }) }
+                </div>
+            }
+        </div>
+    }
+}
+
+/// Mensagem da lista vazia e uma dica opcional.
+fn empty_message(view: NoteStatus, searching: bool, in_notebook: bool) -> (&'static str, Option<String>) {
+    match view {
+        _ if searching => ("Nenhuma nota encontrada", None),
+        NoteStatus::Trashed => (
+            "A lixeira está vazia",
+            Some(format!("Notas excluídas ficam aqui por {} dias", TRASH_RETENTION_DAYS)),
+        ),
+        NoteStatus::Archived => ("Nenhuma nota arquivada", None),
+        NoteStatus::Active if in_notebook => (
+            "Nenhuma nota neste caderno",
+            Some("Arraste notas para cá ou crie uma nova".to_string()),
+        ),
+        NoteStatus::Active => ("Nenhuma nota ainda", Some("Clique em 'Nova Nota' para começar".to_string())),
+    }
+}
+
+fn view_label(name: &str, count: usize) -> String {
+    if count == 0 {
+        name.to_string()
+    } else {
+        format!("{} ({})", name, count)
+    }
+}
+
+fn sync_indicator(state: SyncState, on_rejected: Callback<MouseEvent>) -> Html {
+    if state.status == SyncStatus::Error && state.rejected > 0 {
+        return html! {
+            <button class="sync-status error" title="Ver as alterações recusadas" onclick={on_rejected}>
+                { format!("⚠ {} alteração(ões) recusada(s)", state.rejected) }
+            </button>
+        };
+    }
+    let (class, label) = match state.status {
+        SyncStatus::Synced => ("sync-status synced", "● Sincronizado".to_string()),
+        SyncStatus::Syncing => ("sync-status syncing", format!("⟳ Sincronizando ({})…", state.pending)),
+        SyncStatus::Offline if state.pending > 0 => ("sync-status offline", format!("● Offline — {} pendente(s)", state.pending)),
+        SyncStatus::Offline => ("sync-status offline", "● Offline".to_string()),
+        SyncStatus::Error => ("sync-status error", "⚠ Erro ao sincronizar".to_string()),
+    };
+    html! { <span class={class} title="Estado da sincronização">{ label }</span> }
+}
+
+fn alert(message: &str) {
+    web_sys::window()
+        .unwrap()
+        .alert_with_message(message)
+        .unwrap();
+}
+
+fn confirm(message: &str) -> bool {
+    web_sys::window()
+        .unwrap()
+        .confirm_with_message(message)
+        .unwrap_or(false)
+}
+
+/// Texto digitado pelo usuário; `None` se ele cancelar.
+fn prompt(message: &str, default: &str) -> Option<String> {
+    web_sys::window()
+        .unwrap()
+        .prompt_with_message_and_default(message, default)
+        .ok()
+        .flatten()
+}
+
+/// Trecho do conteúdo a mostrar e onde ele começa: o começo, ou perto do
+/// primeiro resultado da busca se ele ficaria de fora.
+fn snippet(content: &str, terms: &[String], max_chars: usize) -> (usize, String) {
+    let first = search::match_ranges(content, terms).first().map(|r| r.start);
+    let Some(first) = first.filter(|&start| content[..start].chars().count() > max_chars * 3 / 4) else {
+        return (0, truncate_chars(content, max_chars));
+    };
+    // recua algumas palavras para dar contexto
+    let start = content[..first].char_indices()
+        .rev()
+        .filter(|(_, c)| c.is_whitespace())
+        .nth(5)
+        .map_or(first, |(i, c)| i + c.len_utf8());
+    (start, format!("...{}", truncate_chars(&content[start..], max_chars)))
+}
+
+/// Texto simples com as palavras encontradas em `<mark>`.
+fn highlight(text: &str, terms: &[String]) -> Html {
+    let mut parts = Vec::new();
+    let mut pos = 0;
+    for range in search::match_ranges(text, terms) {
+        parts.push(html! { { &text[pos..range.start] } });
+        parts.push(html! { <mark>{ &text[range.clone()] }</mark> });
+        pos = range.end;
+    }
+    parts.push(html! { { &text[pos..] } });
+    parts.into_iter().collect()
+}
+
+/// Mesmo destaque para um trecho de texto do Markdown renderizado.
+fn highlight_events(text: &str, terms: &[String]) -> Vec<Event<'static>> {
+    let mut events = Vec::new();
+    let mut pos = 0;
+    for range in search::match_ranges(text, terms) {
+        events.push(Event::Text(CowStr::from(text[pos..range.start].to_string())));
+        events.push(Event::Html(CowStr::Borrowed("<mark>")));
+        events.push(Event::Text(CowStr::from(text[range.clone()].to_string())));
+        events.push(Event::Html(CowStr::Borrowed("</mark>")));
+        pos = range.end;
+    }
+    events.push(Event::Text(CowStr::from(text[pos..].to_string())));
+    events
+}
+
 fn truncate_chars(s: &str, max_chars: usize) -> String {
     let mut out = String::new();
     for (i, ch) in s.chars().enumerate() {
@@ -285,3 +1067,32 @@ fn format_date(timestamp: i64) -> String {
     let year = date.get_full_year();
     format!("{:02}/{:02}/{}", day, month, year)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(query: &str) -> Vec<String> {
+        search::query_terms(query)
+    }
+
+    #[test]
+    fn snippet_starts_at_char_boundary_before_match() {
+        let content = format!("{}encontrar ação aqui", "ção é ü ".repeat(40));
+        let (start, text) = snippet(&content, &terms("acao"), 60);
+        assert!(content.is_char_boundary(start));
+        assert!(text.starts_with("..."));
+        assert!(text.contains("ação"));
+        // o destaque usa intervalos do próprio trecho
+        let _ = highlight(&text, &terms("acao"));
+        let _ = highlight_events(&text, &terms("acao"));
+    }
+
+    #[test]
+    fn snippet_without_distant_match_keeps_start() {
+        let (start, text) = snippet("ação no começo", &terms("acao"), 60);
+        assert_eq!((start, text.as_str()), (0, "ação no começo"));
+        let (_, text) = snippet("😀😀😀", &terms("x"), 2);
+        assert_eq!(text, "😀😀...");
+    }
+}
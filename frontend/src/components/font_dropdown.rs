@@ -8,10 +8,8 @@ pub struct FontDropdownProps {
 
 #[function_component(FontDropdown)]
 pub fn font_dropdown(props: &FontDropdownProps) -> Html {
-    let fonts = vec![
-        "Arial", "Courier New", "Georgia", "Times New Roman",
-        "Verdana", "Comic Sans MS", "Impact", "Franklin Gothic Medium",
-    ];
+    let fonts = ["Arial", "Courier New", "Georgia", "Times New Roman",
+        "Verdana", "Comic Sans MS", "Impact", "Franklin Gothic Medium"];
 
     let is_open = use_state(|| false);
     
@@ -47,7 +45,7 @@ pub fn font_dropdown(props: &FontDropdownProps) -> Html {
                         <button
                             onclick={Callback::from(move |_| on_select.emit(font_clone.clone()))}
                             class="dropdown-item"
-                            style={format!("display:flex;align-items:center;gap:10px;")}
+                            style={"display:flex;align-items:center;gap:10px;".to_string()}
                         >
                             <span class="font-sample" style={format!("font-family: {};", font)}>{ "Aa" }</span>
                             <span>{ font }</span>
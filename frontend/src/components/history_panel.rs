@@ -0,0 +1,199 @@
+// src/components/history_panel.rs
+use yew::prelude::*;
+use notepad_models::NoteHistory;
+use crate::text::diff::{self, DiffHunk, DiffTag};
+
+/// Linhas iguais mostradas em volta de cada alteração.
+const DIFF_CONTEXT: usize = 3;
+
+#[derive(Properties, PartialEq)]
+pub struct HistoryPanelProps {
+    /// Versões gravadas, da mais antiga para a mais nova (a última é a salva).
+    pub versions: Vec<NoteHistory>,
+    pub draft_title: String,
+    pub draft_content: String,
+    #[prop_or_default]
+    pub read_only: bool,
+    /// Índice em `versions` da versão a restaurar no editor.
+    pub on_restore: Callback<usize>,
+    pub on_close: Callback<()>,
+}
+
+/// Com o que a versão selecionada é comparada.
+#[derive(Clone, Copy, PartialEq)]
+enum Target {
+    Draft,
+    Version(usize),
+}
+
+impl Target {
+    fn value(self) -> String {
+        match self {
+            Target::Draft => "draft".to_string(),
+            Target::Version(i) => i.to_string(),
+        }
+    }
+
+    fn from_value(value: &str) -> Self {
+        value.parse().map_or(Target::Draft, Target::Version)
+    }
+}
+
+/// Histórico da nota: lista das versões e o diff da selecionada para o
+/// rascunho atual ou para outra versão.
+#[function_component(HistoryPanel)]
+pub fn history_panel(props: &HistoryPanelProps) -> Html {
+    let selected = use_state(|| props.versions.len().saturating_sub(1));
+    let target = use_state(|| Target::Draft);
+
+    let on_target = {
+        let target = target.clone();
+        Callback::from(move |e: Event| {
+            let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+            target.set(Target::from_value(&select.value()));
+        })
+    };
+    let on_restore = {
+        let on_restore = props.on_restore.clone();
+        let selected = *selected;
+        Callback::from(move |_: MouseEvent| on_restore.emit(selected))
+    };
+    let on_close = props.on_close.reform(|_: MouseEvent| ());
+
+    let Some(version) = props.versions.get(*selected) else {
+        return html! {
+            <div class="dialog-overlay">
+                <div class="history-dialog">
+                    <h3>{ "Histórico" }</h3>
+                    <p>{ "Esta nota ainda não tem versões salvas." }</p>
+                    <div class="editor-actions">
+                        <button onclick={on_close} class="btn-primary">{ "Fechar" }</button>
+                    </div>
+                </div>
+            </div>
+        };
+    };
+    let (target_title, target_content, target_label) = match *target {
+        Target::Version(i) if i < props.versions.len() => {
+            let other = &props.versions[i];
+            (other.title.as_str(), other.content.as_str(), version_label(i, other))
+        }
+        _ => (props.draft_title.as_str(), props.draft_content.as_str(), "Rascunho atual".to_string()),
+    };
+    let hunks = diff::unified_diff(&version.content, target_content, DIFF_CONTEXT);
+
+    html! {
+        <div class="dialog-overlay">
+            <div class="history-dialog">
+                <h3>{ "Histórico" }</h3>
+                <div class="history-body">
+                    <ul class="history-list">
+                        { for props.versions.iter().enumerate().rev().map(|(i, v)| {
+                            let onclick = {
+                                let selected = selected.clone();
+                                Callback::from(move |_: MouseEvent| selected.set(i))
+                            };
+                            let (inserted, deleted) = match i.checked_sub(1).and_then(|p| props.versions.get(p)) {
+                                Some(previous) => diff::line_stats(&previous.content, &v.content),
+                                None => (v.content.lines().count(), 0),
+                            };
+                            html! {
+                                <li class={classes!("history-item", (i == *selected).then_some("active"))} {onclick}>
+                                    <strong>{ version_label(i, v) }</strong>
+                                    <span class="history-title">{ &v.title }</span>
+                                    <span class="history-stats">
+                                        <span class="diff-insert-count">{ format!("+{}", inserted) }</span>
+                                        <span class="diff-delete-count">{ format!("−{}", deleted) }</span>
+                                    </span>
+                                </li>
+                            }
+                        }) }
+                    </ul>
+                    <div class="history-diff">
+                        <div class="history-compare">
+                            <span>{ format!("{} →", version_label(*selected, version)) }</span>
+                            <select onchange={on_target}>
+                                <option value={Target::Draft.value()} selected={*target == Target::Draft}>
+                                    { "Rascunho atual" }
+                                </option>
+                                { for props.versions.iter().enumerate().rev()
+                                    .filter(|(i, _)| *i != *selected)
+                                    .map(|(i, v)| html! {
+                                        <option value={Target::Version(i).value()} selected={*target == Target::Version(i)}>
+                                            { version_label(i, v) }
+                                        </option>
+                                    }) }
+                            </select>
+                        </div>
+                        if version.title != target_title {
+                            <div class="diff-title">
+                                <del>{ &version.title }</del>
+                                { " → " }
+                                <ins>{ target_title }</ins>
+                            </div>
+                        }
+                        { diff_view(&hunks, &target_label) }
+                    </div>
+                </div>
+                <div class="editor-actions">
+                    <button onclick={on_close} class="btn-secondary">{ "Fechar" }</button>
+                    if !props.read_only {
+                        <button onclick={on_restore} class="btn-primary">{ "Restaurar esta versão" }</button>
+                    }
+                </div>
+            </div>
+        </div>
+    }
+}
+
+fn diff_view(hunks: &[DiffHunk], target_label: &str) -> Html {
+    if hunks.is_empty() {
+        return html! { <p class="diff-empty">{ format!("Conteúdo igual ao de {}.", target_label.to_lowercase()) }</p> };
+    }
+    html! {
+        <div class="diff">
+            { for hunks.iter().map(|hunk| html! {
+                <>
+                    <div class="diff-hunk-header">{ hunk.header.trim_end() }</div>
+                    { for hunk.lines.iter().map(|line| {
+                        let (class, sign) = match line.tag {
+                            DiffTag::Equal => ("diff-equal", " "),
+                            DiffTag::Delete => ("diff-delete", "−"),
+                            DiffTag::Insert => ("diff-insert", "+"),
+                        };
+                        html! {
+                            <div class={classes!("diff-line", class)}>
+                                <span class="diff-number">{ line.old_number.map(|n| n.to_string()).unwrap_or_default() }</span>
+                                <span class="diff-number">{ line.new_number.map(|n| n.to_string()).unwrap_or_default() }</span>
+                                <span class="diff-sign">{ sign }</span>
+                                <span class="diff-text">
+                                    { for line.spans.iter().map(|span| if span.emphasized {
+                                        html! { <mark>{ &span.text }</mark> }
+                                    } else {
+                                        html! { { &span.text } }
+                                    }) }
+                                </span>
+                            </div>
+                        }
+                    }) }
+                </>
+            }) }
+        </div>
+    }
+}
+
+fn version_label(index: usize, version: &NoteHistory) -> String {
+    format!("v{} · {}", index + 1, format_datetime(version.updated_at))
+}
+
+pub(crate) fn format_datetime(timestamp: i64) -> String {
+    let date = js_sys::Date::new(&(timestamp as f64).into());
+    format!(
+        "{:02}/{:02}/{} {:02}:{:02}",
+        date.get_date(),
+        date.get_month() + 1,
+        date.get_full_year(),
+        date.get_hours(),
+        date.get_minutes()
+    )
+}
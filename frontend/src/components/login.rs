@@ -8,8 +8,8 @@ use crate::Route;
 #[function_component(Login)]
 pub fn login() -> Html {
     let navigator = use_navigator().unwrap();
-    let email = use_state(|| String::new());
-    let password = use_state(|| String::new());
+    let email = use_state(String::new);
+    let password = use_state(String::new);
     let error = use_state(|| None::<String>);
     let loading = use_state(|| false);
     
@@ -53,7 +53,7 @@ pub fn login() -> Html {
                         navigator.push(&Route::Dashboard);
                     }
                     Err(e) => {
-                        error.set(Some(e));
+                        error.set(Some(format!("Erro ao fazer login: {}", e)));
                         loading.set(false);
                     }
                 }
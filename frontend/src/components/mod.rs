@@ -3,5 +3,8 @@ pub mod login;
 pub mod note_editor;
 pub mod register;
 pub mod font_dropdown;
+pub mod history_panel;
 pub mod color_dropdown;
-pub mod background_dropdown;
\ No newline at end of file
+pub mod background_dropdown;
+pub mod notebook_list;
+pub mod tag_input;
@@ -1,22 +1,95 @@
 // src/components/note_editor.rs
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use gloo_timers::callback::Timeout;
+use wasm_bindgen_futures::spawn_local;
 use yew::{use_state, prelude::*, AttrValue, Html};
-use pulldown_cmark::{Parser, Options, html};
-use ammonia::clean;
+use wasm_bindgen::JsCast;
 use crate::components::background_dropdown::BackgroundDropdown;
-use crate::models::note::{Note, NoteHistory};
+use notepad_models::{Clock, Note, NoteHistory, Role, SystemClock, User};
 use crate::components::font_dropdown::{FontDropdown};
 use crate::components::color_dropdown::{ColorDropdown};
+use crate::components::history_panel::{format_datetime, HistoryPanel};
+use crate::components::tag_input::TagInput;
+use crate::services::collab::{CollabSession, RemoteCursor, RemoteText};
+use crate::services::drafts::{DraftsService, LocalDraft};
+use crate::services::error::ServiceError;
+use crate::services::notes::NotesService;
+use crate::services::sync::SyncService;
+use crate::text::format::{self, byte_to_utf16, utf16_to_byte, Format};
+use crate::text::markdown;
+use crate::text::merge::merge3;
+use crate::text::wiki::{self, NoteRef};
+
+/// Espera depois da última alteração antes do salvamento automático.
+const AUTOSAVE_DELAY_MS: u32 = 1500;
 
 #[derive(Properties, PartialEq)]
 pub struct NoteEditorProps {
     pub note: Option<Note>,
     pub user_id: String,
-    pub on_save: Callback<Note>,
+    /// Nome mostrado aos outros participantes da edição colaborativa e dono
+    /// das notas novas.
+    #[prop_or_default]
+    pub user_email: String,
+    /// Versão remota que recusou o último salvamento, se houver.
+    #[prop_or_default]
+    pub conflict: Option<Note>,
+    /// Etiquetas das outras notas, sugeridas ao digitar.
+    #[prop_or_default]
+    pub known_tags: Vec<String>,
+    /// Notas que os `[[links]]` podem apontar, sugeridas ao digitar `[[`.
+    #[prop_or_default]
+    pub linkable: Vec<NoteRef>,
+    /// Notas com `[[links]]` para esta ("Referenciado por").
+    #[prop_or_default]
+    pub backlinks: Vec<NoteRef>,
+    /// Abre outra nota, por um link ou referência.
+    #[prop_or_default]
+    pub on_open_note: Callback<String>,
+    /// Caderno das notas novas.
+    #[prop_or_default]
+    pub notebook_id: Option<String>,
+    /// Só leitura mesmo para quem pode editar (nota na lixeira).
+    #[prop_or_default]
+    pub read_only: bool,
+    pub on_save: Callback<SaveRequest>,
     pub on_close: Callback<()>,
+    #[prop_or_default]
+    pub on_dismiss_conflict: Callback<()>,
+}
+
+/// Nota a gravar pelo botão "Salvar"; quem grava chama `on_saved` com a
+/// versão gravada.
+pub struct SaveRequest {
+    pub note: Note,
+    pub on_saved: Callback<Note>,
 }
 
 #[function_component(NoteEditor)]
 pub fn note_editor(props: &NoteEditorProps) -> Html {
+    // versão sobre a qual as edições foram feitas; passa a ser a remota
+    // quando um conflito é resolvido
+    let base = use_state(|| props.note.clone());
+
+    let user = User { uid: props.user_id.clone(), email: props.user_email.clone() };
+    // nota nova é do próprio usuário
+    let role = base.as_ref().map_or(Some(Role::Owner), |n| n.role_of(&user));
+    let read_only = props.read_only || !role.is_some_and(Role::can_edit);
+
+    // Compartilhamento: editado no diálogo, gravado com "Salvar"
+    let acl = use_state(|| props.note.clone().unwrap_or_else(|| {
+        let mut note = Note::new(String::new(), String::new(), props.user_id.clone(),
+            String::new(), String::new(), String::new(), None);
+        note.owner_email = props.user_email.clone();
+        note
+    }));
+    let show_share = use_state(|| false);
+    let share_email = use_state(String::new);
+    let share_role = use_state(|| Role::Editor);
+
     let title = use_state(|| {
         props.note.as_ref()
             .map(|n| n.title.clone())
@@ -29,6 +102,17 @@ pub fn note_editor(props: &NoteEditorProps) -> Html {
             .unwrap_or_default()
     });
 
+    let tags = use_state(|| {
+        props.note.as_ref()
+            .map(|n| n.tags.clone())
+            .unwrap_or_default()
+    });
+
+    let on_tags_change = {
+        let tags = tags.clone();
+        Callback::from(move |updated: Vec<String>| tags.set(updated))
+    };
+
     // Keep a copy of the saved/current note values so "Versão Seguinte" can restore them
     let saved_title = use_state(|| {
         props.note.as_ref()
@@ -44,8 +128,8 @@ pub fn note_editor(props: &NoteEditorProps) -> Html {
 
     let history = use_state(|| {
         props.note.as_ref()
-            .map(|n| n.history.clone())
-            .unwrap_or_else(|| vec![])
+            .map(|n| n.history.versions())
+            .unwrap_or_default()
     });
 
     let current_version_index = use_state(|| {
@@ -101,7 +185,116 @@ pub fn note_editor(props: &NoteEditorProps) -> Html {
     };
     
     // reference to textarea for selection manipulation
-    let textarea_ref = NodeRef::default();
+    let textarea_ref = use_node_ref();
+    let cursors_ref = use_node_ref();
+
+    // Edição colaborativa: só para notas já gravadas no backend
+    let collab = use_mut_ref(|| None::<CollabSession>);
+    let remote_cursors = use_state(Vec::<RemoteCursor>::new);
+    {
+        let collab = collab.clone();
+        let content = content.clone();
+        let remote_cursors = remote_cursors.clone();
+        let textarea_ref = textarea_ref.clone();
+        let note = props.note.clone().filter(|n| n.id.as_deref()
+            .is_some_and(|id| SyncService::is_online() && !SyncService::is_pending(id)));
+        let user = user.clone();
+        use_effect_with((), move |_| {
+            if let Some(note) = note {
+                let on_text = move |remote: RemoteText| {
+                    // atualiza o textarea antes do render para não mover o cursor local
+                    if let Some(elem) = textarea_ref.cast::<web_sys::HtmlTextAreaElement>() {
+                        elem.set_value(&remote.text);
+                        if let Some((start, end)) = remote.selection {
+                            let _ = elem.set_selection_range(start as u32, end as u32);
+                        }
+                    }
+                    content.set(remote.text);
+                };
+                let on_cursors = move |cursors| remote_cursors.set(cursors);
+                match CollabSession::join(&note, &user, on_text, on_cursors) {
+                    Ok(session) => *collab.borrow_mut() = Some(session),
+                    Err(e) => web_sys::console::error_1(&format!("Edição colaborativa indisponível: {}", e).into()),
+                }
+            }
+            move || drop(collab.borrow_mut().take())
+        });
+    }
+    // alterações feitas pela barra de formatação, versões e conflitos
+    {
+        let collab = collab.clone();
+        use_effect_with((*content).clone(), move |text| {
+            if let Some(session) = collab.borrow().as_ref() {
+                session.set_text(text);
+            }
+        });
+    }
+
+    // `[[` aberto antes do cursor (onde começa e o que foi digitado): as
+    // notas com esse título são sugeridas; `link_dismissed` guarda o `[[`
+    // fechado com Esc
+    let link_query = use_state(|| None::<(usize, String)>);
+    let link_selected = use_state(|| 0usize);
+    let link_dismissed = use_mut_ref(|| None::<usize>);
+
+    let on_caret = {
+        let collab = collab.clone();
+        let textarea_ref = textarea_ref.clone();
+        let link_query = link_query.clone();
+        let link_selected = link_selected.clone();
+        let link_dismissed = link_dismissed.clone();
+        Callback::from(move |_: ()| {
+            let Some(elem) = textarea_ref.cast::<web_sys::HtmlTextAreaElement>() else { return };
+            let start = elem.selection_start().ok().flatten().unwrap_or(0);
+            let end = elem.selection_end().ok().flatten().unwrap_or(start);
+            if let Some(session) = collab.borrow().as_ref() {
+                session.set_selection(start as usize, end as usize);
+            }
+            let text = elem.value();
+            let open = (start == end && !read_only)
+                .then(|| wiki::open_link(&text, utf16_to_byte(&text, start as usize)))
+                .flatten()
+                .map(|(at, query)| (at, query.to_string()));
+            let mut dismissed = link_dismissed.borrow_mut();
+            if open.is_none() {
+                *dismissed = None;
+            }
+            let open = open.filter(|(at, _)| *dismissed != Some(*at));
+            if open != *link_query {
+                link_selected.set(0);
+                link_query.set(open);
+            }
+        })
+    };
+
+    let on_pick_link = {
+        let content = content.clone();
+        let textarea_ref = textarea_ref.clone();
+        let link_query = link_query.clone();
+        Callback::from(move |title: String| {
+            let Some(elem) = textarea_ref.cast::<web_sys::HtmlTextAreaElement>() else { return };
+            let val = elem.value();
+            let caret = utf16_to_byte(&val, elem.selection_start().ok().flatten().unwrap_or(0) as usize);
+            // o `[[` ainda tem que estar lá (o texto pode ter mudado)
+            let Some((start, _)) = wiki::open_link(&val, caret)
+                .filter(|(start, _)| link_query.as_ref().is_some_and(|(at, _)| at == start)) else { return };
+            let edit = wiki::complete(&val, start, caret, &title);
+            elem.set_value(&edit.text);
+            let caret = byte_to_utf16(&edit.text, edit.end) as u32;
+            let _ = elem.set_selection_range(caret, caret);
+            content.set(edit.text);
+            link_query.set(None);
+            let _ = elem.focus();
+        })
+    };
+    let note_id = base.as_ref().and_then(|n| n.id.clone());
+    let suggestions: Vec<NoteRef> = link_query.as_ref()
+        .map(|(_, query)| wiki::suggestions(&props.linkable, query, note_id.as_deref()))
+        .unwrap_or_default()
+        .into_iter()
+        .cloned()
+        .collect();
+
     
     let on_title_change = {
         let title = title.clone();
@@ -113,66 +306,373 @@ pub fn note_editor(props: &NoteEditorProps) -> Html {
     
     let on_content_change = {
         let content = content.clone();
+        let collab = collab.clone();
+        let on_caret = on_caret.clone();
         Callback::from(move |e: InputEvent| {
             let textarea: web_sys::HtmlTextAreaElement = e.target_unchecked_into();
+            // envia já, antes que uma alteração remota chegue e sobrescreva o textarea
+            if let Some(session) = collab.borrow().as_ref() {
+                session.set_text(&textarea.value());
+            }
+            on_caret.emit(());
             content.set(textarea.value());
         })
     };
     
-    let on_save_click = {
+    // Salvamento automático: grava o rascunho um pouco depois da última
+    // alteração, uma gravação por vez
+    let draft = Draft {
+        title: (*title).clone(),
+        content: (*content).clone(),
+        tags: (*tags).clone(),
+        font: (*selected_font).clone(),
+        color: (*text_color).clone(),
+        background: (*background_color).clone(),
+        font_size: *font_size,
+        editors: acl.editors.clone(),
+        viewers: acl.viewers.clone(),
+        shared_with: acl.shared_with.clone(),
+    };
+    let save_status = use_state(|| SaveStatus::Saved);
+    // última versão gravada, lida pelo temporizador (o estado `base` capturado
+    // pode estar desatualizado quando ele dispara)
+    let saved_base = use_mut_ref(|| props.note.clone());
+    let autosave_timer = use_mut_ref(|| None::<Timeout>);
+    let pending_save = use_mut_ref(|| None::<Rc<dyn Fn()>>);
+    let in_flight = use_mut_ref(|| false);
+    {
+        let saved_base = saved_base.clone();
+        use_effect_with((*base).clone(), move |base| *saved_base.borrow_mut() = base.clone());
+    }
+
+    let save_draft: Rc<dyn Fn()> = {
+        let draft = draft.clone();
+        let saved_base = saved_base.clone();
+        let in_flight = in_flight.clone();
+        let save_status = save_status.clone();
+        let base = base.clone();
+        let saved_title = saved_title.clone();
+        let saved_content = saved_content.clone();
+        let history = history.clone();
+        let current_version_index = current_version_index.clone();
+        let collab = collab.clone();
+        let user_id = props.user_id.clone();
+        let user_email = props.user_email.clone();
+        let notebook_id = props.notebook_id.clone();
+        Rc::new(move || {
+            // com uma gravação em andamento, o efeito do salvamento automático
+            // agenda outra quando ela terminar e mudar `base`
+            if *in_flight.borrow() || draft.title.is_empty() {
+                return;
+            }
+            let existing = saved_base.borrow().clone();
+            let note = draft.to_note(existing.as_ref(), &user_id, &user_email, notebook_id.clone(), collab.borrow().as_ref());
+            *in_flight.borrow_mut() = true;
+            save_status.set(SaveStatus::Saving);
+
+            let in_flight = in_flight.clone();
+            let saved_base = saved_base.clone();
+            let save_status = save_status.clone();
+            let base = base.clone();
+            let saved_title = saved_title.clone();
+            let saved_content = saved_content.clone();
+            let history = history.clone();
+            let current_version_index = current_version_index.clone();
+            let collab = collab.clone();
+            spawn_local(async move {
+                let result = match note.id {
+                    Some(_) => NotesService::update_note(&note).await,
+                    None => NotesService::create_note(&note).await
+                        .map(|id| Note { id: Some(id), ..note }),
+                };
+                *in_flight.borrow_mut() = false;
+                match result {
+                    Ok(saved) => {
+                        if let Some(session) = collab.borrow().as_ref() {
+                            session.mark_saved(saved.revision);
+                        }
+                        *saved_base.borrow_mut() = Some(saved.clone());
+                        saved_title.set(saved.title.clone());
+                        saved_content.set(saved.content.clone());
+                        history.set(saved.history.versions());
+                        current_version_index.set(saved.history.len());
+                        base.set(Some(saved));
+                        save_status.set(SaveStatus::Saved);
+                    }
+                    Err(ServiceError::Conflict) => save_status.set(SaveStatus::Conflict),
+                    Err(e) => {
+                        web_sys::console::error_1(&format!("Erro ao salvar automaticamente: {}", e).into());
+                        save_status.set(SaveStatus::Failed);
+                    }
+                }
+            });
+        })
+    };
+
+    let dirty = draft.differs_from(base.as_ref(), &props.user_id);
+    // navegando pelas versões ou com conflito, só o "Salvar" grava
+    let paused = read_only
+        || *current_version_index < history.len()
+        || props.conflict.is_some()
+        || *save_status == SaveStatus::Conflict;
+    {
+        let autosave_timer = autosave_timer.clone();
+        let pending_save = pending_save.clone();
+        let save_status = save_status.clone();
+        let save_draft = save_draft.clone();
+        use_effect_with((draft.clone(), (*base).clone(), paused), move |(_, _, paused)| {
+            autosave_timer.borrow_mut().take();
+            if dirty && !*paused {
+                *pending_save.borrow_mut() = Some(save_draft.clone());
+                *autosave_timer.borrow_mut() = Some(Timeout::new(AUTOSAVE_DELAY_MS, move || save_draft()));
+            } else {
+                pending_save.borrow_mut().take();
+            }
+            match *save_status {
+                SaveStatus::Saved if dirty => save_status.set(SaveStatus::Pending),
+                SaveStatus::Pending if !dirty => save_status.set(SaveStatus::Saved),
+                _ => {}
+            }
+        });
+    }
+    // fechar o editor (ou trocar de nota) grava o que ainda estiver pendente
+    {
+        let autosave_timer = autosave_timer.clone();
+        let pending_save = pending_save.clone();
+        use_effect_with((), move |_| move || {
+            autosave_timer.borrow_mut().take();
+            if let Some(save) = pending_save.borrow_mut().take() {
+                save();
+            }
+        });
+    }
+
+    // Diário local: o texto ainda não gravado sobrevive ao fechamento da aba
+    let journal_key = use_state(|| match props.note.as_ref().and_then(|n| n.id.clone()) {
+        Some(id) => id,
+        None => DraftsService::latest_new(&props.user_id)
+            .map(|(key, _)| key)
+            .unwrap_or_else(DraftsService::new_key),
+    });
+    // rascunho mais novo que a versão salva, oferecido ao abrir o editor
+    let recovered = use_state(|| DraftsService::get(&journal_key, &props.user_id)
+        .filter(|_| !read_only)
+        .filter(|local| match props.note.as_ref() {
+            Some(note) => local.saved_at > note.updated_at
+                && (local.title != note.title || local.content != note.content),
+            None => !local.title.is_empty() || !local.content.is_empty(),
+        }));
+    {
+        let journal_key = journal_key.clone();
+        let user_id = props.user_id.clone();
+        let base_id = base.as_ref().and_then(|n| n.id.clone());
+        let prompting = recovered.is_some();
+        use_effect_with(
+            (draft.clone(), dirty, prompting, (*journal_key).clone(), base_id),
+            move |(draft, dirty, prompting, key, base_id)| {
+                // enquanto a recuperação é oferecida, o rascunho antigo fica
+                if *prompting || read_only {
+                    return;
+                }
+                if *dirty {
+                    DraftsService::save(key, LocalDraft {
+                        user_id,
+                        title: draft.title.clone(),
+                        content: draft.content.clone(),
+                        saved_at: SystemClock.now_millis(),
+                    });
+                } else {
+                    DraftsService::discard(key);
+                }
+                // nota nova já gravada: o diário passa a usar o id dela
+                if let Some(id) = base_id.as_ref().filter(|id| *id != key) {
+                    DraftsService::discard(key);
+                    journal_key.set(id.clone());
+                }
+            },
+        );
+    }
+    let on_recover = {
+        let recovered = recovered.clone();
         let title = title.clone();
         let content = content.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(local) = (*recovered).clone() {
+                title.set(local.title);
+                content.set(local.content);
+                recovered.set(None);
+            }
+        })
+    };
+    let on_discard_recovered = {
+        let recovered = recovered.clone();
+        let journal_key = journal_key.clone();
+        Callback::from(move |_: MouseEvent| {
+            DraftsService::discard(&journal_key);
+            recovered.set(None);
+        })
+    };
+
+    let on_save_click = {
+        let draft = draft.clone();
+        let journal_key = journal_key.clone();
         let on_save = props.on_save.clone();
         let user_id = props.user_id.clone();
-        let existing_note = props.note.clone();
-        let color = text_color.clone();
-        let font = selected_font.clone();
-        let background = background_color.clone();
-        let font_size = font_size.clone();
+        let user_email = props.user_email.clone();
+        let notebook_id = props.notebook_id.clone();
+        let saved_base = saved_base.clone();
         let saved_title_state = saved_title.clone();
         let saved_content_state = saved_content.clone();
+        let collab = collab.clone();
+        let autosave_timer = autosave_timer.clone();
+        let pending_save = pending_save.clone();
 
         Callback::from(move |_| {
-            if title.is_empty() {
+            if draft.title.is_empty() {
                 web_sys::window()
                     .unwrap()
                     .alert_with_message("O título não pode estar vazio!")
                     .unwrap();
                 return;
             }
-            
-            let note = if let Some(existing) = existing_note.as_ref() {
-                let mut n = existing.clone();
-                n.title = (*title).clone();
-                n.content = (*content).clone();
-                n.updated_at = js_sys::Date::now() as i64;
-                let old  = NoteHistory {title: existing.title.clone(), 
-                    content: existing.content.clone(), updated_at: existing.updated_at,};
-                n.history.push(old);
-                n.color = color.clone().to_string();
-                n.background = background.clone().to_string();
-                n.font = font.clone().to_string();
-                n.font_size = Some(*font_size);
-                n
-            } else {
-                Note::new(
-                    (*title).clone(),
-                    (*content).clone(),
-                    user_id.clone(),
-                    font.clone().to_string(),
-                    color.clone().to_string(),
-                    background.clone().to_string()
-                    , Some(*font_size)
-                )
-            };
-            
-            on_save.emit(note);
+            autosave_timer.borrow_mut().take();
+            pending_save.borrow_mut().take();
+            DraftsService::discard(&journal_key);
+
+            let existing = saved_base.borrow().clone();
+            let note = draft.to_note(existing.as_ref(), &user_id, &user_email, notebook_id.clone(), collab.borrow().as_ref());
+            let collab = collab.clone();
+            let on_saved = Callback::from(move |saved: Note| {
+                if let Some(session) = collab.borrow().as_ref() {
+                    session.mark_saved(saved.revision);
+                }
+            });
+            on_save.emit(SaveRequest { note, on_saved });
             // update saved copies so history navigation can restore the latest
-            saved_title_state.set((*title).clone());
-            saved_content_state.set((*content).clone());
+            saved_title_state.set(draft.title.clone());
+            saved_content_state.set(draft.content.clone());
         })
     };
     
+    // Resolução de conflito: todas as opções passam a editar sobre a versão remota
+    let adopt_remote = {
+        let base = base.clone();
+        let history = history.clone();
+        let current_version_index = current_version_index.clone();
+        let save_status = save_status.clone();
+        let on_dismiss = props.on_dismiss_conflict.clone();
+        move |remote: &Note| {
+            save_status.set(SaveStatus::Pending);
+            history.set(remote.history.versions());
+            current_version_index.set(remote.history.len());
+            base.set(Some(remote.clone()));
+            on_dismiss.emit(());
+        }
+    };
+
+    let on_keep_mine = {
+        let adopt_remote = adopt_remote.clone();
+        let conflict = props.conflict.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(remote) = conflict.as_ref() {
+                adopt_remote(remote);
+            }
+        })
+    };
+
+    let on_use_remote = {
+        let adopt_remote = adopt_remote.clone();
+        let conflict = props.conflict.clone();
+        let title = title.clone();
+        let content = content.clone();
+        let saved_title = saved_title.clone();
+        let saved_content = saved_content.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(remote) = conflict.as_ref() {
+                title.set(remote.title.clone());
+                content.set(remote.content.clone());
+                saved_title.set(remote.title.clone());
+                saved_content.set(remote.content.clone());
+                adopt_remote(remote);
+            }
+        })
+    };
+
+    let on_merge = {
+        let adopt_remote = adopt_remote.clone();
+        let conflict = props.conflict.clone();
+        let base = base.clone();
+        let title = title.clone();
+        let content = content.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(remote) = conflict.as_ref() {
+                let (base_title, base_content) = base.as_ref()
+                    .map(|n| (n.title.as_str(), n.content.as_str()))
+                    .unwrap_or_default();
+                let merged = merge3(base_content, &content, &remote.content);
+                if *title == base_title {
+                    title.set(remote.title.clone());
+                }
+                content.set(merged.text);
+                adopt_remote(remote);
+                if merged.conflicts > 0 {
+                    web_sys::window()
+                        .unwrap()
+                        .alert_with_message(&format!(
+                            "{} trecho(s) alterado(s) nas duas versões foram marcados no texto. Revise antes de salvar.",
+                            merged.conflicts
+                        ))
+                        .unwrap();
+                }
+            }
+        })
+    };
+
+    let on_cancel_conflict = {
+        let on_dismiss = props.on_dismiss_conflict.clone();
+        Callback::from(move |_: MouseEvent| on_dismiss.emit(()))
+    };
+
+    let on_toggle_share = {
+        let show_share = show_share.clone();
+        Callback::from(move |_: MouseEvent| show_share.set(!*show_share))
+    };
+
+    let on_share_email = {
+        let share_email = share_email.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            share_email.set(input.value());
+        })
+    };
+
+    let on_share_role = {
+        let share_role = share_role.clone();
+        Callback::from(move |e: Event| {
+            let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+            share_role.set(role_from_value(&select.value()));
+        })
+    };
+
+    let on_add_share = {
+        let acl = acl.clone();
+        let share_email = share_email.clone();
+        let share_role = share_role.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut updated = (*acl).clone();
+            match updated.share(&share_email, *share_role) {
+                Ok(()) => {
+                    acl.set(updated);
+                    share_email.set(String::new());
+                }
+                Err(e) => web_sys::window()
+                    .unwrap()
+                    .alert_with_message(&e.to_string())
+                    .unwrap(),
+            }
+        })
+    };
+
     let on_close_click = {
         let on_close = props.on_close.clone();
         Callback::from(move |_| {
@@ -180,30 +680,98 @@ pub fn note_editor(props: &NoteEditorProps) -> Html {
         })
     };
 
-    // Preview mode
-    let preview = use_state(|| false);
-    let on_toggle_preview = {
-        let preview = preview.clone();
-        Callback::from(move |_| {
-            preview.set(!*preview);
-        })
+    // Visualização: só o texto, lado a lado ou só o Markdown renderizado
+    let view_mode = use_state(|| ViewMode::Edit);
+    let on_view_mode = {
+        let view_mode = view_mode.clone();
+        Callback::from(move |mode: ViewMode| view_mode.set(mode))
+    };
+    let preview_ref = use_node_ref();
+    // HTML sanitizado por bloco: ao digitar, só o bloco alterado é refeito
+    let sanitized = use_mut_ref(HashMap::<String, String>::new);
+    let preview_blocks: Vec<(usize, String)> = if *view_mode == ViewMode::Edit {
+        Vec::new()
+    } else {
+        let mut cache = sanitized.borrow_mut();
+        let mut kept = HashMap::new();
+        let blocks = markdown::blocks(&content, &props.linkable).into_iter()
+            .map(|block| {
+                let html = cache.remove(&block.html)
+                    .or_else(|| kept.get(&block.html).cloned())
+                    .unwrap_or_else(|| markdown::sanitize(&block.html));
+                kept.insert(block.html, html.clone());
+                (block.line, html)
+            })
+            .collect();
+        *cache = kept;
+        blocks
     };
 
-    // Full Markdown renderer using pulldown-cmark + ammonia for sanitization
-    fn markdown_to_html(src: &str) -> String {
-        let mut options = Options::empty();
-        options.insert(Options::ENABLE_TABLES);
-        options.insert(Options::ENABLE_FOOTNOTES);
-        options.insert(Options::ENABLE_STRIKETHROUGH);
-        options.insert(Options::ENABLE_TASKLISTS);
-
-        let parser = Parser::new_ext(src, options);
-        let mut html_output = String::new();
-        html::push_html(&mut html_output, parser);
+    // Caixas de tarefa na visualização: marcar altera a linha no texto, que
+    // é gravado pelo salvamento automático
+    let on_toggle_task = {
+        let content = content.clone();
+        Callback::from(move |index: usize| {
+            if read_only {
+                return;
+            }
+            if let Some(text) = markdown::toggle_task(&content, index) {
+                content.set(text);
+            }
+        })
+    };
 
-        // sanitize generated HTML
-        clean(&html_output)
-    }
+    // Rolagem sincronizada no modo lado a lado; `scrolled_by_sync` evita
+    // que a rolagem feita por um painel volte para ele
+    let scrolled_by_sync = use_mut_ref(|| None::<Pane>);
+    let on_content_scroll = {
+        let cursors_ref = cursors_ref.clone();
+        let preview_ref = preview_ref.clone();
+        let scrolled_by_sync = scrolled_by_sync.clone();
+        let split = *view_mode == ViewMode::Split;
+        let line_count = content.lines().count().max(1) as f64;
+        Callback::from(move |e: Event| {
+            let textarea: web_sys::HtmlTextAreaElement = e.target_unchecked_into();
+            if let Some(overlay) = cursors_ref.cast::<web_sys::Element>() {
+                overlay.set_scroll_top(textarea.scroll_top());
+            }
+            if !split || scrolled_by_sync.borrow_mut().take_if(|p| *p == Pane::Source).is_some() {
+                return;
+            }
+            let Some(preview) = preview_ref.cast::<web_sys::HtmlElement>() else { return };
+            let top = if at_bottom(&textarea) {
+                preview.scroll_height() as f64
+            } else {
+                let line = textarea.scroll_top() as f64 / textarea.scroll_height().max(1) as f64 * line_count;
+                markdown::interpolate(&preview_anchors(&preview, line_count), line)
+            };
+            *scrolled_by_sync.borrow_mut() = Some(Pane::Preview);
+            preview.set_scroll_top(top as i32);
+        })
+    };
+    let on_preview_scroll = {
+        let textarea_ref = textarea_ref.clone();
+        let scrolled_by_sync = scrolled_by_sync.clone();
+        let line_count = content.lines().count().max(1) as f64;
+        Callback::from(move |e: Event| {
+            if scrolled_by_sync.borrow_mut().take_if(|p| *p == Pane::Preview).is_some() {
+                return;
+            }
+            let preview: web_sys::HtmlElement = e.target_unchecked_into();
+            let Some(textarea) = textarea_ref.cast::<web_sys::HtmlTextAreaElement>() else { return };
+            let top = if at_bottom(&preview) {
+                textarea.scroll_height() as f64
+            } else {
+                let anchors: Vec<(f64, f64)> = preview_anchors(&preview, line_count).into_iter()
+                    .map(|(line, top)| (top, line))
+                    .collect();
+                let line = markdown::interpolate(&anchors, preview.scroll_top() as f64);
+                line / line_count * textarea.scroll_height() as f64
+            };
+            *scrolled_by_sync.borrow_mut() = Some(Pane::Source);
+            textarea.set_scroll_top(top as i32);
+        })
+    };
 
     let on_earlier_click = {
         let history = history.clone();
@@ -257,123 +825,106 @@ pub fn note_editor(props: &NoteEditorProps) -> Html {
         })
     };
     
+    // Painel de histórico: as versões anteriores e a última salva
+    let show_history = use_state(|| false);
+    let on_toggle_history = {
+        let show_history = show_history.clone();
+        Callback::from(move |_: MouseEvent| show_history.set(!*show_history))
+    };
+    let on_close_history = {
+        let show_history = show_history.clone();
+        Callback::from(move |()| show_history.set(false))
+    };
+    let versions: Vec<NoteHistory> = match base.as_ref() {
+        Some(note) => history.iter().cloned()
+            .chain(std::iter::once(NoteHistory {
+                title: (*saved_title).clone(),
+                content: (*saved_content).clone(),
+                updated_at: note.updated_at,
+            }))
+            .collect(),
+        None => Vec::new(),
+    };
+    let on_restore_version = {
+        let history = history.clone();
+        let title = title.clone();
+        let content = content.clone();
+        let current_version_index = current_version_index.clone();
+        let saved_title = saved_title.clone();
+        let saved_content = saved_content.clone();
+        let show_history = show_history.clone();
+        Callback::from(move |index: usize| {
+            // o mesmo índice do navegador de versões: `history.len()` é a salva
+            match history.get(index) {
+                Some(version) => {
+                    title.set(version.title.clone());
+                    content.set(version.content.clone());
+                }
+                None => {
+                    title.set((*saved_title).clone());
+                    content.set((*saved_content).clone());
+                }
+            }
+            current_version_index.set(index.min(history.len()));
+            show_history.set(false);
+        })
+    };
+
     let is_new = props.note.is_none();
     let char_count = content.len();
-    let preview_html = markdown_to_html(&*content);
+    let preview_style = format!("font-family: {}; background-color: {}; color: {}; padding: 12px; border-radius: 4px;", *selected_font, *background_color, *text_color);
     
-    // formatting toolbar handlers
-    // helper: convert UTF-16 index (JS selectionStart/End) to Rust byte index
-    let utf16_to_byte_index = |s: &str, target: usize| -> usize {
-        let mut utf16_count: usize = 0;
-        for (byte_idx, ch) in s.char_indices() {
-            let ch_u32 = ch as u32;
-            let ch_utf16_len = if ch_u32 >= 0x10000 { 2 } else { 1 };
-            if utf16_count + ch_utf16_len > target {
-                return byte_idx;
-            }
-            utf16_count += ch_utf16_len;
-        }
-        s.len()
-    };
-
-    // Create reusable actions for bold/italic with toggle support
-    let do_bold = {
+    // barra de formatação e atalhos: a transformação fica em `text::format`
+    let apply_format = {
         let content = content.clone();
         let textarea_ref = textarea_ref.clone();
-        let utf16_to_byte_index = utf16_to_byte_index.clone();
-        Callback::from(move |_: ()| {
+        Callback::from(move |format: Format| {
             if let Some(elem) = textarea_ref.cast::<web_sys::HtmlTextAreaElement>() {
                 let val = elem.value();
-                let start = elem.selection_start().unwrap_or(Some(0)).unwrap_or(0) as u32;
-                let end = elem.selection_end().unwrap_or(Some(0)).unwrap_or(0) as u32;
-
-                if start < end {
-                    let s_byte = utf16_to_byte_index(&val, start as usize);
-                    let e_byte = utf16_to_byte_index(&val, end as usize);
-                    let before = &val[..s_byte];
-                    let selected = &val[s_byte..e_byte];
-                    let after = &val[e_byte..];
-
-                    // toggle: if already wrapped with ** on both sides, remove them
-                    if before.ends_with("**") && after.starts_with("**") {
-                        let new_before = &before[..before.len()-2];
-                        let new_after = &after[2..];
-                        let new = format!("{}{}{}", new_before, selected, new_after);
-                        elem.set_value(&new);
-                        content.set(new);
-                        let new_start = start - 2;
-                        let new_end = end - 2;
-                        let _ = elem.set_selection_range(new_start, new_end);
-                    } else {
-                        let new = format!("{}**{}**{}", before, selected, after);
-                        elem.set_value(&new);
-                        content.set(new);
-                        let new_start = start + 2;
-                        let new_end = end + 2;
-                        let _ = elem.set_selection_range(new_start, new_end);
-                    }
-                } else {
-                    // no selection: insert pair and place caret between
-                    let s_byte = utf16_to_byte_index(&val, start as usize);
-                    let before = &val[..s_byte];
-                    let after = &val[s_byte..];
-                    let new = format!("{}****{}", before, after);
-                    elem.set_value(&new);
-                    content.set(new);
-                    let caret = start + 2;
-                    let _ = elem.set_selection_range(caret, caret);
-                }
+                let start = elem.selection_start().ok().flatten().unwrap_or(0) as usize;
+                let end = elem.selection_end().ok().flatten().unwrap_or(start as u32) as usize;
+                let edit = format.apply(&val, utf16_to_byte(&val, start), utf16_to_byte(&val, end));
+                elem.set_value(&edit.text);
+                let _ = elem.set_selection_range(
+                    byte_to_utf16(&edit.text, edit.start) as u32,
+                    byte_to_utf16(&edit.text, edit.end) as u32,
+                );
+                content.set(edit.text);
                 let _ = elem.focus();
             }
         })
     };
 
-    let do_italic = {
-        let content = content.clone();
-        let textarea_ref = textarea_ref.clone();
-        let utf16_to_byte_index = utf16_to_byte_index.clone();
-        Callback::from(move |_: ()| {
-            if let Some(elem) = textarea_ref.cast::<web_sys::HtmlTextAreaElement>() {
-                let val = elem.value();
-                let start = elem.selection_start().unwrap_or(Some(0)).unwrap_or(0) as u32;
-                let end = elem.selection_end().unwrap_or(Some(0)).unwrap_or(0) as u32;
-
-                if start < end {
-                    let s_byte = utf16_to_byte_index(&val, start as usize);
-                    let e_byte = utf16_to_byte_index(&val, end as usize);
-                    let before = &val[..s_byte];
-                    let selected = &val[s_byte..e_byte];
-                    let after = &val[e_byte..];
-
-                    // toggle: if already wrapped with * on both sides, remove them
-                    if before.ends_with("*") && after.starts_with("*") {
-                        let new_before = &before[..before.len()-1];
-                        let new_after = &after[1..];
-                        let new = format!("{}{}{}", new_before, selected, new_after);
-                        elem.set_value(&new);
-                        content.set(new);
-                        let new_start = start - 1;
-                        let new_end = end - 1;
-                        let _ = elem.set_selection_range(new_start, new_end);
-                    } else {
-                        let new = format!("{}*{}*{}", before, selected, after);
-                        elem.set_value(&new);
-                        content.set(new);
-                        let new_start = start + 1;
-                        let new_end = end + 1;
-                        let _ = elem.set_selection_range(new_start, new_end);
+    // com sugestões de `[[` abertas, as setas, Enter/Tab e Esc são delas
+    let on_content_keydown = {
+        let apply_format = apply_format.clone();
+        let on_pick_link = on_pick_link.clone();
+        let link_query = link_query.clone();
+        let link_selected = link_selected.clone();
+        let link_dismissed = link_dismissed.clone();
+        let suggestions = suggestions.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            if !suggestions.is_empty() {
+                let count = suggestions.len();
+                let selected = (*link_selected).min(count - 1);
+                match e.key().as_str() {
+                    "ArrowDown" => link_selected.set((selected + 1) % count),
+                    "ArrowUp" => link_selected.set((selected + count - 1) % count),
+                    "Enter" | "Tab" => on_pick_link.emit(suggestions[selected].title.clone()),
+                    "Escape" => {
+                        *link_dismissed.borrow_mut() = link_query.as_ref().map(|(at, _)| *at);
+                        link_query.set(None);
                     }
-                } else {
-                    let s_byte = utf16_to_byte_index(&val, start as usize);
-                    let before = &val[..s_byte];
-                    let after = &val[s_byte..];
-                    let new = format!("{}**{}", before, after);
-                    elem.set_value(&new);
-                    content.set(new);
-                    let caret = start + 1;
-                    let _ = elem.set_selection_range(caret, caret);
+                    _ => return,
+                }
+                e.prevent_default();
+                return;
+            }
+            if (e.ctrl_key() || e.meta_key()) && !read_only {
+                if let Some(format) = Format::from_shortcut(&e.code(), e.shift_key(), e.alt_key()) {
+                    e.prevent_default();
+                    apply_format.emit(format);
                 }
-                let _ = elem.focus();
             }
         })
     };
@@ -401,8 +952,119 @@ pub fn note_editor(props: &NoteEditorProps) -> Html {
     
     html! {
         <div class="note-editor">
+            if let Some(remote) = props.conflict.as_ref() {
+                <div class="dialog-overlay">
+                    <div class="conflict-dialog">
+                        <h3>{ "Conflito de edição" }</h3>
+                        <p>{ "Esta nota foi alterada em outro dispositivo enquanto você editava. Escolha como continuar:" }</p>
+                        <div class="conflict-versions">
+                            <div class="conflict-version">
+                                <h4>{ "Minha versão" }</h4>
+                                <strong>{ (*title).clone() }</strong>
+                                <pre>{ (*content).clone() }</pre>
+                            </div>
+                            <div class="conflict-version">
+                                <h4>{ "Versão remota" }</h4>
+                                <strong>{ remote.title.clone() }</strong>
+                                <pre>{ remote.content.clone() }</pre>
+                            </div>
+                        </div>
+                        <div class="editor-actions">
+                            <button onclick={on_cancel_conflict} class="btn-secondary">{ "Cancelar" }</button>
+                            <button onclick={on_use_remote} class="btn-secondary">{ "Usar a remota" }</button>
+                            <button onclick={on_keep_mine} class="btn-secondary">{ "Manter a minha" }</button>
+                            <button onclick={on_merge} class="btn-primary">{ "Mesclar" }</button>
+                        </div>
+                    </div>
+                </div>
+            }
+            if let Some(local) = recovered.as_ref() {
+                <div class="dialog-overlay">
+                    <div class="recover-dialog">
+                        <h3>{ "Rascunho não salvo" }</h3>
+                        <p>{ format!("Há um rascunho desta nota de {} que não chegou a ser salvo. Recuperá-lo?", format_datetime(local.saved_at)) }</p>
+                        <div class="conflict-version">
+                            <strong>{ &local.title }</strong>
+                            <pre>{ &local.content }</pre>
+                        </div>
+                        <div class="editor-actions">
+                            <button onclick={on_discard_recovered} class="btn-secondary">{ "Descartar" }</button>
+                            <button onclick={on_recover} class="btn-primary">{ "Recuperar" }</button>
+                        </div>
+                    </div>
+                </div>
+            }
+            if *show_history {
+                <HistoryPanel
+                    versions={versions}
+                    draft_title={(*title).clone()}
+                    draft_content={(*content).clone()}
+                    read_only={read_only}
+                    on_restore={on_restore_version}
+                    on_close={on_close_history}
+                />
+            }
+            if *show_share {
+                <div class="dialog-overlay">
+                    <div class="share-dialog">
+                        <h3>{ "Compartilhar" }</h3>
+                        <ul class="share-list">
+                            <li>
+                                <span>{ &acl.owner_email }</span>
+                                <span class="share-role">{ Role::Owner.label() }</span>
+                            </li>
+                            { for acl.shares().into_iter().map(|(email, role)| {
+                                let on_change = {
+                                    let acl = acl.clone();
+                                    let email = email.clone();
+                                    Callback::from(move |e: Event| {
+                                        let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                                        let mut updated = (*acl).clone();
+                                        if updated.share(&email, role_from_value(&select.value())).is_ok() {
+                                            acl.set(updated);
+                                        }
+                                    })
+                                };
+                                let on_remove = {
+                                    let acl = acl.clone();
+                                    let email = email.clone();
+                                    Callback::from(move |_: MouseEvent| {
+                                        let mut updated = (*acl).clone();
+                                        updated.unshare(&email);
+                                        acl.set(updated);
+                                    })
+                                };
+                                html! {
+                                    <li>
+                                        <span>{ &email }</span>
+                                        { role_select(role, on_change) }
+                                        <button class="btn-delete" onclick={on_remove} title="Remover acesso">{ "✕" }</button>
+                                    </li>
+                                }
+                            }) }
+                        </ul>
+                        <div class="share-add">
+                            <input
+                                type="email"
+                                placeholder="E-mail"
+                                value={(*share_email).clone()}
+                                oninput={on_share_email}
+                            />
+                            { role_select(*share_role, on_share_role) }
+                            <button onclick={on_add_share} class="btn-secondary">{ "Adicionar" }</button>
+                        </div>
+                        <p class="share-hint">{ "As alterações valem depois de salvar a nota." }</p>
+                        <div class="editor-actions">
+                            <button onclick={on_toggle_share.clone()} class="btn-primary">{ "Concluir" }</button>
+                        </div>
+                    </div>
+                </div>
+            }
             <div class="editor-header">
-                <h2>{ if is_new { "Nova Nota" } else { "Editar Nota" } }</h2>
+                <h2>{ if is_new { "Nova Nota" } else if read_only { "Ver Nota" } else { "Editar Nota" } }</h2>
+                if role == Some(Role::Owner) {
+                    <button onclick={on_toggle_share} class="btn-secondary">{ "Compartilhar" }</button>
+                }
                 <button onclick={&on_close_click} class="btn-close">{ "✕" }</button>
             </div>
             
@@ -413,49 +1075,119 @@ pub fn note_editor(props: &NoteEditorProps) -> Html {
                         class="note-title-input"
                         placeholder="Título da nota"
                         value={(*title).clone()}
+                        readonly={read_only}
                         oninput={on_title_change}
                     />
                 </div>
+
+                <div class="form-group">
+                    <TagInput
+                        tags={(*tags).clone()}
+                        known_tags={props.known_tags.clone()}
+                        on_change={on_tags_change}
+                        readonly={read_only}
+                    />
+                </div>
                 
                 <div class="form-group">
-                    if *preview {
-                        <div class="note-preview" style={format!("font-family: {}; background-color: {}; color: {}; padding: 12px; border-radius: 4px;", *selected_font, *background_color, *text_color)}>
-                            { Html::from_html_unchecked(AttrValue::from(preview_html.clone())) }
-                        </div>
+                    if *view_mode == ViewMode::Preview {
+                        { preview_pane(&preview_blocks, &preview_style, &preview_ref, Callback::noop(), on_toggle_task.clone(), props.on_open_note.clone()) }
                     } else {
                         <div>
+                            if !read_only {
                             <div class="format-toolbar">
-                                <button class="format-btn" onmousedown={Callback::from(|e: MouseEvent| e.prevent_default())} onclick={Callback::from({ let do_bold = do_bold.clone(); move |_: MouseEvent| { do_bold.emit(()) } })} title="Negrito">{"B"}</button>
-                                <button class="format-btn" onmousedown={Callback::from(|e: MouseEvent| e.prevent_default())} onclick={Callback::from({ let do_italic = do_italic.clone(); move |_: MouseEvent| { do_italic.emit(()) } })} title="Itálico">{"I"}</button>
+                                { for format::TOOLBAR.iter().map(|&format| html! {
+                                    <button
+                                        class="format-btn"
+                                        onmousedown={Callback::from(|e: MouseEvent| e.prevent_default())}
+                                        onclick={apply_format.reform(move |_: MouseEvent| format)}
+                                        title={format!("{} ({})", format.label(), format.shortcut())}
+                                    >
+                                        { format.icon() }
+                                    </button>
+                                }) }
                                 <div class="font-size-controls">
                                     <button class="format-btn" onclick={on_decrease_font.clone()} title="Diminuir fonte">{"-"}</button>
                                     <span class="font-size-label">{ format!("{}px", *font_size) }</span>
                                     <button class="format-btn" onclick={on_increase_font.clone()} title="Aumentar fonte">{"+"}</button>
                                 </div>
                             </div>
+                            }
+                            if !remote_cursors.is_empty() {
+                                <div class="collaborators">
+                                    { for remote_cursors.iter().map(|c| html! {
+                                        <span class="collaborator" style={format!("background-color: {};", c.color)} title={c.email.clone()}>
+                                            { c.email.chars().next().unwrap_or('?').to_uppercase().to_string() }
+                                        </span>
+                                    }) }
+                                </div>
+                            }
+                            <div class={classes!("editor-panes", (*view_mode == ViewMode::Split).then_some("editor-split"))}>
+                            <div class="collab-editor">
                             <textarea
                                 ref={textarea_ref.clone()}
                                 class="note-content-input"
                                 placeholder="Escreva sua nota aqui..."
                                 style={format!("font-family: {}; background-color: {}; color: {}; font-size: {}px;", *selected_font, *background_color, *text_color, *font_size)}
                                 value={(*content).clone()}
+                                readonly={read_only}
                                 oninput={on_content_change}
-                                onkeydown={Callback::from({ let do_bold = do_bold.clone(); let do_italic = do_italic.clone(); move |e: KeyboardEvent| {
-                                    if e.ctrl_key() || e.meta_key() {
-                                        let k = e.key();
-                                        if k.eq_ignore_ascii_case("b") {
-                                            e.prevent_default();
-                                            do_bold.emit(());
-                                        } else if k.eq_ignore_ascii_case("i") {
-                                            e.prevent_default();
-                                            do_italic.emit(());
-                                        }
-                                    }
-                                } })}
+                                onscroll={on_content_scroll}
+                                onselect={on_caret.reform(|_: Event| ())}
+                                onclick={on_caret.reform(|_: MouseEvent| ())}
+                                onkeyup={on_caret.reform(|_: KeyboardEvent| ())}
+                                onkeydown={on_content_keydown}
                             />
+                            if !suggestions.is_empty() {
+                                <ul class="wiki-suggestions">
+                                    { for suggestions.iter().enumerate().map(|(i, note)| {
+                                        let title = note.title.clone();
+                                        html! {
+                                            <li
+                                                class={classes!("wiki-suggestion", (i == *link_selected).then_some("active"))}
+                                                onmousedown={Callback::from(|e: MouseEvent| e.prevent_default())}
+                                                onclick={on_pick_link.reform(move |_: MouseEvent| title.clone())}
+                                            >
+                                                { &note.title }
+                                            </li>
+                                        }
+                                    }) }
+                                </ul>
+                            }
+                            if !remote_cursors.is_empty() {
+                                <div
+                                    ref={cursors_ref.clone()}
+                                    class="remote-cursors"
+                                    style={format!("font-family: {}; font-size: {}px;", *selected_font, *font_size)}
+                                >
+                                    { remote_cursor_overlay(&content, &remote_cursors) }
+                                </div>
+                            }
+                            </div>
+                            if *view_mode == ViewMode::Split {
+                                { preview_pane(&preview_blocks, &preview_style, &preview_ref, on_preview_scroll, on_toggle_task, props.on_open_note.clone()) }
+                            }
+                            </div>
                         </div>
                     }
                 </div>
+                if !props.backlinks.is_empty() {
+                    <div class="backlinks">
+                        <h4>{ "Referenciado por" }</h4>
+                        <ul>
+                            { for props.backlinks.iter().map(|note| {
+                                let id = note.id.clone();
+                                html! {
+                                    <li>
+                                        <button class="backlink" onclick={props.on_open_note.reform(move |_: MouseEvent| id.clone())}>
+                                            { if note.title.is_empty() { "Sem título" } else { &note.title } }
+                                        </button>
+                                    </li>
+                                }
+                            }) }
+                        </ul>
+                    </div>
+                }
                 <div class="dropdown-buttons">
                     <BackgroundDropdown selected_background={(*background_color).clone()} on_select={on_background_select} />
                     <ColorDropdown selected_color={(*text_color).clone()} on_select={on_color_select} />
@@ -464,21 +1196,282 @@ pub fn note_editor(props: &NoteEditorProps) -> Html {
                 
                 <div class="editor-footer">
                     <span class="char-count">{ format!("{} caracteres", char_count) }</span>
+                    if !read_only && (dirty || base.is_some()) {
+                        <span class={classes!("save-status", save_status.class())}>
+                            { if dirty && draft.title.is_empty() { "Sem título: não salvo" } else { save_status.label() } }
+                        </span>
+                    }
                     <div class="editor-actions">
-                        <button onclick={on_toggle_preview.clone()} class="btn-secondary">
-                            { if *preview { "Editar" } else { "Visualizar" } }
-                        </button>
+                        <div class="view-modes">
+                            { for [ViewMode::Edit, ViewMode::Split, ViewMode::Preview].into_iter().map(|mode| html! {
+                                <button
+                                    class={classes!("btn-secondary", (*view_mode == mode).then_some("active"))}
+                                    onclick={on_view_mode.reform(move |_: MouseEvent| mode)}
+                                >
+                                    { mode.label() }
+                                </button>
+                            }) }
+                        </div>
                         <button onclick={&on_earlier_click} class="btn-secondary" disabled={ *current_version_index == 0 }>{ "Versão Anterior" }</button>
                         <button onclick={&on_later_click} class="btn-secondary" disabled={ *current_version_index >= history.len() }>{ "Versão Seguinte" }</button>
+                        if !is_new {
+                            <button onclick={on_toggle_history} class="btn-secondary">{ "Histórico" }</button>
+                        }
                         <button onclick={&on_close_click} class="btn-secondary">
-                            { "Cancelar" }
-                        </button>
-                        <button onclick={on_save_click} class="btn-primary">
-                            { "Salvar" }
+                            { "Fechar" }
                         </button>
+                        if !read_only {
+                            <button onclick={on_save_click} class="btn-primary">
+                                { "Salvar" }
+                            </button>
+                        }
                     </div>
                 </div>
             </div>
         </div>
     }
 }
+
+/// Como o editor mostra o texto.
+#[derive(Clone, Copy, PartialEq)]
+enum ViewMode {
+    Edit,
+    /// Texto e visualização lado a lado, com a rolagem sincronizada.
+    Split,
+    Preview,
+}
+
+impl ViewMode {
+    fn label(self) -> &'static str {
+        match self {
+            ViewMode::Edit => "Editar",
+            ViewMode::Split => "Lado a lado",
+            ViewMode::Preview => "Visualizar",
+        }
+    }
+}
+
+/// Painel do modo lado a lado que rolou por último por sincronização.
+#[derive(Clone, Copy, PartialEq)]
+enum Pane {
+    Source,
+    Preview,
+}
+
+/// Situação do salvamento automático, mostrada no rodapé.
+#[derive(Clone, Copy, PartialEq)]
+enum SaveStatus {
+    Saved,
+    Pending,
+    Saving,
+    Failed,
+    /// Outra gravação chegou antes; o "Salvar" abre o diálogo de conflito.
+    Conflict,
+}
+
+impl SaveStatus {
+    fn label(self) -> &'static str {
+        match self {
+            SaveStatus::Saved => "Salvo",
+            SaveStatus::Pending => "Alterações não salvas",
+            SaveStatus::Saving => "Salvando…",
+            SaveStatus::Failed => "Erro ao salvar",
+            SaveStatus::Conflict => "Alterada em outro lugar: clique em Salvar",
+        }
+    }
+
+    fn class(self) -> &'static str {
+        match self {
+            SaveStatus::Saved => "saved",
+            SaveStatus::Pending | SaveStatus::Saving => "pending",
+            SaveStatus::Failed | SaveStatus::Conflict => "error",
+        }
+    }
+}
+
+/// Campos editáveis da nota como estão no editor.
+#[derive(Clone, PartialEq)]
+struct Draft {
+    title: String,
+    content: String,
+    tags: Vec<String>,
+    font: String,
+    color: String,
+    background: String,
+    font_size: u8,
+    editors: Vec<String>,
+    viewers: Vec<String>,
+    shared_with: Vec<String>,
+}
+
+impl Draft {
+    /// Se há algo a gravar em relação à versão salva (`None` para nota nova);
+    /// o compartilhamento só conta para o dono.
+    fn differs_from(&self, saved: Option<&Note>, user_id: &str) -> bool {
+        let Some(note) = saved else {
+            return !self.title.is_empty() || !self.content.is_empty();
+        };
+        self.title != note.title
+            || self.content != note.content
+            || self.tags != note.tags
+            || self.font != note.font
+            || self.color != note.color
+            || self.background != note.background
+            || self.font_size != note.font_size.unwrap_or(16)
+            || (note.user_id == user_id
+                && (self.editors != note.editors || self.viewers != note.viewers || self.shared_with != note.shared_with))
+    }
+
+    /// A nota a gravar: a salva com as alterações (e a versão anterior no
+    /// histórico) ou uma nota nova.
+    fn to_note(&self, existing: Option<&Note>, user_id: &str, user_email: &str,
+        notebook_id: Option<String>, collab: Option<&CollabSession>) -> Note {
+        let Some(existing) = existing else {
+            let mut n = Note::new(self.title.clone(), self.content.clone(), user_id.to_string(),
+                self.font.clone(), self.color.clone(), self.background.clone(), Some(self.font_size));
+            n.owner_email = user_email.to_string();
+            n.notebook_id = notebook_id;
+            n.tags = self.tags.clone();
+            n.editors = self.editors.clone();
+            n.viewers = self.viewers.clone();
+            n.shared_with = self.shared_with.clone();
+            return n;
+        };
+        let mut n = existing.clone();
+        n.title = self.title.clone();
+        n.content = self.content.clone();
+        n.updated_at = SystemClock.now_millis();
+        let old = NoteHistory { title: existing.title.clone(),
+            content: existing.content.clone(), updated_at: existing.updated_at };
+        // gravações próximas (o salvamento automático) viram uma só versão
+        n.history.record(old, &NotesService::history_policy());
+        n.color = self.color.clone();
+        n.background = self.background.clone();
+        n.font = self.font.clone();
+        n.font_size = Some(self.font_size);
+        n.tags = self.tags.clone();
+        // só o dono altera o compartilhamento
+        if n.user_id == user_id {
+            n.editors = self.editors.clone();
+            n.viewers = self.viewers.clone();
+            n.shared_with = self.shared_with.clone();
+        }
+        if let Some(session) = collab {
+            // outro participante pode ter gravado o mesmo texto antes
+            n.revision = n.revision.max(session.saved_revision());
+        }
+        n
+    }
+}
+
+/// Markdown renderizado, um `div` por bloco com a linha onde ele começa no
+/// texto. As chaves vêm do conteúdo, para que digitar num bloco não refaça os
+/// outros. Clicar numa caixa de tarefa emite o número dela em `on_task`;
+/// num `[[link]]`, o id da nota em `on_open_note`.
+fn preview_pane(blocks: &[(usize, String)], style: &str, node_ref: &NodeRef, onscroll: Callback<Event>,
+    on_task: Callback<usize>, on_open_note: Callback<String>) -> Html {
+    let mut seen = HashMap::<&str, usize>::new();
+    let onclick = Callback::from(move |e: MouseEvent| {
+        if let Some(index) = clicked_task(&e) {
+            // a caixa só muda quando o texto mudar
+            e.prevent_default();
+            on_task.emit(index);
+        } else if let Some(id) = clicked_note(&e) {
+            e.prevent_default();
+            on_open_note.emit(id);
+        }
+    });
+    html! {
+        <div class="note-preview" ref={node_ref.clone()} {onscroll} {onclick} style={style.to_string()}>
+            { for blocks.iter().map(|(line, html)| {
+                let occurrence = seen.entry(html.as_str()).or_default();
+                *occurrence += 1;
+                let mut hasher = DefaultHasher::new();
+                (html, *occurrence).hash(&mut hasher);
+                html! {
+                    <div class="preview-block" key={hasher.finish().to_string()} data-line={line.to_string()}>
+                        { Html::from_html_unchecked(AttrValue::from(html.clone())) }
+                    </div>
+                }
+            }) }
+        </div>
+    }
+}
+
+/// Número da caixa de tarefa clicada (`data-task`), se o clique foi numa.
+pub(crate) fn clicked_task(e: &MouseEvent) -> Option<usize> {
+    let target: web_sys::Element = e.target_dyn_into()?;
+    if target.tag_name() != "INPUT" {
+        return None;
+    }
+    target.get_attribute("data-task")?.parse().ok()
+}
+
+/// Nota do `[[link]]` clicado (`data-note`), se o clique foi num.
+pub(crate) fn clicked_note(e: &MouseEvent) -> Option<String> {
+    let target: web_sys::Element = e.target_dyn_into()?;
+    target.closest("a[data-note]").ok()??.get_attribute("data-note")
+}
+
+/// Linha do texto e posição na visualização do começo de cada bloco, do
+/// início ao fim do documento.
+fn preview_anchors(preview: &web_sys::HtmlElement, line_count: f64) -> Vec<(f64, f64)> {
+    let children = preview.children();
+    let mut anchors = vec![(0.0, 0.0)];
+    for i in 0..children.length() {
+        let Some(block) = children.item(i).and_then(|c| c.dyn_into::<web_sys::HtmlElement>().ok()) else { continue };
+        let Some(line) = block.get_attribute("data-line").and_then(|l| l.parse::<f64>().ok()) else { continue };
+        let top = block.offset_top() as f64;
+        // blocos fora de ordem (não deveria acontecer) não entram
+        if anchors.last().is_some_and(|&(l, t)| line >= l && top >= t) {
+            anchors.push((line, top));
+        }
+    }
+    anchors.push((line_count, preview.scroll_height() as f64));
+    anchors
+}
+
+fn at_bottom(elem: &web_sys::Element) -> bool {
+    elem.scroll_top() + elem.client_height() >= elem.scroll_height() - 1
+}
+
+/// Cópia invisível do texto com marcadores nas posições dos cursores remotos,
+/// sobreposta ao `<textarea>` (que não consegue desenhar outros cursores).
+fn remote_cursor_overlay(text: &str, cursors: &[RemoteCursor]) -> Html {
+    let mut sorted: Vec<&RemoteCursor> = cursors.iter().collect();
+    sorted.sort_by_key(|c| c.index);
+
+    let mut parts = Vec::new();
+    let mut pos = 0;
+    for cursor in sorted {
+        let at = utf16_to_byte(text, cursor.index).max(pos);
+        parts.push(html! { { &text[pos..at] } });
+        parts.push(html! {
+            <span class="remote-cursor" style={format!("border-color: {};", cursor.color)}>
+                <span class="remote-cursor-label" style={format!("background-color: {};", cursor.color)}>
+                    { &cursor.email }
+                </span>
+            </span>
+        });
+        pos = at;
+    }
+    // o textarea reserva uma linha depois de uma quebra final
+    parts.push(html! { { format!("{}\n", &text[pos..]) } });
+    parts.into_iter().collect::<Html>()
+}
+
+fn role_from_value(value: &str) -> Role {
+    match value {
+        "viewer" => Role::Viewer,
+        _ => Role::Editor,
+    }
+}
+
+fn role_select(selected: Role, on_change: Callback<Event>) -> Html {
+    html! {
+        <select class="share-role" onchange={on_change}>
+            <option value="editor" selected={selected == Role::Editor}>{ Role::Editor.label() }</option>
+            <option value="viewer" selected={selected == Role::Viewer}>{ Role::Viewer.label() }</option>
+        </select>
+    }
+}
@@ -0,0 +1,181 @@
+// src/components/notebook_list.rs
+use std::collections::{HashMap, HashSet};
+use yew::prelude::*;
+use yew_router::prelude::*;
+use notepad_models::{Notebook, NotebookTree};
+use crate::Route;
+
+/// Formato do id da nota arrastada para um caderno.
+pub const NOTE_DRAG_FORMAT: &str = "application/x-notepad-note";
+
+#[derive(Properties, PartialEq)]
+pub struct NotebookListProps {
+    pub notebooks: Vec<Notebook>,
+    /// Caderno aberto; `None` mostra todas as notas.
+    #[prop_or_default]
+    pub selected: Option<String>,
+    /// Notas diretamente em cada caderno.
+    #[prop_or_default]
+    pub counts: HashMap<String, usize>,
+    /// Novo caderno dentro do indicado (`None` na raiz).
+    pub on_create: Callback<Option<String>>,
+    pub on_rename: Callback<Notebook>,
+    pub on_delete: Callback<Notebook>,
+    /// Nota solta sobre um caderno: (id da nota, caderno de destino).
+    pub on_move_note: Callback<(String, Option<String>)>,
+}
+
+/// Estado compartilhado pelos nós da árvore.
+struct TreeContext<'a> {
+    props: &'a NotebookListProps,
+    tree: NotebookTree<'a>,
+    collapsed: UseStateHandle<HashSet<String>>,
+    /// Destino sob a nota arrastada (`Some(None)` é a raiz).
+    drop_target: UseStateHandle<Option<Option<String>>>,
+}
+
+/// Árvore de cadernos da barra lateral; as notas podem ser arrastadas para
+/// um caderno ou para "Todas as notas", que as tira de qualquer caderno.
+#[function_component(NotebookList)]
+pub fn notebook_list(props: &NotebookListProps) -> Html {
+    let collapsed = use_state(HashSet::<String>::new);
+    let drop_target = use_state(|| None::<Option<String>>);
+
+    let on_create_root = {
+        let on_create = props.on_create.clone();
+        Callback::from(move |_: MouseEvent| on_create.emit(None))
+    };
+
+    let ctx = TreeContext {
+        props,
+        tree: NotebookTree::new(&props.notebooks),
+        collapsed,
+        drop_target,
+    };
+    let root_active = props.selected.is_none();
+
+    html! {
+        <nav class="notebook-tree">
+            <div class="notebook-tree-header">
+                <h2 class="sidebar-section">{ "Cadernos" }</h2>
+                <button class="notebook-action" onclick={on_create_root} title="Novo caderno">{ "+" }</button>
+            </div>
+            <div
+                class={classes!("notebook-node", root_active.then_some("active"), (*ctx.drop_target == Some(None)).then_some("drop-target"))}
+                ondragover={drag_over(&ctx, None)}
+                ondragleave={drag_leave(&ctx)}
+                ondrop={drop_note(&ctx, None)}
+            >
+                <span class="notebook-toggle"></span>
+                <Link<Route> to={Route::Dashboard} classes="notebook-name">{ "📚 Todas as notas" }</Link<Route>>
+            </div>
+            <ul class="notebook-children">
+                { for ctx.tree.children(None).into_iter().map(|n| node(&ctx, n)) }
+            </ul>
+        </nav>
+    }
+}
+
+fn node(ctx: &TreeContext, notebook: &Notebook) -> Html {
+    let Some(id) = notebook.id.clone() else { return Html::default() };
+    let children = ctx.tree.children(Some(&id));
+    let collapsed = ctx.collapsed.contains(&id);
+    let active = ctx.props.selected.as_deref() == Some(id.as_str());
+    let target = *ctx.drop_target == Some(Some(id.clone()));
+    let count = ctx.props.counts.get(&id).copied().unwrap_or(0);
+
+    let on_toggle = {
+        let state = ctx.collapsed.clone();
+        let id = id.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut updated = (*state).clone();
+            if !updated.remove(&id) {
+                updated.insert(id.clone());
+            }
+            state.set(updated);
+        })
+    };
+    let on_create = {
+        let on_create = ctx.props.on_create.clone();
+        let id = id.clone();
+        Callback::from(move |_: MouseEvent| on_create.emit(Some(id.clone())))
+    };
+    let on_rename = {
+        let on_rename = ctx.props.on_rename.clone();
+        let notebook = notebook.clone();
+        Callback::from(move |_: MouseEvent| on_rename.emit(notebook.clone()))
+    };
+    let on_delete = {
+        let on_delete = ctx.props.on_delete.clone();
+        let notebook = notebook.clone();
+        Callback::from(move |_: MouseEvent| on_delete.emit(notebook.clone()))
+    };
+
+    html! {
+        <li key={id.clone()}>
+            <div
+                class={classes!("notebook-node", active.then_some("active"), target.then_some("drop-target"))}
+                ondragover={drag_over(ctx, Some(id.clone()))}
+                ondragleave={drag_leave(ctx)}
+                ondrop={drop_note(ctx, Some(id.clone()))}
+            >
+                <button class="notebook-toggle" onclick={on_toggle} disabled={children.is_empty()}>
+                    { if children.is_empty() { "" } else if collapsed { "▸" } else { "▾" } }
+                </button>
+                <Link<Route> to={Route::Notebook { id: id.clone() }} classes="notebook-name">
+                    { format!("📁 {}", notebook.name) }
+                </Link<Route>>
+                if count > 0 {
+                    <span class="notebook-count">{ count }</span>
+                }
+                <span class="notebook-actions">
+                    <button class="notebook-action" onclick={on_create} title="Novo caderno dentro deste">{ "+" }</button>
+                    <button class="notebook-action" onclick={on_rename} title="Renomear">{ "✏️" }</button>
+                    <button class="notebook-action" onclick={on_delete} title="Excluir caderno">{ "🗑️" }</button>
+                </span>
+            </div>
+            if !collapsed && !children.is_empty() {
+                <ul class="notebook-children">
+                    { for children.into_iter().map(|n| node(ctx, n)) }
+                </ul>
+            }
+        </li>
+    }
+}
+
+/// Aceita a nota arrastada (o navegador só permite soltar se o evento for
+/// cancelado) e destaca o caderno.
+fn drag_over(ctx: &TreeContext, target: Option<String>) -> Callback<DragEvent> {
+    let drop_target = ctx.drop_target.clone();
+    Callback::from(move |e: DragEvent| {
+        let carries_note = e.data_transfer()
+            .is_some_and(|dt| dt.types().includes(&NOTE_DRAG_FORMAT.into(), 0));
+        if !carries_note {
+            return;
+        }
+        e.prevent_default();
+        if *drop_target != Some(target.clone()) {
+            drop_target.set(Some(target.clone()));
+        }
+    })
+}
+
+fn drag_leave(ctx: &TreeContext) -> Callback<DragEvent> {
+    let drop_target = ctx.drop_target.clone();
+    Callback::from(move |_: DragEvent| drop_target.set(None))
+}
+
+fn drop_note(ctx: &TreeContext, target: Option<String>) -> Callback<DragEvent> {
+    let drop_target = ctx.drop_target.clone();
+    let on_move_note = ctx.props.on_move_note.clone();
+    Callback::from(move |e: DragEvent| {
+        e.prevent_default();
+        drop_target.set(None);
+        let note_id = e.data_transfer()
+            .and_then(|dt| dt.get_data(NOTE_DRAG_FORMAT).ok())
+            .filter(|id| !id.is_empty());
+        if let Some(note_id) = note_id {
+            on_move_note.emit((note_id, target.clone()));
+        }
+    })
+}
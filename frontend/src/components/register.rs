@@ -8,9 +8,9 @@ use crate::Route;
 #[function_component(Register)]
 pub fn register() -> Html {
     let navigator = use_navigator().unwrap();
-    let email = use_state(|| String::new());
-    let password = use_state(|| String::new());
-    let confirm_password = use_state(|| String::new());
+    let email = use_state(String::new);
+    let password = use_state(String::new);
+    let confirm_password = use_state(String::new);
     let error = use_state(|| None::<String>);
     let loading = use_state(|| false);
     
@@ -73,7 +73,7 @@ pub fn register() -> Html {
                         navigator.push(&Route::Dashboard);
                     }
                     Err(e) => {
-                        error.set(Some(e));
+                        error.set(Some(format!("Erro ao registrar: {}", e)));
                         loading.set(false);
                     }
                 }
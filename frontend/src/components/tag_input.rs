@@ -0,0 +1,144 @@
+// src/components/tag_input.rs
+use yew::prelude::*;
+use notepad_models::normalize_tag;
+use crate::text::search::fold;
+
+/// Sugestões mostradas enquanto se digita.
+const MAX_SUGGESTIONS: usize = 6;
+
+#[derive(Properties, PartialEq)]
+pub struct TagInputProps {
+    pub tags: Vec<String>,
+    /// Etiquetas já usadas em outras notas, para o autocompletar.
+    #[prop_or_default]
+    pub known_tags: Vec<String>,
+    pub on_change: Callback<Vec<String>>,
+    #[prop_or_default]
+    pub readonly: bool,
+}
+
+/// Etiquetas da nota como chips; Enter ou vírgula adiciona o que foi digitado
+/// e Backspace com o campo vazio remove a última.
+#[function_component(TagInput)]
+pub fn tag_input(props: &TagInputProps) -> Html {
+    let input = use_state(String::new);
+
+    let add_tag = {
+        let tags = props.tags.clone();
+        let on_change = props.on_change.clone();
+        let input = input.clone();
+        Callback::from(move |tag: String| {
+            if let Some(tag) = normalize_tag(&tag) {
+                if !tags.contains(&tag) {
+                    let mut updated = tags.clone();
+                    updated.push(tag);
+                    on_change.emit(updated);
+                }
+            }
+            input.set(String::new());
+        })
+    };
+
+    let remove_tag = {
+        let tags = props.tags.clone();
+        let on_change = props.on_change.clone();
+        Callback::from(move |tag: String| {
+            on_change.emit(tags.iter().filter(|t| **t != tag).cloned().collect());
+        })
+    };
+
+    let on_input = {
+        let input = input.clone();
+        let add_tag = add_tag.clone();
+        Callback::from(move |e: InputEvent| {
+            let elem: web_sys::HtmlInputElement = e.target_unchecked_into();
+            let value = elem.value();
+            match value.strip_suffix(',') {
+                Some(tag) => add_tag.emit(tag.to_string()),
+                None => input.set(value),
+            }
+        })
+    };
+
+    let on_keydown = {
+        let input = input.clone();
+        let add_tag = add_tag.clone();
+        let remove_tag = remove_tag.clone();
+        let last = props.tags.last().cloned();
+        Callback::from(move |e: KeyboardEvent| match e.key().as_str() {
+            "Enter" => {
+                e.prevent_default();
+                add_tag.emit((*input).clone());
+            }
+            "Backspace" if input.is_empty() => {
+                if let Some(last) = &last {
+                    remove_tag.emit(last.clone());
+                }
+            }
+            _ => {}
+        })
+    };
+
+    let typed = fold(input.trim().trim_start_matches('#'));
+    let suggestions: Vec<&String> = if typed.is_empty() {
+        Vec::new()
+    } else {
+        props.known_tags.iter()
+            .filter(|t| !props.tags.contains(t) && fold(t).starts_with(&typed))
+            .take(MAX_SUGGESTIONS)
+            .collect()
+    };
+
+    html! {
+        <div class="tag-input">
+            { for props.tags.iter().map(|tag| html! {
+                <span class="tag-chip">
+                    { format!("#{}", tag) }
+                    if !props.readonly {
+                        <button
+                            class="tag-remove"
+                            title="Remover etiqueta"
+                            onclick={{
+                                let remove_tag = remove_tag.clone();
+                                let tag = tag.clone();
+                                Callback::from(move |_| remove_tag.emit(tag.clone()))
+                            }}
+                        >
+                            { "×" }
+                        </button>
+                    }
+                </span>
+            }) }
+            if !props.readonly {
+                <div class="tag-field">
+                    <input
+                        type="text"
+                        placeholder="Adicionar etiqueta"
+                        value={(*input).clone()}
+                        oninput={on_input}
+                        onkeydown={on_keydown}
+                    />
+                    if !suggestions.is_empty() {
+                        <div class="tag-suggestions">
+                            { for suggestions.into_iter().map(|tag| {
+                                let add_tag = add_tag.clone();
+                                let label = format!("#{}", tag);
+                                let tag = tag.clone();
+                                html! {
+                                    <button
+                                        class="dropdown-item"
+                                        // antes do blur do campo
+                                        onmousedown={Callback::from(|e: MouseEvent| e.prevent_default())}
+                                        onclick={Callback::from(move |_| add_tag.emit(tag.clone()))}
+                                    >
+                                        { label }
+                                    </button>
+                                }
+                            }) }
+                        </div>
+                    }
+                </div>
+            }
+        </div>
+    }
+}
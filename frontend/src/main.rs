@@ -1,10 +1,11 @@
 // src/main.rs
 use yew::prelude::*;
 use yew_router::prelude::*;
+use notepad_models::NoteStatus;
 
 mod components;
 mod services;
-mod models;
+mod text;
 
 use components::{login::Login, register::Register, dashboard::Dashboard};
 
@@ -16,6 +17,16 @@ enum Route {
     Register,
     #[at("/dashboard")]
     Dashboard,
+    #[at("/dashboard/new")]
+    NewNote,
+    #[at("/dashboard/note/:id")]
+    Note { id: String },
+    #[at("/dashboard/notebook/:id")]
+    Notebook { id: String },
+    #[at("/dashboard/archived")]
+    Archived,
+    #[at("/dashboard/trash")]
+    Trash,
 }
 
 fn switch(routes: Route) -> Html {
@@ -23,6 +34,11 @@ fn switch(routes: Route) -> Html {
         Route::Login => html! { <Login /> },
         Route::Register => html! { <Register /> },
         Route::Dashboard => html! { <Dashboard /> },
+        Route::NewNote => html! { <Dashboard new_note=true /> },
+        Route::Note { id } => html! { <Dashboard note_id={Some(id)} /> },
+        Route::Notebook { id } => html! { <Dashboard notebook_id={Some(id)} /> },
+        Route::Archived => html! { <Dashboard view={NoteStatus::Archived} /> },
+        Route::Trash => html! { <Dashboard view={NoteStatus::Trashed} /> },
     }
 }
 
@@ -36,5 +52,6 @@ fn app() -> Html {
 }
 
 fn main() {
+    services::sync::SyncService::start();
     yew::Renderer::<App>::new().render();
 }
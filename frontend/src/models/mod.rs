@@ -1,3 +0,0 @@
-
-// src/models/mod.rs
-pub mod note;
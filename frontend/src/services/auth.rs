@@ -2,7 +2,8 @@
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::JsFuture;
 use js_sys::{Reflect, Function};
-use crate::models::note::User;
+use notepad_models::User;
+use crate::services::error::ServiceError;
 
 #[wasm_bindgen]
 extern "C" {
@@ -14,7 +15,7 @@ extern "C" {
 pub struct AuthService;
 
 impl AuthService {
-    pub async fn register(email: &str, password: &str) -> Result<User, String> {
+    pub async fn register(email: &str, password: &str) -> Result<User, ServiceError> {
         let email_val = JsValue::from_str(email);
         let password_val = JsValue::from_str(password);
         
@@ -25,15 +26,32 @@ impl AuthService {
             })
         });
 
-        let promise = promise_res.map_err(|e| format!("Erro ao chamar função: {:?}", e))?;
+        let promise = promise_res.map_err(|e| ServiceError::from_js(&e))?;
         
         let result = JsFuture::from(js_sys::Promise::from(promise)).await
-            .map_err(|e| Self::map_auth_error(&e, "Erro ao registrar"))?;
-        
+            .map_err(|e| ServiceError::from_js(&e))?;
+
+        // as notas compartilhadas com o e-mail só ficam acessíveis depois da confirmação
+        if let Err(e) = Self::send_verification(&result).await {
+            web_sys::console::error_1(&format!("Erro ao enviar a confirmação de e-mail: {}", e).into());
+        }
         Self::parse_user_from_credential(&result)
     }
+
+    async fn send_verification(cred: &JsValue) -> Result<(), ServiceError> {
+        let user = Reflect::get(cred, &JsValue::from_str("user"))
+            .map_err(|_| ServiceError::missing("Usuário"))?;
+        let promise = Reflect::get(&user, &JsValue::from_str("sendEmailVerification")).and_then(|f| {
+            let func: Function = f.into();
+            func.call0(&user)
+        }).map_err(|e| ServiceError::from_js(&e))?;
+
+        JsFuture::from(js_sys::Promise::from(promise)).await
+            .map_err(|e| ServiceError::from_js(&e))?;
+        Ok(())
+    }
     
-    pub async fn login(email: &str, password: &str) -> Result<User, String> {
+    pub async fn login(email: &str, password: &str) -> Result<User, ServiceError> {
         let email_val = JsValue::from_str(email);
         let password_val = JsValue::from_str(password);
         
@@ -44,15 +62,15 @@ impl AuthService {
             })
         });
 
-        let promise = promise_res.map_err(|e| format!("Erro ao chamar função: {:?}", e))?;
+        let promise = promise_res.map_err(|e| ServiceError::from_js(&e))?;
         
         let result = JsFuture::from(js_sys::Promise::from(promise)).await
-            .map_err(|e| Self::map_auth_error(&e, "Erro ao fazer login"))?;
+            .map_err(|e| ServiceError::from_js(&e))?;
         
         Self::parse_user_from_credential(&result)
     }
     
-    pub async fn logout() -> Result<(), String> {
+    pub async fn logout() -> Result<(), ServiceError> {
         let promise_res = AUTH.with(|auth| {
             Reflect::get(auth, &JsValue::from_str("signOut")).and_then(|f| {
                 let func: Function = f.into();
@@ -60,10 +78,10 @@ impl AuthService {
             })
         });
 
-        let promise = promise_res.map_err(|e| format!("Erro ao chamar função: {:?}", e))?;
+        let promise = promise_res.map_err(|e| ServiceError::from_js(&e))?;
         
         JsFuture::from(js_sys::Promise::from(promise)).await
-            .map_err(|e| Self::map_auth_error(&e, "Erro ao sair"))?;
+            .map_err(|e| ServiceError::from_js(&e))?;
         Ok(())
     }
     
@@ -83,46 +101,39 @@ impl AuthService {
         })
     }
     
-    fn parse_user_from_credential(cred: &JsValue) -> Result<User, String> {
+    /// Token de ID do Firebase do usuário logado, que o servidor `backend/`
+    /// verifica; o SDK o renova quando está para expirar.
+    pub async fn id_token() -> Result<String, ServiceError> {
+        let user = AUTH.with(|auth| Reflect::get(auth, &JsValue::from_str("currentUser")))
+            .map_err(|e| ServiceError::from_js(&e))?;
+        if user.is_null() || user.is_undefined() {
+            return Err(ServiceError::PermissionDenied);
+        }
+
+        let promise = Reflect::get(&user, &JsValue::from_str("getIdToken")).and_then(|f| {
+            let func: Function = f.into();
+            func.call0(&user)
+        }).map_err(|e| ServiceError::from_js(&e))?;
+
+        let token = JsFuture::from(js_sys::Promise::from(promise)).await
+            .map_err(|e| ServiceError::from_js(&e))?;
+        token.as_string().ok_or_else(|| ServiceError::Serialization("Token de ID inválido".to_string()))
+    }
+    
+    fn parse_user_from_credential(cred: &JsValue) -> Result<User, ServiceError> {
         let user_obj = js_sys::Reflect::get(cred, &"user".into())
-            .map_err(|_| "Não foi possível obter dados do usuário")?;
+            .map_err(|_| ServiceError::missing("Usuário"))?;
         
         let uid = js_sys::Reflect::get(&user_obj, &"uid".into())
-            .map_err(|_| "UID não encontrado")?
+            .map_err(|_| ServiceError::missing("UID"))?
             .as_string()
-            .ok_or("UID inválido")?;
+            .ok_or_else(|| ServiceError::Serialization("UID inválido".to_string()))?;
         
         let email = js_sys::Reflect::get(&user_obj, &"email".into())
-            .map_err(|_| "Email não encontrado")?
+            .map_err(|_| ServiceError::missing("Email"))?
             .as_string()
-            .ok_or("Email inválido")?;
+            .ok_or_else(|| ServiceError::Serialization("Email inválido".to_string()))?;
         
         Ok(User { uid, email })
     }
-
-    fn map_auth_error(err: &JsValue, prefix: &str) -> String {
-        // Try to extract Firebase-style `code` and `message` fields from the error JsValue
-        let code = js_sys::Reflect::get(err, &JsValue::from_str("code")).ok()
-            .and_then(|v| v.as_string());
-        let message = js_sys::Reflect::get(err, &JsValue::from_str("message")).ok()
-            .and_then(|v| v.as_string());
-
-        if let Some(code) = code {
-            // Map common Firebase auth codes to friendly Portuguese messages
-            let friendly = match code.as_str() {
-                "auth/wrong-password" => "Senha incorreta. Verifique e tente novamente.",
-                "auth/user-not-found" => "Usuário não encontrado. Verifique o email cadastrado.",
-                "auth/invalid-email" => "Formato de email inválido.",
-                "auth/email-already-in-use" => "Este email já está em uso.",
-                "auth/weak-password" => "Senha muito fraca. Use pelo menos 6 caracteres.",
-                "auth/invalid-credential" => "Credenciais incorretas, tente novamente!",
-                _ => message.as_deref().unwrap_or(&code),
-            };
-            format!("{}: {}", prefix, friendly)
-        } else if let Some(msg) = message {
-            format!("{}: {}", prefix, msg)
-        } else {
-            format!("{}: erro desconhecido", prefix)
-        }
-    }
 }
@@ -0,0 +1,286 @@
+// src/services/collab.rs
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::{Rc, Weak};
+use notepad_models::{Clock, CollabChange, CollabMessage, Note, Presence, Role, SystemClock, User};
+use crate::services::error::ServiceError;
+use crate::services::store::{self, CollabChannel, NoteStore};
+use crate::text::crdt::CollabDoc;
+
+/// Presenças sem atualização há mais tempo que isso são de abas que fecharam
+/// sem sair do canal.
+const PRESENCE_TTL_MS: i64 = 10 * 60 * 1000;
+
+const CURSOR_COLORS: [&str; 6] = ["#e53e3e", "#3182ce", "#38a169", "#d69e2e", "#805ad5", "#dd6b20"];
+
+/// Texto alterado por outro participante, com a seleção local já ajustada
+/// (posições UTF-16 do `<textarea>`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteText {
+    pub text: String,
+    pub selection: Option<(usize, usize)>,
+}
+
+/// Cursor de outro participante, para desenhar sobre o editor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteCursor {
+    pub session: String,
+    pub email: String,
+    pub color: &'static str,
+    pub index: usize,
+}
+
+struct SessionInner {
+    note_id: String,
+    /// Conteúdo e revisão da nota quando o editor foi aberto.
+    note_content: String,
+    note_revision: u64,
+    /// Último texto do editor; enviado quando o documento fica pronto.
+    local_text: String,
+    /// `None` até o canal entregar as changes existentes.
+    doc: Option<CollabDoc>,
+    me: Presence,
+    /// Leitores acompanham as edições, mas não enviam changes.
+    read_only: bool,
+    remote: BTreeMap<String, Presence>,
+    on_text: Rc<dyn Fn(RemoteText)>,
+    on_cursors: Rc<dyn Fn(Vec<RemoteCursor>)>,
+    channel: Option<Rc<CollabChannel>>,
+}
+
+/// Edição colaborativa de uma nota: mantém o conteúdo em um `CollabDoc`
+/// sincronizado pelo canal do backend e publica a posição do cursor.
+///
+/// Enquanto o canal não responde (ou se falhar), o editor continua
+/// funcionando sozinho e as edições são enviadas quando ele ficar pronto.
+/// Duas sessões abertas no mesmo `MemoryStore` editam a mesma nota como
+/// dois clientes.
+pub struct CollabSession {
+    inner: Rc<RefCell<SessionInner>>,
+}
+
+impl CollabSession {
+    /// Entra na edição colaborativa da nota no backend ativo; quem não pode
+    /// editar a nota (leitor) só recebe as alterações e publica o cursor.
+    pub fn join(
+        note: &Note,
+        user: &User,
+        on_text: impl Fn(RemoteText) + 'static,
+        on_cursors: impl Fn(Vec<RemoteCursor>) + 'static,
+    ) -> Result<Self, ServiceError> {
+        Self::join_with(&store::current(), note, user, on_text, on_cursors)
+    }
+
+    pub fn join_with(
+        store: &impl NoteStore,
+        note: &Note,
+        user: &User,
+        on_text: impl Fn(RemoteText) + 'static,
+        on_cursors: impl Fn(Vec<RemoteCursor>) + 'static,
+    ) -> Result<Self, ServiceError> {
+        let note_id = note.id.clone().ok_or(ServiceError::NotFound)?;
+        let read_only = !note.role_of(user).is_some_and(Role::can_edit);
+        let session_id = format!(
+            "{:x}{:x}",
+            js_sys::Date::now() as u64,
+            (js_sys::Math::random() * 1e9) as u64
+        );
+        let inner = Rc::new(RefCell::new(SessionInner {
+            note_id: note_id.clone(),
+            note_content: note.content.clone(),
+            note_revision: note.revision,
+            local_text: note.content.clone(),
+            doc: None,
+            me: Presence {
+                session: session_id,
+                user_id: user.uid.clone(),
+                email: user.email.clone(),
+                anchor: None,
+                head: None,
+                updated_at: SystemClock.now_millis(),
+            },
+            read_only,
+            remote: BTreeMap::new(),
+            on_text: Rc::new(on_text),
+            on_cursors: Rc::new(on_cursors),
+            channel: None,
+        }));
+
+        let weak = Rc::downgrade(&inner);
+        let channel = store.join_collab(&note_id, Rc::new(move |message| {
+            Self::on_message(&weak, message)
+        }))?;
+        inner.borrow_mut().channel = Some(Rc::new(channel));
+        Ok(Self { inner })
+    }
+
+    /// Registra o texto atual do editor e envia a diferença.
+    pub fn set_text(&self, text: &str) {
+        let changes = {
+            let mut inner = self.inner.borrow_mut();
+            inner.local_text = text.to_string();
+            match inner.doc.as_mut() {
+                Some(doc) => doc.set_text(text),
+                None => return,
+            }
+        };
+        self.send_changes(changes);
+    }
+
+    /// Publica a seleção local (posições UTF-16).
+    pub fn set_selection(&self, start: usize, end: usize) {
+        let presence = {
+            let mut inner = self.inner.borrow_mut();
+            let Some(doc) = inner.doc.as_ref() else { return };
+            let (anchor, head) = (doc.cursor(start), doc.cursor(end));
+            if inner.me.anchor == anchor && inner.me.head == head {
+                return;
+            }
+            inner.me.anchor = anchor;
+            inner.me.head = head;
+            inner.me.updated_at = SystemClock.now_millis();
+            inner.me.clone()
+        };
+        self.send(CollabMessage::Presence(presence));
+    }
+
+    /// Maior revisão da nota já gravada por algum participante.
+    pub fn saved_revision(&self) -> u64 {
+        self.inner.borrow().doc.as_ref().map(CollabDoc::revision).unwrap_or(0)
+    }
+
+    /// Avisa os demais que a nota foi gravada com `revision`, para que o
+    /// próximo salvamento deles não seja tomado por um conflito.
+    pub fn mark_saved(&self, revision: u64) {
+        let changes = match self.inner.borrow_mut().doc.as_mut() {
+            Some(doc) => doc.set_revision(revision),
+            None => return,
+        };
+        self.send_changes(changes);
+    }
+
+    fn send_changes(&self, changes: Vec<CollabChange>) {
+        if !changes.is_empty() && !self.inner.borrow().read_only {
+            self.send(CollabMessage::Changes { changes });
+        }
+    }
+
+    fn send(&self, message: CollabMessage) {
+        // o canal pode chamar outros participantes de forma síncrona
+        let channel = self.inner.borrow().channel.clone();
+        if let Some(channel) = channel {
+            channel.send(message);
+        }
+    }
+
+    fn on_message(weak: &Weak<RefCell<SessionInner>>, message: Result<CollabMessage, ServiceError>) {
+        let Some(inner) = weak.upgrade() else { return };
+        let session = CollabSession { inner };
+
+        match message {
+            Ok(CollabMessage::Changes { changes }) => session.receive_changes(&changes),
+            Ok(CollabMessage::Presence(presence)) => {
+                let mut inner = session.inner.borrow_mut();
+                if presence.session == inner.me.session {
+                    return;
+                }
+                inner.remote.insert(presence.session.clone(), presence);
+                drop(inner);
+                session.emit_cursors();
+            }
+            Ok(CollabMessage::Leave { session: id }) => {
+                session.inner.borrow_mut().remote.remove(&id);
+                session.emit_cursors();
+            }
+            Err(e) => {
+                web_sys::console::error_1(&format!("Edição colaborativa indisponível: {}", e).into());
+            }
+        }
+    }
+
+    fn receive_changes(&self, changes: &[CollabChange]) {
+        let mut outgoing = Vec::new();
+        let remote_text = {
+            let mut inner = self.inner.borrow_mut();
+            let inner = &mut *inner;
+
+            let doc = match inner.doc.as_mut() {
+                Some(doc) => doc,
+                // primeira entrega: as changes já gravadas
+                None if changes.is_empty() => {
+                    match CollabDoc::seed(&inner.note_id, &inner.note_content, inner.note_revision) {
+                        Ok((doc, seed)) => {
+                            outgoing.extend(seed);
+                            inner.doc.insert(doc)
+                        }
+                        Err(e) => {
+                            web_sys::console::error_1(&format!("Erro ao criar o documento colaborativo: {}", e).into());
+                            return;
+                        }
+                    }
+                }
+                None => inner.doc.insert(CollabDoc::new()),
+            };
+
+            if let Err(e) = doc.apply(changes) {
+                web_sys::console::error_1(&format!("Erro ao aplicar alterações colaborativas: {}", e).into());
+            }
+            if !doc.is_ready() {
+                return;
+            }
+            // a nota foi gravada fora da edição colaborativa depois da última change
+            if doc.revision() < inner.note_revision {
+                outgoing.extend(doc.set_text(&inner.note_content));
+                outgoing.extend(doc.set_revision(inner.note_revision));
+            }
+            // digitado antes de o canal responder
+            if inner.local_text != inner.note_content {
+                outgoing.extend(doc.set_text(&inner.local_text));
+            }
+
+            let text = doc.text();
+            if text == inner.local_text {
+                None
+            } else {
+                inner.local_text = text.clone();
+                // a seleção local acompanha o texto inserido ou removido antes dela
+                let selection = (|| {
+                    let anchor = doc.cursor_index(inner.me.anchor.as_deref()?)?;
+                    let head = doc.cursor_index(inner.me.head.as_deref()?)?;
+                    Some((anchor, head))
+                })();
+                Some((inner.on_text.clone(), RemoteText { text, selection }))
+            }
+        };
+
+        self.send_changes(outgoing);
+        if let Some((on_text, remote)) = remote_text {
+            on_text(remote);
+        }
+        self.emit_cursors();
+    }
+
+    fn emit_cursors(&self) {
+        let (on_cursors, cursors) = {
+            let inner = self.inner.borrow();
+            let Some(doc) = inner.doc.as_ref() else { return };
+            let now = SystemClock.now_millis();
+            let cursors = inner.remote.values()
+                .filter(|p| now - p.updated_at < PRESENCE_TTL_MS)
+                .filter_map(|p| Some(RemoteCursor {
+                    session: p.session.clone(),
+                    email: p.email.clone(),
+                    color: cursor_color(&p.session),
+                    index: doc.cursor_index(p.head.as_deref()?)?,
+                }))
+                .collect();
+            (inner.on_cursors.clone(), cursors)
+        };
+        on_cursors(cursors);
+    }
+}
+
+fn cursor_color(session: &str) -> &'static str {
+    let hash = session.bytes().fold(0usize, |h, b| h.wrapping_mul(31).wrapping_add(b as usize));
+    CURSOR_COLORS[hash % CURSOR_COLORS.len()]
+}
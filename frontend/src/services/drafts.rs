@@ -0,0 +1,68 @@
+// src/services/drafts.rs
+use std::collections::BTreeMap;
+use gloo_storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+
+const DRAFTS_KEY: &str = "notepad.drafts";
+/// Prefixo das chaves de rascunhos de notas ainda não gravadas.
+const NEW_PREFIX: &str = "new-";
+
+/// Texto do editor ainda não gravado, guardado a cada alteração.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalDraft {
+    pub user_id: String,
+    pub title: String,
+    pub content: String,
+    pub saved_at: i64,
+}
+
+/// Diário de rascunhos no localStorage, por id da nota (ou por uma chave
+/// temporária para notas novas), para recuperar o texto se a aba fechar antes
+/// do salvamento. Síncrono para poder gravar a cada tecla.
+pub struct DraftsService;
+
+impl DraftsService {
+    fn load() -> BTreeMap<String, LocalDraft> {
+        LocalStorage::get(DRAFTS_KEY).unwrap_or_default()
+    }
+
+    fn store(drafts: &BTreeMap<String, LocalDraft>) {
+        if let Err(e) = LocalStorage::set(DRAFTS_KEY, drafts) {
+            web_sys::console::error_1(&format!("Erro ao gravar rascunho local: {}", e).into());
+        }
+    }
+
+    /// Chave para o rascunho de uma nota nova.
+    pub fn new_key() -> String {
+        let now = js_sys::Date::now() as u64;
+        let rand = (js_sys::Math::random() * 1e9) as u64;
+        format!("{}{:x}-{:x}", NEW_PREFIX, now, rand)
+    }
+
+    pub fn get(key: &str, user_id: &str) -> Option<LocalDraft> {
+        Self::load().remove(key).filter(|d| d.user_id == user_id)
+    }
+
+    /// Rascunho de nota nova mais recente do usuário, com a sua chave.
+    pub fn latest_new(user_id: &str) -> Option<(String, LocalDraft)> {
+        Self::load().into_iter()
+            .filter(|(key, d)| key.starts_with(NEW_PREFIX) && d.user_id == user_id)
+            .max_by_key(|(_, d)| d.saved_at)
+    }
+
+    pub fn save(key: &str, draft: LocalDraft) {
+        let mut drafts = Self::load();
+        if drafts.get(key) != Some(&draft) {
+            drafts.insert(key.to_string(), draft);
+            Self::store(&drafts);
+        }
+    }
+
+    pub fn discard(key: &str) {
+        let mut drafts = Self::load();
+        if drafts.remove(key).is_some() {
+            Self::store(&drafts);
+        }
+    }
+}
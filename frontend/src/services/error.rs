@@ -0,0 +1,106 @@
+// src/services/error.rs
+use std::fmt;
+use wasm_bindgen::prelude::*;
+use js_sys::Reflect;
+
+/// Erro retornado por `AuthService`, `NotesService` e pelos backends de notas.
+///
+/// Os componentes podem fazer `match` na variante; o texto para o usuário
+/// vem do `Display` (em português).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServiceError {
+    /// Sem conexão ou servidor indisponível.
+    Network(String),
+    /// Erro do Firebase Authentication (`auth/...`).
+    Auth(AuthCode),
+    /// Regras de segurança ou servidor recusaram a operação.
+    PermissionDenied,
+    NotFound,
+    /// O documento mudou desde a última leitura.
+    Conflict,
+    /// Documento ou resposta em formato inesperado.
+    Serialization(String),
+    /// Qualquer outra falha do backend (método ausente, armazenamento cheio…).
+    Backend(String),
+}
+
+/// Códigos de erro do Firebase Authentication que a interface trata.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuthCode {
+    WrongPassword,
+    UserNotFound,
+    InvalidEmail,
+    EmailAlreadyInUse,
+    WeakPassword,
+    InvalidCredential,
+    Other { code: String, message: Option<String> },
+}
+
+impl AuthCode {
+    pub fn from_code(code: &str, message: Option<String>) -> Self {
+        match code {
+            "auth/wrong-password" => AuthCode::WrongPassword,
+            "auth/user-not-found" => AuthCode::UserNotFound,
+            "auth/invalid-email" => AuthCode::InvalidEmail,
+            "auth/email-already-in-use" => AuthCode::EmailAlreadyInUse,
+            "auth/weak-password" => AuthCode::WeakPassword,
+            "auth/invalid-credential" => AuthCode::InvalidCredential,
+            _ => AuthCode::Other { code: code.to_string(), message },
+        }
+    }
+}
+
+impl ServiceError {
+    /// Classifica um erro lançado pelo SDK do Firebase a partir do campo `code`.
+    pub fn from_js(err: &JsValue) -> Self {
+        let code = Reflect::get(err, &JsValue::from_str("code")).ok()
+            .and_then(|v| v.as_string());
+        let message = Reflect::get(err, &JsValue::from_str("message")).ok()
+            .and_then(|v| v.as_string());
+        let detail = || message.clone().unwrap_or_else(|| format!("{:?}", err));
+
+        match code.as_deref() {
+            Some("auth/network-request-failed") | Some("unavailable") | Some("deadline-exceeded") => {
+                ServiceError::Network(detail())
+            }
+            Some(c) if c.starts_with("auth/") => ServiceError::Auth(AuthCode::from_code(c, message)),
+            Some("permission-denied") | Some("unauthenticated") => ServiceError::PermissionDenied,
+            Some("not-found") => ServiceError::NotFound,
+            Some("already-exists") | Some("aborted") | Some("failed-precondition") => ServiceError::Conflict,
+            _ => ServiceError::Backend(detail()),
+        }
+    }
+
+    /// Erro de um método ausente no objeto JS (SDK não carregado, etc.).
+    pub fn missing(what: &str) -> Self {
+        ServiceError::Backend(format!("{} não encontrado", what))
+    }
+}
+
+impl fmt::Display for AuthCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthCode::WrongPassword => write!(f, "Senha incorreta. Verifique e tente novamente."),
+            AuthCode::UserNotFound => write!(f, "Usuário não encontrado. Verifique o email cadastrado."),
+            AuthCode::InvalidEmail => write!(f, "Formato de email inválido."),
+            AuthCode::EmailAlreadyInUse => write!(f, "Este email já está em uso."),
+            AuthCode::WeakPassword => write!(f, "Senha muito fraca. Use pelo menos 6 caracteres."),
+            AuthCode::InvalidCredential => write!(f, "Credenciais incorretas, tente novamente!"),
+            AuthCode::Other { code, message } => write!(f, "{}", message.as_deref().unwrap_or(code)),
+        }
+    }
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServiceError::Network(_) => write!(f, "Sem conexão com o servidor. Verifique sua internet e tente novamente."),
+            ServiceError::Auth(code) => write!(f, "{}", code),
+            ServiceError::PermissionDenied => write!(f, "Você não tem permissão para esta operação."),
+            ServiceError::NotFound => write!(f, "Nota não encontrada."),
+            ServiceError::Conflict => write!(f, "A nota foi alterada em outro dispositivo."),
+            ServiceError::Serialization(detail) => write!(f, "Dados em formato inválido: {}", detail),
+            ServiceError::Backend(detail) => write!(f, "Erro inesperado: {}", detail),
+        }
+    }
+}
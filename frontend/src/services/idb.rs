@@ -0,0 +1,129 @@
+// src/services/idb.rs
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbOpenDbRequest, IdbRequest, IdbTransactionMode};
+use crate::services::error::ServiceError;
+
+const DB_NAME: &str = "notepad";
+const DB_VERSION: u32 = 1;
+
+/// Cópia local das notas, por id.
+pub const NOTES: &str = "notes";
+/// Operações pendentes de envio, por id da nota.
+pub const OUTBOX: &str = "outbox";
+
+thread_local! {
+    static DB: RefCell<Option<IdbDatabase>> = const { RefCell::new(None) };
+}
+
+/// Acesso mínimo ao IndexedDB do navegador (abre o banco uma vez por sessão).
+pub struct Idb;
+
+impl Idb {
+    async fn db() -> Result<IdbDatabase, ServiceError> {
+        if let Some(db) = DB.with(|db| db.borrow().clone()) {
+            return Ok(db);
+        }
+
+        let factory = web_sys::window()
+            .and_then(|w| w.indexed_db().ok().flatten())
+            .ok_or_else(|| ServiceError::missing("IndexedDB"))?;
+        let open_req: IdbOpenDbRequest = factory.open_with_u32(DB_NAME, DB_VERSION)
+            .map_err(|e| ServiceError::from_js(&e))?;
+
+        let on_upgrade = Closure::<dyn FnMut(web_sys::Event)>::new({
+            let open_req = open_req.clone();
+            move |_| {
+                let Ok(result) = open_req.result() else { return };
+                let db: IdbDatabase = result.unchecked_into();
+                for name in [NOTES, OUTBOX] {
+                    if !db.object_store_names().contains(name) {
+                        let _ = db.create_object_store(name);
+                    }
+                }
+            }
+        });
+        open_req.set_onupgradeneeded(Some(on_upgrade.as_ref().unchecked_ref()));
+
+        let db: IdbDatabase = request(&open_req).await?.unchecked_into();
+        DB.with(|cell| *cell.borrow_mut() = Some(db.clone()));
+        Ok(db)
+    }
+
+    fn store(db: &IdbDatabase, name: &str, mode: IdbTransactionMode) -> Result<web_sys::IdbObjectStore, ServiceError> {
+        db.transaction_with_str_and_mode(name, mode)
+            .and_then(|tx| tx.object_store(name))
+            .map_err(|e| ServiceError::from_js(&e))
+    }
+
+    /// Todos os pares (chave, valor) de um object store.
+    pub async fn entries(name: &str) -> Result<Vec<(String, JsValue)>, ServiceError> {
+        let db = Self::db().await?;
+        let store = Self::store(&db, name, IdbTransactionMode::Readonly)?;
+        let keys = store.get_all_keys().map_err(|e| ServiceError::from_js(&e))?;
+        let values = store.get_all().map_err(|e| ServiceError::from_js(&e))?;
+
+        let keys: js_sys::Array = request(&keys).await?.unchecked_into();
+        let values: js_sys::Array = request(&values).await?.unchecked_into();
+        Ok(keys.iter()
+            .zip(values.iter())
+            .filter_map(|(k, v)| k.as_string().map(|k| (k, v)))
+            .collect())
+    }
+
+    /// Valor guardado na chave, se houver.
+    pub async fn get(name: &str, key: &str) -> Result<Option<JsValue>, ServiceError> {
+        let db = Self::db().await?;
+        let store = Self::store(&db, name, IdbTransactionMode::Readonly)?;
+        let req = store.get(&JsValue::from_str(key))
+            .map_err(|e| ServiceError::from_js(&e))?;
+        let value = request(&req).await?;
+        Ok((!value.is_undefined()).then_some(value))
+    }
+
+    pub async fn put(name: &str, key: &str, value: &JsValue) -> Result<(), ServiceError> {
+        let db = Self::db().await?;
+        let store = Self::store(&db, name, IdbTransactionMode::Readwrite)?;
+        let req = store.put_with_key(value, &JsValue::from_str(key))
+            .map_err(|e| ServiceError::from_js(&e))?;
+        request(&req).await.map(|_| ())
+    }
+
+    pub async fn delete(name: &str, key: &str) -> Result<(), ServiceError> {
+        let db = Self::db().await?;
+        let store = Self::store(&db, name, IdbTransactionMode::Readwrite)?;
+        let req = store.delete(&JsValue::from_str(key))
+            .map_err(|e| ServiceError::from_js(&e))?;
+        request(&req).await.map(|_| ())
+    }
+}
+
+/// Espera o `onsuccess`/`onerror` de uma requisição do IndexedDB.
+///
+/// Só um dos dois dispara; os dois closures são descartados juntos quando a
+/// requisição termina.
+async fn request(req: &IdbRequest) -> Result<JsValue, ServiceError> {
+    let mut handlers = None;
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let ok_req = req.clone();
+        let on_success: Closure<dyn FnMut(web_sys::Event)> = Closure::once(move |_: web_sys::Event| {
+            let _ = resolve.call1(&JsValue::NULL, &ok_req.result().unwrap_or(JsValue::UNDEFINED));
+        });
+        let err_req = req.clone();
+        let on_error: Closure<dyn FnMut(web_sys::Event)> = Closure::once(move |_: web_sys::Event| {
+            let err = err_req.error().ok().flatten().map(JsValue::from).unwrap_or(JsValue::UNDEFINED);
+            let _ = reject.call1(&JsValue::NULL, &err);
+        });
+        req.set_onsuccess(Some(on_success.as_ref().unchecked_ref()));
+        req.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+        handlers = Some((on_success, on_error));
+    });
+    let result = JsFuture::from(promise).await
+        .map_err(|e| ServiceError::Backend(format!("IndexedDB: {:?}", e)));
+    req.set_onsuccess(None);
+    req.set_onerror(None);
+    drop(handlers);
+    result
+}
@@ -1,3 +1,10 @@
 // src/services/mod.rs
 pub mod auth;
+pub mod collab;
+pub mod drafts;
+pub mod error;
+pub mod idb;
+pub mod notebooks;
 pub mod notes;
+pub mod store;
+pub mod sync;
@@ -0,0 +1,41 @@
+// src/services/notebooks.rs
+use std::rc::Rc;
+use notepad_models::{Clock, Notebook, SystemClock};
+use crate::services::error::ServiceError;
+use crate::services::store::{self, NotebookStore, Subscription};
+
+/// Cadernos do usuário no backend ativo. Diferente das notas, não há
+/// suporte offline: as operações falham sem conexão.
+pub struct NotebooksService;
+
+impl NotebooksService {
+    /// Lista de cadernos do usuário, entregue inteira a cada alteração.
+    pub fn subscribe(
+        user_id: &str,
+        on_change: impl Fn(Result<Vec<Notebook>, ServiceError>) + 'static,
+    ) -> Subscription {
+        let on_change = Rc::new(on_change);
+        let subscription = store::current().subscribe_notebooks(user_id, on_change.clone())
+            .map_err(|e| on_change(Err(e)))
+            .ok();
+        Subscription::new(move || drop(subscription))
+    }
+
+    pub async fn create(name: &str, user_id: &str, parent_id: Option<String>) -> Result<String, ServiceError> {
+        let notebook = Notebook::new(name.trim().to_string(), user_id.to_string(), parent_id, SystemClock.now_millis());
+        store::current().create_notebook(&notebook).await
+    }
+
+    pub async fn rename(notebook: &Notebook, name: &str) -> Result<(), ServiceError> {
+        let mut renamed = notebook.clone();
+        renamed.name = name.trim().to_string();
+        store::current().update_notebook(&renamed).await
+    }
+
+    /// Exclui o caderno sem perder o conteúdo: as notas e os cadernos dentro
+    /// dele sobem para o caderno pai (ver `NotebookStore::delete_notebook`).
+    pub async fn delete(notebook: &Notebook) -> Result<(), ServiceError> {
+        let id = notebook.id.as_deref().ok_or(ServiceError::NotFound)?;
+        store::current().delete_notebook(id).await
+    }
+}
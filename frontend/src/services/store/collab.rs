@@ -0,0 +1,146 @@
+// src/services/store/collab.rs
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+use notepad_models::{CollabChange, CollabMessage, Presence};
+use crate::services::error::ServiceError;
+use super::Subscription;
+
+pub type CollabHandler = Rc<dyn Fn(Result<CollabMessage, ServiceError>)>;
+
+/// Participação no canal colaborativo de uma nota; sai do canal ao ser
+/// descartada (`drop`).
+pub struct CollabChannel {
+    send: Box<dyn Fn(CollabMessage)>,
+    _subscription: Subscription,
+}
+
+impl CollabChannel {
+    pub fn new(send: impl Fn(CollabMessage) + 'static, subscription: Subscription) -> Self {
+        Self { send: Box::new(send), _subscription: subscription }
+    }
+
+    /// Envia a mensagem aos demais participantes; falhas de envio são
+    /// registradas no console, pois as changes são reenviadas na próxima edição.
+    pub fn send(&self, message: CollabMessage) {
+        (self.send)(message)
+    }
+}
+
+#[derive(Default)]
+struct Room {
+    changes: BTreeMap<String, CollabChange>,
+    presences: BTreeMap<String, Presence>,
+    members: Vec<(u64, CollabHandler)>,
+    /// Sessões anunciadas por cada participante, removidas quando ele sai.
+    sessions: HashMap<u64, Vec<String>>,
+}
+
+/// Canal colaborativo em memória para backends sem servidor (memória,
+/// localStorage): os participantes são as sessões abertas nesta aba.
+#[derive(Clone, Default)]
+pub struct CollabHub {
+    rooms: Rc<RefCell<HashMap<String, Room>>>,
+    next_id: Rc<Cell<u64>>,
+}
+
+impl CollabHub {
+    pub fn join(&self, note_id: &str, on_message: CollabHandler) -> CollabChannel {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+
+        let (changes, presences) = {
+            let mut rooms = self.rooms.borrow_mut();
+            let room = rooms.entry(note_id.to_string()).or_default();
+            room.members.push((id, on_message.clone()));
+            (
+                room.changes.values().cloned().collect::<Vec<_>>(),
+                room.presences.values().cloned().collect::<Vec<_>>(),
+            )
+        };
+        // o estado atual chega depois de `join` retornar, como nos backends remotos
+        spawn_local(async move {
+            on_message(Ok(CollabMessage::Changes { changes }));
+            for presence in presences {
+                on_message(Ok(CollabMessage::Presence(presence)));
+            }
+        });
+
+        let send = {
+            let hub = self.clone();
+            let note_id = note_id.to_string();
+            move |message| hub.publish(&note_id, id, message)
+        };
+        let leave = {
+            let hub = self.clone();
+            let note_id = note_id.to_string();
+            move || hub.leave(&note_id, id)
+        };
+        CollabChannel::new(send, Subscription::new(leave))
+    }
+
+    fn publish(&self, note_id: &str, from: u64, message: CollabMessage) {
+        let message = {
+            let mut rooms = self.rooms.borrow_mut();
+            let Some(room) = rooms.get_mut(note_id) else { return };
+            match message {
+                CollabMessage::Changes { changes } => {
+                    let fresh: Vec<CollabChange> = changes.into_iter()
+                        .filter(|c| !room.changes.contains_key(&c.hash))
+                        .collect();
+                    if fresh.is_empty() {
+                        return;
+                    }
+                    for change in &fresh {
+                        room.changes.insert(change.hash.clone(), change.clone());
+                    }
+                    CollabMessage::Changes { changes: fresh }
+                }
+                CollabMessage::Presence(presence) => {
+                    let sessions = room.sessions.entry(from).or_default();
+                    if !sessions.contains(&presence.session) {
+                        sessions.push(presence.session.clone());
+                    }
+                    room.presences.insert(presence.session.clone(), presence.clone());
+                    CollabMessage::Presence(presence)
+                }
+                CollabMessage::Leave { session } => {
+                    room.presences.remove(&session);
+                    CollabMessage::Leave { session }
+                }
+            }
+        };
+        self.broadcast(note_id, from, &message);
+    }
+
+    fn leave(&self, note_id: &str, member: u64) {
+        let sessions = {
+            let mut rooms = self.rooms.borrow_mut();
+            let Some(room) = rooms.get_mut(note_id) else { return };
+            room.members.retain(|(id, _)| *id != member);
+            let sessions = room.sessions.remove(&member).unwrap_or_default();
+            for session in &sessions {
+                room.presences.remove(session);
+            }
+            sessions
+        };
+        for session in sessions {
+            self.broadcast(note_id, member, &CollabMessage::Leave { session });
+        }
+    }
+
+    fn broadcast(&self, note_id: &str, from: u64, message: &CollabMessage) {
+        // copia os handlers: um participante pode sair durante a chamada
+        let handlers: Vec<CollabHandler> = self.rooms.borrow()
+            .get(note_id)
+            .map(|room| room.members.iter()
+                .filter(|(id, _)| *id != from)
+                .map(|(_, h)| h.clone())
+                .collect())
+            .unwrap_or_default();
+        for handler in handlers {
+            handler(Ok(message.clone()));
+        }
+    }
+}
@@ -0,0 +1,601 @@
+// src/services/store/firestore.rs
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{future_to_promise, spawn_local, JsFuture};
+use js_sys::{Array, Object, Reflect, Function};
+use serde::Serialize;
+use serde_wasm_bindgen::Serializer;
+use notepad_models::{CollabChange, CollabMessage, Note, Notebook, Presence, NOTE_SCHEMA_VERSION};
+use crate::services::error::ServiceError;
+use super::{ChangeHandler, CollabChannel, CollabHandler, NoteChange, NoteQuery, NoteStore, NotebookStore, NotebooksHandler, Subscription};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = window, js_name = db)]
+    #[wasm_bindgen(thread_local_v2)]
+    pub static DB: JsValue;
+}
+
+/// Notas na coleção `notes` do Firestore (`window.db`).
+///
+/// A edição colaborativa usa as subcoleções `notes/{id}/changes` (uma change
+/// do documento por hash) e `notes/{id}/presence` (um documento por sessão).
+/// Os cadernos ficam na coleção `notebooks`.
+#[derive(Clone, Copy, Default)]
+pub struct FirestoreStore;
+
+impl NoteStore for FirestoreStore {
+    async fn create(&self, note: &Note) -> Result<String, ServiceError> {
+        let notes_ref = Self::get_collection("notes")?;
+        let note_obj = Self::note_to_js(note)?;
+        Self::add_doc(&notes_ref, &note_obj).await
+    }
+    
+    async fn get(&self, note_id: &str) -> Result<Option<Note>, ServiceError> {
+        let notes_ref = Self::get_collection("notes")?;
+        let doc_ref = Self::get_doc(&notes_ref, note_id)?;
+
+        let get_fn = Reflect::get(&doc_ref, &JsValue::from_str("get"))
+            .map_err(|_| ServiceError::missing("Método get"))?;
+        let get_fn: Function = get_fn.into();
+
+        let promise = get_fn.call0(&doc_ref)
+            .map_err(|e| ServiceError::from_js(&e))?;
+
+        let snapshot = JsFuture::from(js_sys::Promise::from(promise)).await
+            .map_err(|e| ServiceError::from_js(&e))?;
+
+        let exists = Reflect::get(&snapshot, &JsValue::from_str("exists"))
+            .ok()
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        if !exists {
+            return Ok(None);
+        }
+
+        Self::js_to_note(&snapshot).map(Some)
+    }
+
+    async fn list(&self, query: &NoteQuery) -> Result<Vec<Note>, ServiceError> {
+        let query = Self::query(query)?;
+        
+        let get_fn = Reflect::get(&query, &JsValue::from_str("get"))
+            .map_err(|_| ServiceError::missing("Método get"))?;
+        let get_fn: Function = get_fn.into();
+        
+        let promise = get_fn.call0(&query)
+            .map_err(|e| ServiceError::from_js(&e))?;
+        
+        let snapshot = JsFuture::from(js_sys::Promise::from(promise)).await
+            .map_err(|e| ServiceError::from_js(&e))?;
+        
+        let docs = Reflect::get(&snapshot, &JsValue::from_str("docs"))
+            .map_err(|_| ServiceError::missing("Docs"))?;
+        
+        let docs_array: Array = docs.into();
+        let mut notes = Vec::new();
+        
+        for doc in docs_array.iter() {
+            // um documento corrompido não deve esconder as demais notas
+            match Self::js_to_note(&doc) {
+                Ok(note) => notes.push(note),
+                Err(e) => web_sys::console::error_1(&format!("Nota ignorada: {:?}", e).into()),
+            }
+        }
+        
+        Ok(notes)
+    }
+    
+    async fn update(&self, note: &Note) -> Result<Note, ServiceError> {
+        let note_id = note.id.as_ref().ok_or(ServiceError::NotFound)?;
+        let notes_ref = Self::get_collection("notes")?;
+        let doc_ref = Self::get_doc(&notes_ref, note_id)?;
+
+        let expected = note.revision;
+        let mut stored = note.clone();
+        stored.revision = expected + 1;
+        let note_obj = Self::note_to_js(&stored)?;
+
+        // lê e grava na mesma transação: falha se outro dispositivo salvou antes
+        let update_fn = Closure::<dyn FnMut(JsValue) -> js_sys::Promise>::new(move |tx: JsValue| {
+            let doc_ref = doc_ref.clone();
+            let note_obj = note_obj.clone();
+            future_to_promise(async move {
+                let get_fn: Function = Reflect::get(&tx, &JsValue::from_str("get"))?.into();
+                let snapshot = JsFuture::from(js_sys::Promise::from(get_fn.call1(&tx, &doc_ref)?)).await?;
+
+                let exists = Reflect::get(&snapshot, &JsValue::from_str("exists"))?
+                    .as_bool()
+                    .unwrap_or(false);
+                if !exists {
+                    return Err(Self::js_error("not-found"));
+                }
+
+                let data_fn: Function = Reflect::get(&snapshot, &JsValue::from_str("data"))?.into();
+                let data = data_fn.call0(&snapshot)?;
+                let current = Reflect::get(&data, &JsValue::from_str("revision"))?
+                    .as_f64()
+                    .unwrap_or(0.0) as u64;
+                if current != expected {
+                    return Err(Self::js_error("failed-precondition"));
+                }
+
+                let set_fn: Function = Reflect::get(&tx, &JsValue::from_str("set"))?.into();
+                set_fn.call2(&tx, &doc_ref, &note_obj)?;
+                Ok(JsValue::UNDEFINED)
+            })
+        });
+
+        let promise = DB.with(|db| {
+            Reflect::get(db, &JsValue::from_str("runTransaction")).and_then(|f| {
+                let func: Function = f.into();
+                func.call1(db, update_fn.as_ref())
+            })
+        }).map_err(|e| ServiceError::from_js(&e))?;
+
+        JsFuture::from(js_sys::Promise::from(promise)).await
+            .map_err(|e| ServiceError::from_js(&e))?;
+
+        Ok(stored)
+    }
+    
+    async fn delete(&self, note_id: &str) -> Result<(), ServiceError> {
+        let notes_ref = Self::get_collection("notes")?;
+        let doc_ref = Self::get_doc(&notes_ref, note_id)?;
+        
+        let delete_fn = Reflect::get(&doc_ref, &JsValue::from_str("delete"))
+            .map_err(|_| ServiceError::missing("Método delete"))?;
+        let delete_fn: Function = delete_fn.into();
+        
+        let promise = delete_fn.call0(&doc_ref)
+            .map_err(|e| ServiceError::from_js(&e))?;
+        
+        JsFuture::from(js_sys::Promise::from(promise)).await
+            .map_err(|e| ServiceError::from_js(&e))?;
+        
+        Ok(())
+    }
+
+    fn subscribe(&self, query: &NoteQuery, on_change: ChangeHandler) -> Result<Subscription, ServiceError> {
+        Self::on_snapshot(&Self::query(query)?, move |changes| on_change(changes), Self::snapshot_changes)
+    }
+
+    fn join_collab(&self, note_id: &str, on_message: CollabHandler) -> Result<CollabChannel, ServiceError> {
+        let changes_ref = Self::sub_collection(note_id, "changes")?;
+        let presence_ref = Self::sub_collection(note_id, "presence")?;
+
+        // a primeira entrega é sempre uma `Changes`, mesmo vazia
+        let first = Rc::new(Cell::new(true));
+        let changes_sub = Self::on_snapshot(&changes_ref, Self::each_message(on_message.clone()), move |snapshot| {
+            let mut changes = Vec::new();
+            for (kind, doc) in Self::doc_changes(snapshot)? {
+                if kind == "added" {
+                    changes.push(Self::js_to_data::<CollabChange>(&doc)?);
+                }
+            }
+            if changes.is_empty() && !first.replace(false) {
+                return Ok(Vec::new());
+            }
+            first.set(false);
+            Ok(vec![CollabMessage::Changes { changes }])
+        })?;
+        let presence_sub = Self::on_snapshot(&presence_ref, Self::each_message(on_message), |snapshot| {
+            Self::doc_changes(snapshot)?
+                .into_iter()
+                .map(|(kind, doc)| match kind.as_str() {
+                    "removed" => Ok(CollabMessage::Leave {
+                        session: Reflect::get(&doc, &JsValue::from_str("id"))
+                            .ok()
+                            .and_then(|v| v.as_string())
+                            .unwrap_or_default(),
+                    }),
+                    _ => Self::js_to_data::<Presence>(&doc).map(CollabMessage::Presence),
+                })
+                .collect()
+        })?;
+
+        // sessões anunciadas por este canal, removidas ao sair
+        let sessions: Rc<RefCell<Vec<String>>> = Rc::default();
+        let send = {
+            let changes_ref = changes_ref.clone();
+            let presence_ref = presence_ref.clone();
+            let sessions = sessions.clone();
+            move |message: CollabMessage| {
+                let written = match message {
+                    CollabMessage::Changes { changes } => changes.iter()
+                        .try_for_each(|c| Self::set_data(&changes_ref, &c.hash, c)),
+                    CollabMessage::Presence(presence) => {
+                        if !sessions.borrow().contains(&presence.session) {
+                            sessions.borrow_mut().push(presence.session.clone());
+                        }
+                        Self::set_data(&presence_ref, &presence.session, &presence)
+                    }
+                    CollabMessage::Leave { session } => Self::delete_doc(&presence_ref, &session),
+                };
+                if let Err(e) = written {
+                    web_sys::console::error_1(&format!("Erro ao enviar alteração colaborativa: {}", e).into());
+                }
+            }
+        };
+        let leave = Subscription::new(move || {
+            drop(changes_sub);
+            drop(presence_sub);
+            for session in sessions.borrow().iter() {
+                let _ = Self::delete_doc(&presence_ref, session);
+            }
+        });
+        Ok(CollabChannel::new(send, leave))
+    }
+}
+
+impl FirestoreStore {
+    /// Erro no formato do SDK (`{ code }`) para abortar uma transação.
+    fn js_error(code: &str) -> JsValue {
+        let err = Object::new();
+        let _ = Reflect::set(&err, &JsValue::from_str("code"), &JsValue::from_str(code));
+        err.into()
+    }
+
+    /// `collection.add(data)`; retorna o id do documento criado.
+    async fn add_doc(collection: &JsValue, data: &JsValue) -> Result<String, ServiceError> {
+        let add_fn = Reflect::get(collection, &JsValue::from_str("add"))
+            .map_err(|_| ServiceError::missing("Método add"))?;
+        let add_fn: Function = add_fn.into();
+        
+        let promise = add_fn.call1(collection, data)
+            .map_err(|e| ServiceError::from_js(&e))?;
+        
+        let result = JsFuture::from(js_sys::Promise::from(promise)).await
+            .map_err(|e| ServiceError::from_js(&e))?;
+        
+        Reflect::get(&result, &JsValue::from_str("id"))
+            .map_err(|_| ServiceError::missing("ID"))?
+            .as_string()
+            .ok_or_else(|| ServiceError::Serialization("ID inválido".to_string()))
+    }
+
+    /// Chama `method` no documento e espera a confirmação do servidor.
+    async fn write_doc(collection: &JsValue, id: &str, method: &str, arg: &JsValue) -> Result<(), ServiceError> {
+        let doc_ref = Self::get_doc(collection, id)?;
+        let method_fn = Reflect::get(&doc_ref, &JsValue::from_str(method))
+            .map_err(|_| ServiceError::missing(method))?;
+        let method_fn: Function = method_fn.into();
+        let promise = method_fn.call1(&doc_ref, arg)
+            .map_err(|e| ServiceError::from_js(&e))?;
+
+        JsFuture::from(js_sys::Promise::from(promise)).await
+            .map_err(|e| ServiceError::from_js(&e))?;
+        Ok(())
+    }
+
+    /// `get()` de um documento ou consulta.
+    async fn fetch(target: &JsValue) -> Result<JsValue, ServiceError> {
+        let get_fn: Function = Reflect::get(target, &JsValue::from_str("get"))
+            .map_err(|_| ServiceError::missing("Método get"))?
+            .into();
+        let promise = get_fn.call0(target)
+            .map_err(|e| ServiceError::from_js(&e))?;
+        JsFuture::from(js_sys::Promise::from(promise)).await
+            .map_err(|e| ServiceError::from_js(&e))
+    }
+
+    /// Acrescenta uma operação (`update`, `delete`...) ao lote.
+    fn batch_call(batch: &JsValue, method: &str, args: &[JsValue]) -> Result<(), ServiceError> {
+        let method_fn: Function = Reflect::get(batch, &JsValue::from_str(method))
+            .map_err(|_| ServiceError::missing(method))?
+            .into();
+        method_fn.apply(batch, &args.iter().collect::<Array>())
+            .map_err(|e| ServiceError::from_js(&e))?;
+        Ok(())
+    }
+
+    fn notebooks_query(user_id: &str) -> Result<JsValue, ServiceError> {
+        let notebooks_ref = Self::get_collection("notebooks")?;
+        Self::where_clause(&notebooks_ref, "userId", "==", &JsValue::from_str(user_id))
+    }
+
+    /// Cadernos em `snapshot.docs`; documentos inválidos são ignorados.
+    fn snapshot_notebooks(snapshot: &JsValue) -> Result<Vec<Notebook>, ServiceError> {
+        let docs: Array = Reflect::get(snapshot, &JsValue::from_str("docs"))
+            .map_err(|_| ServiceError::missing("Docs"))?
+            .into();
+
+        let mut notebooks = Vec::new();
+        for doc in docs.iter() {
+            match Self::js_to_data::<Notebook>(&doc) {
+                Ok(mut notebook) => {
+                    notebook.id = Reflect::get(&doc, &JsValue::from_str("id")).ok().and_then(|v| v.as_string());
+                    notebooks.push(notebook);
+                }
+                Err(e) => web_sys::console::error_1(&format!("Caderno ignorado: {:?}", e).into()),
+            }
+        }
+        Ok(notebooks)
+    }
+
+    fn notebook_to_js(notebook: &Notebook) -> Result<JsValue, ServiceError> {
+        let mut doc = notebook.clone();
+        doc.id = None;
+        doc.serialize(&Serializer::json_compatible())
+            .map_err(|e| ServiceError::Serialization(e.to_string()))
+    }
+
+    /// `onSnapshot` de uma consulta: cada snapshot passa por `decode` e o
+    /// resultado, ou o erro da assinatura, vai para `deliver`.
+    fn on_snapshot<T: 'static>(
+        query: &JsValue,
+        deliver: impl Fn(Result<T, ServiceError>) + 'static,
+        decode: impl Fn(&JsValue) -> Result<T, ServiceError> + 'static,
+    ) -> Result<Subscription, ServiceError> {
+        let deliver = Rc::new(deliver);
+        let on_next = {
+            let deliver = deliver.clone();
+            Closure::<dyn FnMut(JsValue)>::new(move |snapshot: JsValue| deliver(decode(&snapshot)))
+        };
+        let on_error = Closure::<dyn FnMut(JsValue)>::new(move |err: JsValue| {
+            deliver(Err(ServiceError::from_js(&err)));
+        });
+
+        let snapshot_fn = Reflect::get(query, &JsValue::from_str("onSnapshot"))
+            .map_err(|_| ServiceError::missing("Método onSnapshot"))?;
+        let snapshot_fn: Function = snapshot_fn.into();
+        let unsubscribe: Function = snapshot_fn.call2(query, on_next.as_ref(), on_error.as_ref())
+            .map_err(|e| ServiceError::from_js(&e))?
+            .into();
+
+        Ok(Subscription::new(move || {
+            let _ = unsubscribe.call0(&JsValue::NULL);
+            // os closures só podem ser liberados depois que o Firestore para de chamá-los
+            drop(on_next);
+            drop(on_error);
+        }))
+    }
+
+    /// Entrega as mensagens de um snapshot uma a uma.
+    fn each_message(on_message: CollabHandler) -> impl Fn(Result<Vec<CollabMessage>, ServiceError>) {
+        move |messages| match messages {
+            Ok(messages) => messages.into_iter().for_each(|m| on_message(Ok(m))),
+            Err(e) => on_message(Err(e)),
+        }
+    }
+
+    /// Pares `(type, doc)` de `snapshot.docChanges()`.
+    fn doc_changes(snapshot: &JsValue) -> Result<Vec<(String, JsValue)>, ServiceError> {
+        let changes_fn = Reflect::get(snapshot, &JsValue::from_str("docChanges"))
+            .map_err(|_| ServiceError::missing("Método docChanges"))?;
+        let changes_fn: Function = changes_fn.into();
+        let changes: Array = changes_fn.call0(snapshot)
+            .map_err(|e| ServiceError::from_js(&e))?
+            .into();
+
+        changes.iter()
+            .map(|change| {
+                let kind = Reflect::get(&change, &JsValue::from_str("type"))
+                    .ok()
+                    .and_then(|v| v.as_string())
+                    .unwrap_or_default();
+                let doc = Reflect::get(&change, &JsValue::from_str("doc"))
+                    .map_err(|_| ServiceError::missing("Documento"))?;
+                Ok((kind, doc))
+            })
+            .collect()
+    }
+
+    fn sub_collection(note_id: &str, name: &str) -> Result<JsValue, ServiceError> {
+        let doc_ref = Self::get_doc(&Self::get_collection("notes")?, note_id)?;
+        let collection_fn = Reflect::get(&doc_ref, &JsValue::from_str("collection"))
+            .map_err(|_| ServiceError::missing("Método collection"))?;
+        let collection_fn: Function = collection_fn.into();
+
+        collection_fn.call1(&doc_ref, &JsValue::from_str(name))
+            .map_err(|e| ServiceError::from_js(&e))
+    }
+
+    /// Grava `value` em `collection/{id}` sem esperar a confirmação do servidor.
+    fn set_data(collection: &JsValue, id: &str, value: &impl Serialize) -> Result<(), ServiceError> {
+        let data = value.serialize(&Serializer::json_compatible())
+            .map_err(|e| ServiceError::Serialization(e.to_string()))?;
+        Self::call_doc(collection, id, "set", &data)
+    }
+
+    fn delete_doc(collection: &JsValue, id: &str) -> Result<(), ServiceError> {
+        Self::call_doc(collection, id, "delete", &JsValue::UNDEFINED)
+    }
+
+    fn call_doc(collection: &JsValue, id: &str, method: &str, arg: &JsValue) -> Result<(), ServiceError> {
+        let doc_ref = Self::get_doc(collection, id)?;
+        let method_fn = Reflect::get(&doc_ref, &JsValue::from_str(method))
+            .map_err(|_| ServiceError::missing(method))?;
+        let method_fn: Function = method_fn.into();
+        let promise = method_fn.call1(&doc_ref, arg)
+            .map_err(|e| ServiceError::from_js(&e))?;
+
+        spawn_local(async move {
+            if let Err(e) = JsFuture::from(js_sys::Promise::from(promise)).await {
+                web_sys::console::error_1(&format!("Erro no Firestore: {}", ServiceError::from_js(&e)).into());
+            }
+        });
+        Ok(())
+    }
+
+    fn js_to_data<T: serde::de::DeserializeOwned>(doc: &JsValue) -> Result<T, ServiceError> {
+        let data_fn = Reflect::get(doc, &JsValue::from_str("data"))
+            .map_err(|_| ServiceError::missing("Método data"))?;
+        let data_fn: Function = data_fn.into();
+        let data = data_fn.call0(doc)
+            .map_err(|e| ServiceError::from_js(&e))?;
+
+        serde_wasm_bindgen::from_value(data)
+            .map_err(|e| ServiceError::Serialization(e.to_string()))
+    }
+
+    /// Converte `snapshot.docChanges()` em `NoteChange`s.
+    fn snapshot_changes(snapshot: &JsValue) -> Result<Vec<NoteChange>, ServiceError> {
+        let mut out = Vec::new();
+        for (kind, doc) in Self::doc_changes(snapshot)? {
+            if kind == "removed" {
+                if let Some(id) = Reflect::get(&doc, &JsValue::from_str("id")).ok().and_then(|v| v.as_string()) {
+                    out.push(NoteChange::Removed(id));
+                }
+                continue;
+            }
+            match Self::js_to_note(&doc) {
+                Ok(note) if kind == "added" => out.push(NoteChange::Added(note)),
+                Ok(note) => out.push(NoteChange::Modified(note)),
+                Err(e) => web_sys::console::error_1(&format!("Nota ignorada: {:?}", e).into()),
+            }
+        }
+        Ok(out)
+    }
+
+    fn get_collection(name: &str) -> Result<JsValue, ServiceError> {
+        let res = DB.with(|db| {
+            Reflect::get(db, &JsValue::from_str("collection")).and_then(|f| {
+                let func: Function = f.into();
+                func.call1(db, &JsValue::from_str(name))
+            })
+        });
+
+        res.map_err(|e| ServiceError::from_js(&e))
+    }
+    
+    fn get_doc(collection: &JsValue, id: &str) -> Result<JsValue, ServiceError> {
+        let doc_fn = Reflect::get(collection, &JsValue::from_str("doc"))
+            .map_err(|_| ServiceError::missing("Método doc"))?;
+        let doc_fn: Function = doc_fn.into();
+        
+        doc_fn.call1(collection, &JsValue::from_str(id))
+            .map_err(|e| ServiceError::from_js(&e))
+    }
+    
+    /// As regras de `firestore.rules` só permitem consultas limitadas às
+    /// notas que o usuário pode ler.
+    fn query(query: &NoteQuery) -> Result<JsValue, ServiceError> {
+        let notes_ref = Self::get_collection("notes")?;
+        match query {
+            NoteQuery::Owner(uid) => Self::where_clause(&notes_ref, "userId", "==", &JsValue::from_str(uid)),
+            NoteQuery::SharedWith(email) => {
+                Self::where_clause(&notes_ref, "sharedWith", "array-contains", &JsValue::from_str(email))
+            }
+        }
+    }
+
+    fn where_clause(collection: &JsValue, field: &str, op: &str, value: &JsValue) -> Result<JsValue, ServiceError> {
+        let where_fn = Reflect::get(collection, &JsValue::from_str("where"))
+            .map_err(|_| ServiceError::missing("Método where"))?;
+        let where_fn: Function = where_fn.into();
+        
+        where_fn.call3(collection, &JsValue::from_str(field), &JsValue::from_str(op), value)
+            .map_err(|e| ServiceError::from_js(&e))
+    }
+    
+
+    fn note_to_js(note: &Note) -> Result<JsValue, ServiceError> {
+        // o id é o nome do documento, não um campo
+        let mut doc = note.clone();
+        doc.id = None;
+        doc.schema_version = NOTE_SCHEMA_VERSION;
+
+        doc.serialize(&Serializer::json_compatible())
+            .map_err(|e| ServiceError::Serialization(e.to_string()))
+    }
+    
+    fn js_to_note(doc: &JsValue) -> Result<Note, ServiceError> {
+        let id = Reflect::get(doc, &"id".into())
+            .ok()
+            .and_then(|v| v.as_string());
+        
+        let data = Reflect::get(doc, &"data".into())
+            .map_err(|_| ServiceError::missing("Método data"))?;
+        
+        let data_fn: js_sys::Function = data.into();
+        let data_obj = data_fn.call0(doc)
+            .map_err(|e| ServiceError::from_js(&e))?;
+
+        let malformed = |e: &dyn std::fmt::Display| ServiceError::Serialization(
+            format!("documento {}: {}", id.as_deref().unwrap_or("?"), e)
+        );
+
+        let mut note: Note = serde_wasm_bindgen::from_value(data_obj)
+            .map_err(|e| malformed(&e))?;
+        note.id = id.clone();
+        note.upgrade().map_err(|e| malformed(&e))
+    }
+}
+
+impl NotebookStore for FirestoreStore {
+    async fn create_notebook(&self, notebook: &Notebook) -> Result<String, ServiceError> {
+        let notebooks_ref = Self::get_collection("notebooks")?;
+        Self::add_doc(&notebooks_ref, &Self::notebook_to_js(notebook)?).await
+    }
+
+    async fn update_notebook(&self, notebook: &Notebook) -> Result<(), ServiceError> {
+        let notebook_id = notebook.id.as_ref().ok_or(ServiceError::NotFound)?;
+        let notebooks_ref = Self::get_collection("notebooks")?;
+        Self::write_doc(&notebooks_ref, notebook_id, "set", &Self::notebook_to_js(notebook)?).await
+    }
+
+    async fn delete_notebook(&self, notebook_id: &str) -> Result<(), ServiceError> {
+        let notebooks_ref = Self::get_collection("notebooks")?;
+        let notebook_ref = Self::get_doc(&notebooks_ref, notebook_id)?;
+        let snapshot = Self::fetch(&notebook_ref).await?;
+        if !Reflect::get(&snapshot, &JsValue::from_str("exists")).ok().and_then(|v| v.as_bool()).unwrap_or(false) {
+            return Ok(());
+        }
+        let notebook: Notebook = Self::js_to_data(&snapshot)?;
+        let parent_id = notebook.parent_id.as_deref().map_or(JsValue::NULL, JsValue::from_str);
+
+        let children: Vec<Notebook> = Self::snapshot_notebooks(&Self::fetch(&Self::notebooks_query(&notebook.user_id)?).await?)?
+            .into_iter()
+            .filter(|n| n.parent_id.as_deref() == Some(notebook_id))
+            .collect();
+        let notes_ref = Self::get_collection("notes")?;
+        let notes_query = Self::where_clause(&notes_ref, "userId", "==", &JsValue::from_str(&notebook.user_id))?;
+        let notes_query = Self::where_clause(&notes_query, "notebookId", "==", &JsValue::from_str(notebook_id))?;
+        let docs: Array = Reflect::get(&Self::fetch(&notes_query).await?, &JsValue::from_str("docs"))
+            .map_err(|_| ServiceError::missing("Docs"))?
+            .into();
+
+        // um único lote: ou tudo sobe para o caderno pai e o caderno some,
+        // ou nada muda
+        let batch = DB.with(|db| {
+            Reflect::get(db, &JsValue::from_str("batch")).and_then(|f| Function::from(f).call0(db))
+        }).map_err(|e| ServiceError::from_js(&e))?;
+        for child in &children {
+            let child_id = child.id.as_deref().ok_or(ServiceError::NotFound)?;
+            let fields = Object::new();
+            let _ = Reflect::set(&fields, &JsValue::from_str("parentId"), &parent_id);
+            Self::batch_call(&batch, "update", &[Self::get_doc(&notebooks_ref, child_id)?, fields.into()])?;
+        }
+        for doc in docs.iter() {
+            let note = Self::js_to_note(&doc)?;
+            let note_id = note.id.as_deref().ok_or(ServiceError::NotFound)?;
+            let fields = Object::new();
+            let _ = Reflect::set(&fields, &JsValue::from_str("notebookId"), &parent_id);
+            // quem ainda tem a revisão anterior recebe conflito em vez de
+            // devolver a nota ao caderno excluído
+            let _ = Reflect::set(&fields, &JsValue::from_str("revision"), &JsValue::from_f64((note.revision + 1) as f64));
+            Self::batch_call(&batch, "update", &[Self::get_doc(&notes_ref, note_id)?, fields.into()])?;
+        }
+        Self::batch_call(&batch, "delete", &[notebook_ref])?;
+
+        let commit: Function = Reflect::get(&batch, &JsValue::from_str("commit"))
+            .map_err(|_| ServiceError::missing("Método commit"))?
+            .into();
+        let promise = commit.call0(&batch)
+            .map_err(|e| ServiceError::from_js(&e))?;
+        JsFuture::from(js_sys::Promise::from(promise)).await
+            .map_err(|e| ServiceError::from_js(&e))?;
+        Ok(())
+    }
+
+    async fn list_notebooks(&self, user_id: &str) -> Result<Vec<Notebook>, ServiceError> {
+        let snapshot = Self::fetch(&Self::notebooks_query(user_id)?).await?;
+        Self::snapshot_notebooks(&snapshot)
+    }
+
+    fn subscribe_notebooks(&self, user_id: &str, on_change: NotebooksHandler) -> Result<Subscription, ServiceError> {
+        Self::on_snapshot(&Self::notebooks_query(user_id)?, move |notebooks| on_change(notebooks), Self::snapshot_notebooks)
+    }
+}
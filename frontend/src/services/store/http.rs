@@ -0,0 +1,375 @@
+// src/services/store/http.rs
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use gloo_net::http::{Request, RequestBuilder, Response};
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+use js_sys::Reflect;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{CloseEvent, MessageEvent, WebSocket};
+use notepad_models::{CollabMessage, Note, Notebook};
+use crate::services::auth::AuthService;
+use crate::services::error::ServiceError;
+use super::{ChangeHandler, CollabChannel, CollabHandler, Listeners, NoteChange, NoteQuery, NoteStore, NotebookListeners, NotebookStore, NotebooksHandler, Subscription};
+
+const DEFAULT_API_URL: &str = "http://localhost:3000";
+
+/// Notas no servidor `backend/` via API REST (`/api/notes`).
+///
+/// O servidor não envia notificações de notas; as assinaturas recebem apenas
+/// as alterações feitas por esta aba. A edição colaborativa usa um WebSocket
+/// por nota (`/api/notes/{id}/collab`). O token de ID do Firebase do usuário
+/// logado vai no cabeçalho `Authorization` e identifica quem faz cada
+/// requisição; `x-user-id` e `x-user-email` só valem para um servidor de
+/// desenvolvimento (feature `dev-auth`).
+#[derive(Clone)]
+pub struct HttpNoteStore {
+    base_url: String,
+    listeners: Listeners,
+    notebook_listeners: NotebookListeners,
+}
+
+#[derive(Deserialize)]
+struct Created {
+    id: String,
+}
+
+impl Default for HttpNoteStore {
+    /// Usa `window.NOTES_API_URL` se definido em `index.html`.
+    fn default() -> Self {
+        let base_url = Reflect::get(&js_sys::global(), &JsValue::from_str("NOTES_API_URL"))
+            .ok()
+            .and_then(|v| v.as_string())
+            .unwrap_or_else(|| DEFAULT_API_URL.to_string());
+        Self::new(&base_url)
+    }
+}
+
+impl HttpNoteStore {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            listeners: Listeners::default(),
+            notebook_listeners: NotebookListeners::default(),
+        }
+    }
+
+    fn notes_url(&self) -> String {
+        format!("{}/api/notes", self.base_url)
+    }
+
+    fn note_url(&self, note_id: &str) -> String {
+        format!("{}/api/notes/{}", self.base_url, note_id)
+    }
+
+    fn notebooks_url(&self) -> String {
+        format!("{}/api/notebooks", self.base_url)
+    }
+
+    fn notebook_url(&self, notebook_id: &str) -> String {
+        format!("{}/api/notebooks/{}", self.base_url, notebook_id)
+    }
+
+    fn collab_url(&self, note_id: &str, token: Option<&str>) -> String {
+        let url = self.note_url(note_id);
+        let url = match url.strip_prefix("https://") {
+            Some(rest) => format!("wss://{}", rest),
+            None => format!("ws://{}", url.trim_start_matches("http://")),
+        };
+        let (uid, email) = AuthService::get_current_user()
+            .map(|u| (u.uid, u.email))
+            .unwrap_or_default();
+        // o navegador não permite cabeçalhos próprios no WebSocket
+        format!(
+            "{}/collab?token={}&uid={}&email={}",
+            url,
+            js_sys::encode_uri_component(token.unwrap_or_default()),
+            js_sys::encode_uri_component(&uid),
+            js_sys::encode_uri_component(&email)
+        )
+    }
+
+    async fn with_user(request: RequestBuilder) -> RequestBuilder {
+        let request = match AuthService::get_current_user() {
+            Some(user) => request
+                .header("x-user-id", &user.uid)
+                .header("x-user-email", &user.email),
+            None => return request,
+        };
+        match AuthService::id_token().await {
+            Ok(token) => request.header("Authorization", &format!("Bearer {}", token)),
+            Err(e) => {
+                web_sys::console::error_1(&format!("Erro ao obter o token de ID: {}", e).into());
+                request
+            }
+        }
+    }
+
+    /// Converte respostas HTTP de erro em `ServiceError`.
+    async fn check(response: Response) -> Result<Response, ServiceError> {
+        match response.status() {
+            200..=299 => Ok(response),
+            401 | 403 => Err(ServiceError::PermissionDenied),
+            404 => Err(ServiceError::NotFound),
+            409 | 412 => Err(ServiceError::Conflict),
+            502..=504 => Err(ServiceError::Network(format!("HTTP {}", response.status()))),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(ServiceError::Backend(format!("HTTP {} {}", status, body)))
+            }
+        }
+    }
+}
+
+impl NoteStore for HttpNoteStore {
+    async fn create(&self, note: &Note) -> Result<String, ServiceError> {
+        let response = Self::with_user(Request::post(&self.notes_url())).await
+            .json(note)
+            .map_err(|e| ServiceError::Serialization(e.to_string()))?
+            .send()
+            .await
+            .map_err(|e| ServiceError::Network(e.to_string()))?;
+
+        let created: Created = Self::check(response).await?
+            .json()
+            .await
+            .map_err(|e| ServiceError::Serialization(e.to_string()))?;
+        let mut stored = note.clone();
+        stored.id = Some(created.id.clone());
+        self.listeners.notify(NoteChange::Added(stored));
+        Ok(created.id)
+    }
+
+    async fn get(&self, note_id: &str) -> Result<Option<Note>, ServiceError> {
+        let response = Self::with_user(Request::get(&self.note_url(note_id))).await
+            .send()
+            .await
+            .map_err(|e| ServiceError::Network(e.to_string()))?;
+
+        match Self::check(response).await {
+            Ok(response) => {
+                let note: Note = response.json()
+                    .await
+                    .map_err(|e| ServiceError::Serialization(e.to_string()))?;
+                note.upgrade()
+                    .map(Some)
+                    .map_err(|e| ServiceError::Serialization(e.to_string()))
+            }
+            Err(ServiceError::NotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn update(&self, note: &Note) -> Result<Note, ServiceError> {
+        let note_id = note.id.as_ref().ok_or(ServiceError::NotFound)?;
+        let response = Self::with_user(Request::put(&self.note_url(note_id))).await
+            .json(note)
+            .map_err(|e| ServiceError::Serialization(e.to_string()))?
+            .send()
+            .await
+            .map_err(|e| ServiceError::Network(e.to_string()))?;
+
+        let stored: Note = Self::check(response).await?
+            .json()
+            .await
+            .map_err(|e| ServiceError::Serialization(e.to_string()))?;
+        self.listeners.notify(NoteChange::Modified(stored.clone()));
+        Ok(stored)
+    }
+
+    async fn delete(&self, note_id: &str) -> Result<(), ServiceError> {
+        let response = Self::with_user(Request::delete(&self.note_url(note_id))).await
+            .send()
+            .await
+            .map_err(|e| ServiceError::Network(e.to_string()))?;
+
+        Self::check(response).await?;
+        self.listeners.notify(NoteChange::Removed(note_id.to_string()));
+        Ok(())
+    }
+
+    async fn list(&self, query: &NoteQuery) -> Result<Vec<Note>, ServiceError> {
+        let param = match query {
+            NoteQuery::Owner(uid) => ("user_id", uid),
+            NoteQuery::SharedWith(email) => ("shared_with", email),
+        };
+        let response = Self::with_user(Request::get(&self.notes_url())).await
+            .query([param])
+            .send()
+            .await
+            .map_err(|e| ServiceError::Network(e.to_string()))?;
+
+        let notes: Vec<Note> = Self::check(response).await?
+            .json()
+            .await
+            .map_err(|e| ServiceError::Serialization(e.to_string()))?;
+        notes.into_iter()
+            .map(|n| n.upgrade().map_err(|e| ServiceError::Serialization(e.to_string())))
+            .collect()
+    }
+
+    fn subscribe(&self, query: &NoteQuery, on_change: ChangeHandler) -> Result<Subscription, ServiceError> {
+        Ok(self.listeners.subscribe(self.clone(), query, on_change))
+    }
+
+    fn join_collab(&self, note_id: &str, on_message: CollabHandler) -> Result<CollabChannel, ServiceError> {
+        // aberto depois de obter o token de ID
+        let socket: Rc<RefCell<Option<WebSocket>>> = Rc::default();
+        let left = Rc::new(Cell::new(false));
+        // mensagens enviadas antes de a conexão abrir
+        let pending: Rc<RefCell<Vec<String>>> = Rc::default();
+
+        let on_open = {
+            let socket = socket.clone();
+            let pending = pending.clone();
+            Closure::<dyn FnMut()>::new(move || {
+                let Some(socket) = socket.borrow().clone() else { return };
+                for text in pending.borrow_mut().drain(..) {
+                    let _ = socket.send_with_str(&text);
+                }
+            })
+        };
+        let on_text = {
+            let on_message = on_message.clone();
+            Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+                if let Some(text) = event.data().as_string() {
+                    on_message(serde_json::from_str(&text)
+                        .map_err(|e| ServiceError::Serialization(e.to_string())));
+                }
+            })
+        };
+        let on_close = {
+            let on_message = on_message.clone();
+            Closure::<dyn FnMut(CloseEvent)>::new(move |event: CloseEvent| {
+                if !event.was_clean() {
+                    on_message(Err(ServiceError::Network(format!("WebSocket fechado ({})", event.code()))));
+                }
+            })
+        };
+        let handlers = Rc::new((on_open, on_text, on_close));
+
+        {
+            let url_for = {
+                let store = self.clone();
+                let note_id = note_id.to_string();
+                move |token: Option<String>| store.collab_url(&note_id, token.as_deref())
+            };
+            let socket = socket.clone();
+            let left = left.clone();
+            let handlers = handlers.clone();
+            spawn_local(async move {
+                let token = AuthService::id_token().await.ok();
+                if left.get() {
+                    return;
+                }
+                match WebSocket::new(&url_for(token)) {
+                    Ok(ws) => {
+                        let (on_open, on_text, on_close) = &*handlers;
+                        ws.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+                        ws.set_onmessage(Some(on_text.as_ref().unchecked_ref()));
+                        ws.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+                        *socket.borrow_mut() = Some(ws);
+                    }
+                    Err(e) => on_message(Err(ServiceError::Network(format!("{:?}", e)))),
+                }
+            });
+        }
+
+        let send = {
+            let socket = socket.clone();
+            move |message: CollabMessage| {
+                let text = match serde_json::to_string(&message) {
+                    Ok(text) => text,
+                    Err(e) => return web_sys::console::error_1(&format!("Mensagem inválida: {}", e).into()),
+                };
+                match socket.borrow().as_ref() {
+                    Some(socket) if socket.ready_state() != WebSocket::CONNECTING => {
+                        if socket.send_with_str(&text).is_err() {
+                            web_sys::console::error_1(&"Erro ao enviar alteração colaborativa".into());
+                        }
+                    }
+                    _ => pending.borrow_mut().push(text),
+                }
+            }
+        };
+        let leave = Subscription::new(move || {
+            left.set(true);
+            if let Some(socket) = socket.borrow_mut().take() {
+                socket.set_onopen(None);
+                socket.set_onmessage(None);
+                socket.set_onclose(None);
+                let _ = socket.close();
+            }
+            drop(handlers);
+        });
+        Ok(CollabChannel::new(send, leave))
+    }
+}
+
+impl NotebookStore for HttpNoteStore {
+    async fn create_notebook(&self, notebook: &Notebook) -> Result<String, ServiceError> {
+        let response = Self::with_user(Request::post(&self.notebooks_url())).await
+            .json(notebook)
+            .map_err(|e| ServiceError::Serialization(e.to_string()))?
+            .send()
+            .await
+            .map_err(|e| ServiceError::Network(e.to_string()))?;
+
+        let created: Created = Self::check(response).await?
+            .json()
+            .await
+            .map_err(|e| ServiceError::Serialization(e.to_string()))?;
+        self.notebook_listeners.notify(self.clone());
+        Ok(created.id)
+    }
+
+    async fn update_notebook(&self, notebook: &Notebook) -> Result<(), ServiceError> {
+        let notebook_id = notebook.id.as_ref().ok_or(ServiceError::NotFound)?;
+        let response = Self::with_user(Request::put(&self.notebook_url(notebook_id))).await
+            .json(notebook)
+            .map_err(|e| ServiceError::Serialization(e.to_string()))?
+            .send()
+            .await
+            .map_err(|e| ServiceError::Network(e.to_string()))?;
+
+        Self::check(response).await?;
+        self.notebook_listeners.notify(self.clone());
+        Ok(())
+    }
+
+    /// O backend move o conteúdo do caderno para o caderno pai e responde
+    /// com as notas movidas.
+    async fn delete_notebook(&self, notebook_id: &str) -> Result<(), ServiceError> {
+        let response = Self::with_user(Request::delete(&self.notebook_url(notebook_id))).await
+            .send()
+            .await
+            .map_err(|e| ServiceError::Network(e.to_string()))?;
+
+        let moved: Vec<Note> = Self::check(response).await?
+            .json()
+            .await
+            .map_err(|e| ServiceError::Serialization(e.to_string()))?;
+        self.notebook_listeners.notify(self.clone());
+        for note in moved {
+            self.listeners.notify(NoteChange::Modified(note));
+        }
+        Ok(())
+    }
+
+    async fn list_notebooks(&self, user_id: &str) -> Result<Vec<Notebook>, ServiceError> {
+        let response = Self::with_user(Request::get(&self.notebooks_url())).await
+            .query([("user_id", user_id)])
+            .send()
+            .await
+            .map_err(|e| ServiceError::Network(e.to_string()))?;
+
+        Self::check(response).await?
+            .json()
+            .await
+            .map_err(|e| ServiceError::Serialization(e.to_string()))
+    }
+
+    fn subscribe_notebooks(&self, user_id: &str, on_change: NotebooksHandler) -> Result<Subscription, ServiceError> {
+        Ok(self.notebook_listeners.subscribe(self.clone(), user_id, on_change))
+    }
+}
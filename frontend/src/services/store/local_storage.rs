@@ -0,0 +1,168 @@
+// src/services/store/local_storage.rs
+use std::collections::BTreeMap;
+use gloo_storage::errors::StorageError;
+use gloo_storage::{LocalStorage, Storage};
+use notepad_models::{Note, Notebook};
+use crate::services::error::ServiceError;
+use super::{ChangeHandler, CollabChannel, CollabHandler, CollabHub, Listeners, NoteChange, NoteQuery, NoteStore, NotebookListeners, NotebookStore, NotebooksHandler, Subscription, remove_notebook};
+
+const NOTES_KEY: &str = "notepad.notes";
+const NOTEBOOKS_KEY: &str = "notepad.notebooks";
+
+/// Persiste as notas no localStorage do navegador, indexadas pelo id.
+#[derive(Clone, Default)]
+pub struct LocalStorageStore {
+    listeners: Listeners,
+    collab: CollabHub,
+    notebook_listeners: NotebookListeners,
+}
+
+impl LocalStorageStore {
+    fn load() -> Result<BTreeMap<String, Note>, ServiceError> {
+        let notes: BTreeMap<String, Note> = match LocalStorage::get(NOTES_KEY) {
+            Ok(notes) => notes,
+            Err(StorageError::KeyNotFound(_)) => BTreeMap::new(),
+            // não sobrescrever dados que não conseguimos ler
+            Err(e) => return Err(ServiceError::Serialization(e.to_string())),
+        };
+        notes.into_iter()
+            .map(|(id, note)| note.upgrade()
+                .map(|n| (id, n))
+                .map_err(|e| ServiceError::Serialization(e.to_string())))
+            .collect()
+    }
+
+    fn save(notes: &BTreeMap<String, Note>) -> Result<(), ServiceError> {
+        LocalStorage::set(NOTES_KEY, notes)
+            .map_err(|e| ServiceError::Backend(format!("Erro ao gravar no localStorage: {}", e)))
+    }
+
+    fn load_notebooks() -> Result<BTreeMap<String, Notebook>, ServiceError> {
+        match LocalStorage::get(NOTEBOOKS_KEY) {
+            Ok(notebooks) => Ok(notebooks),
+            Err(StorageError::KeyNotFound(_)) => Ok(BTreeMap::new()),
+            Err(e) => Err(ServiceError::Serialization(e.to_string())),
+        }
+    }
+
+    fn save_notebooks(notebooks: &BTreeMap<String, Notebook>) -> Result<(), ServiceError> {
+        LocalStorage::set(NOTEBOOKS_KEY, notebooks)
+            .map_err(|e| ServiceError::Backend(format!("Erro ao gravar no localStorage: {}", e)))
+    }
+
+    fn new_id() -> String {
+        let now = js_sys::Date::now() as u64;
+        let rand = (js_sys::Math::random() * 1e9) as u64;
+        format!("local-{:x}-{:x}", now, rand)
+    }
+}
+
+impl NoteStore for LocalStorageStore {
+    async fn create(&self, note: &Note) -> Result<String, ServiceError> {
+        let mut notes = Self::load()?;
+        let id = Self::new_id();
+
+        let mut stored = note.clone();
+        stored.id = Some(id.clone());
+        notes.insert(id.clone(), stored.clone());
+        Self::save(&notes)?;
+        self.listeners.notify(NoteChange::Added(stored));
+        Ok(id)
+    }
+
+    async fn get(&self, note_id: &str) -> Result<Option<Note>, ServiceError> {
+        Ok(Self::load()?.remove(note_id))
+    }
+
+    async fn update(&self, note: &Note) -> Result<Note, ServiceError> {
+        let note_id = note.id.as_ref().ok_or(ServiceError::NotFound)?;
+        let mut notes = Self::load()?;
+        let current = notes.get(note_id).ok_or(ServiceError::NotFound)?;
+        if current.revision != note.revision {
+            return Err(ServiceError::Conflict);
+        }
+        let mut stored = note.clone();
+        stored.revision = note.revision + 1;
+        notes.insert(note_id.clone(), stored.clone());
+        Self::save(&notes)?;
+        self.listeners.notify(NoteChange::Modified(stored.clone()));
+        Ok(stored)
+    }
+
+    async fn delete(&self, note_id: &str) -> Result<(), ServiceError> {
+        let mut notes = Self::load()?;
+        notes.remove(note_id);
+        Self::save(&notes)?;
+        self.listeners.notify(NoteChange::Removed(note_id.to_string()));
+        Ok(())
+    }
+
+    async fn list(&self, query: &NoteQuery) -> Result<Vec<Note>, ServiceError> {
+        Ok(Self::load()?
+            .into_values()
+            .filter(|n| query.matches(n))
+            .collect())
+    }
+
+    fn subscribe(&self, query: &NoteQuery, on_change: ChangeHandler) -> Result<Subscription, ServiceError> {
+        Ok(self.listeners.subscribe(self.clone(), query, on_change))
+    }
+
+    fn join_collab(&self, note_id: &str, on_message: CollabHandler) -> Result<CollabChannel, ServiceError> {
+        Ok(self.collab.join(note_id, on_message))
+    }
+}
+
+impl NotebookStore for LocalStorageStore {
+    async fn create_notebook(&self, notebook: &Notebook) -> Result<String, ServiceError> {
+        let mut notebooks = Self::load_notebooks()?;
+        let id = Self::new_id();
+
+        let mut stored = notebook.clone();
+        stored.id = Some(id.clone());
+        notebooks.insert(id.clone(), stored);
+        Self::save_notebooks(&notebooks)?;
+        self.notebook_listeners.notify(self.clone());
+        Ok(id)
+    }
+
+    async fn update_notebook(&self, notebook: &Notebook) -> Result<(), ServiceError> {
+        let id = notebook.id.as_ref().ok_or(ServiceError::NotFound)?;
+        let mut notebooks = Self::load_notebooks()?;
+        if !notebooks.contains_key(id) {
+            return Err(ServiceError::NotFound);
+        }
+        notebooks.insert(id.clone(), notebook.clone());
+        Self::save_notebooks(&notebooks)?;
+        self.notebook_listeners.notify(self.clone());
+        Ok(())
+    }
+
+    async fn delete_notebook(&self, notebook_id: &str) -> Result<(), ServiceError> {
+        let mut notebooks = Self::load_notebooks()?;
+        let mut notes = Self::load()?;
+        let Some(moved) = remove_notebook(&mut notebooks, &mut notes, notebook_id) else {
+            return Ok(());
+        };
+        // notas primeiro: se a segunda gravação falhar, elas já estão no
+        // caderno pai e o caderno excluído continua existindo
+        Self::save(&notes)?;
+        Self::save_notebooks(&notebooks)?;
+        for note in moved {
+            self.listeners.notify(NoteChange::Modified(note));
+        }
+        self.notebook_listeners.notify(self.clone());
+        Ok(())
+    }
+
+    async fn list_notebooks(&self, user_id: &str) -> Result<Vec<Notebook>, ServiceError> {
+        Ok(Self::load_notebooks()?
+            .into_values()
+            .filter(|n| n.user_id == user_id)
+            .collect())
+    }
+
+    fn subscribe_notebooks(&self, user_id: &str, on_change: NotebooksHandler) -> Result<Subscription, ServiceError> {
+        Ok(self.notebook_listeners.subscribe(self.clone(), user_id, on_change))
+    }
+}
@@ -0,0 +1,118 @@
+// src/services/store/memory.rs
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use notepad_models::{Note, Notebook};
+use crate::services::error::ServiceError;
+use super::{ChangeHandler, CollabChannel, CollabHandler, CollabHub, Listeners, NoteChange, NoteQuery, NoteStore, NotebookListeners, NotebookStore, NotebooksHandler, Subscription, remove_notebook};
+
+/// Armazenamento volátil: as notas somem ao recarregar a página.
+/// Útil para desenvolvimento sem um projeto Firebase.
+#[derive(Clone, Default)]
+pub struct MemoryStore {
+    notes: Rc<RefCell<BTreeMap<String, Note>>>,
+    next_id: Rc<Cell<u64>>,
+    listeners: Listeners,
+    collab: CollabHub,
+    notebooks: Rc<RefCell<BTreeMap<String, Notebook>>>,
+    notebook_listeners: NotebookListeners,
+}
+
+impl NoteStore for MemoryStore {
+    async fn create(&self, note: &Note) -> Result<String, ServiceError> {
+        let id = format!("mem-{}", self.next_id.get());
+        self.next_id.set(self.next_id.get() + 1);
+
+        let mut stored = note.clone();
+        stored.id = Some(id.clone());
+        self.notes.borrow_mut().insert(id.clone(), stored.clone());
+        self.listeners.notify(NoteChange::Added(stored));
+        Ok(id)
+    }
+
+    async fn get(&self, note_id: &str) -> Result<Option<Note>, ServiceError> {
+        Ok(self.notes.borrow().get(note_id).cloned())
+    }
+
+    async fn update(&self, note: &Note) -> Result<Note, ServiceError> {
+        let note_id = note.id.as_ref().ok_or(ServiceError::NotFound)?;
+        let mut stored = note.clone();
+        stored.revision = note.revision + 1;
+        {
+            let mut notes = self.notes.borrow_mut();
+            let current = notes.get(note_id).ok_or(ServiceError::NotFound)?;
+            if current.revision != note.revision {
+                return Err(ServiceError::Conflict);
+            }
+            notes.insert(note_id.clone(), stored.clone());
+        }
+        self.listeners.notify(NoteChange::Modified(stored.clone()));
+        Ok(stored)
+    }
+
+    async fn delete(&self, note_id: &str) -> Result<(), ServiceError> {
+        self.notes.borrow_mut().remove(note_id);
+        self.listeners.notify(NoteChange::Removed(note_id.to_string()));
+        Ok(())
+    }
+
+    async fn list(&self, query: &NoteQuery) -> Result<Vec<Note>, ServiceError> {
+        Ok(self.notes.borrow()
+            .values()
+            .filter(|n| query.matches(n))
+            .cloned()
+            .collect())
+    }
+
+    fn subscribe(&self, query: &NoteQuery, on_change: ChangeHandler) -> Result<Subscription, ServiceError> {
+        Ok(self.listeners.subscribe(self.clone(), query, on_change))
+    }
+
+    fn join_collab(&self, note_id: &str, on_message: CollabHandler) -> Result<CollabChannel, ServiceError> {
+        Ok(self.collab.join(note_id, on_message))
+    }
+}
+
+impl NotebookStore for MemoryStore {
+    async fn create_notebook(&self, notebook: &Notebook) -> Result<String, ServiceError> {
+        let id = format!("mem-nb-{}", self.next_id.get());
+        self.next_id.set(self.next_id.get() + 1);
+
+        let mut stored = notebook.clone();
+        stored.id = Some(id.clone());
+        self.notebooks.borrow_mut().insert(id.clone(), stored);
+        self.notebook_listeners.notify(self.clone());
+        Ok(id)
+    }
+
+    async fn update_notebook(&self, notebook: &Notebook) -> Result<(), ServiceError> {
+        let id = notebook.id.as_ref().ok_or(ServiceError::NotFound)?;
+        match self.notebooks.borrow_mut().get_mut(id) {
+            Some(stored) => *stored = notebook.clone(),
+            None => return Err(ServiceError::NotFound),
+        }
+        self.notebook_listeners.notify(self.clone());
+        Ok(())
+    }
+
+    async fn delete_notebook(&self, notebook_id: &str) -> Result<(), ServiceError> {
+        let moved = remove_notebook(&mut self.notebooks.borrow_mut(), &mut self.notes.borrow_mut(), notebook_id);
+        for note in moved.into_iter().flatten() {
+            self.listeners.notify(NoteChange::Modified(note));
+        }
+        self.notebook_listeners.notify(self.clone());
+        Ok(())
+    }
+
+    async fn list_notebooks(&self, user_id: &str) -> Result<Vec<Notebook>, ServiceError> {
+        Ok(self.notebooks.borrow()
+            .values()
+            .filter(|n| n.user_id == user_id)
+            .cloned()
+            .collect())
+    }
+
+    fn subscribe_notebooks(&self, user_id: &str, on_change: NotebooksHandler) -> Result<Subscription, ServiceError> {
+        Ok(self.notebook_listeners.subscribe(self.clone(), user_id, on_change))
+    }
+}
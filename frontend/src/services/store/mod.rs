@@ -0,0 +1,295 @@
+// src/services/store/mod.rs
+use std::collections::BTreeMap;
+use wasm_bindgen::prelude::*;
+use js_sys::Reflect;
+use notepad_models::{Note, Notebook};
+use crate::services::error::ServiceError;
+
+pub mod collab;
+pub mod firestore;
+pub mod http;
+pub mod local_storage;
+pub mod memory;
+pub mod subscription;
+
+pub use collab::{CollabChannel, CollabHandler, CollabHub};
+pub use firestore::FirestoreStore;
+pub use http::HttpNoteStore;
+pub use local_storage::LocalStorageStore;
+pub use memory::MemoryStore;
+pub use subscription::{ChangeHandler, Listeners, NoteChange, NoteQuery, NotebookListeners, NotebooksHandler, Subscription};
+
+/// Operações de persistência de notas, independentes do backend.
+#[allow(async_fn_in_trait)]
+pub trait NoteStore {
+    async fn create(&self, note: &Note) -> Result<String, ServiceError>;
+    async fn get(&self, note_id: &str) -> Result<Option<Note>, ServiceError>;
+    /// Grava a nota se `note.revision` ainda for a revisão armazenada
+    /// (senão `ServiceError::Conflict`) e retorna a nota com a nova revisão.
+    async fn update(&self, note: &Note) -> Result<Note, ServiceError>;
+    async fn delete(&self, note_id: &str) -> Result<(), ServiceError>;
+    async fn list(&self, query: &NoteQuery) -> Result<Vec<Note>, ServiceError>;
+    /// Acompanha as notas da consulta: primeiro entrega todas como `Added`,
+    /// depois cada alteração, até a `Subscription` ser descartada.
+    fn subscribe(&self, query: &NoteQuery, on_change: ChangeHandler) -> Result<Subscription, ServiceError>;
+    /// Entra no canal de edição colaborativa da nota (changes do documento e
+    /// presença dos participantes), até o `CollabChannel` ser descartado.
+    fn join_collab(&self, note_id: &str, on_message: CollabHandler) -> Result<CollabChannel, ServiceError>;
+}
+
+/// Cadernos do usuário. Não passam pelo outbox do `SyncService`: criar,
+/// renomear e excluir cadernos exige conexão.
+#[allow(async_fn_in_trait)]
+pub trait NotebookStore {
+    async fn create_notebook(&self, notebook: &Notebook) -> Result<String, ServiceError>;
+    async fn update_notebook(&self, notebook: &Notebook) -> Result<(), ServiceError>;
+    /// Exclui o caderno sem perder o conteúdo: as notas e os cadernos dentro
+    /// dele sobem para o caderno pai, na mesma escrita da exclusão.
+    async fn delete_notebook(&self, notebook_id: &str) -> Result<(), ServiceError>;
+    async fn list_notebooks(&self, user_id: &str) -> Result<Vec<Notebook>, ServiceError>;
+    /// Entrega a lista de cadernos do usuário agora e a cada alteração.
+    fn subscribe_notebooks(&self, user_id: &str, on_change: NotebooksHandler) -> Result<Subscription, ServiceError>;
+}
+
+/// Exclusão de caderno para os armazenamentos locais: tira `notebook_id` de
+/// `notebooks` e passa os cadernos e as notas que estavam nele para o caderno
+/// pai. Devolve as notas movidas, já com a nova revisão, ou `None` se o
+/// caderno não existe.
+fn remove_notebook(
+    notebooks: &mut BTreeMap<String, Notebook>,
+    notes: &mut BTreeMap<String, Note>,
+    notebook_id: &str,
+) -> Option<Vec<Note>> {
+    let parent_id = notebooks.remove(notebook_id)?.parent_id;
+    for child in notebooks.values_mut().filter(|n| n.parent_id.as_deref() == Some(notebook_id)) {
+        child.parent_id = parent_id.clone();
+    }
+    let moved = notes.values_mut()
+        .filter(|n| n.notebook_id.as_deref() == Some(notebook_id))
+        .map(|note| {
+            note.notebook_id = parent_id.clone();
+            // quem ainda tem a revisão anterior recebe conflito em vez de
+            // devolver a nota ao caderno excluído
+            note.revision += 1;
+            note.clone()
+        })
+        .collect();
+    Some(moved)
+}
+
+/// Backend escolhido na inicialização (`window.NOTES_BACKEND` em `index.html`).
+#[derive(Clone)]
+pub enum Store {
+    Firestore(FirestoreStore),
+    Http(HttpNoteStore),
+    Memory(MemoryStore),
+    LocalStorage(LocalStorageStore),
+}
+
+impl Store {
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "memory" => Store::Memory(MemoryStore::default()),
+            "local" | "localStorage" => Store::LocalStorage(LocalStorageStore::default()),
+            "http" => Store::Http(HttpNoteStore::default()),
+            "firestore" => Store::Firestore(FirestoreStore),
+            other => {
+                web_sys::console::warn_1(&format!("Backend de notas desconhecido '{}', usando Firestore", other).into());
+                Store::Firestore(FirestoreStore)
+            }
+        }
+    }
+
+    fn from_config() -> Self {
+        let name = Reflect::get(&js_sys::global(), &JsValue::from_str("NOTES_BACKEND"))
+            .ok()
+            .and_then(|v| v.as_string())
+            .unwrap_or_else(|| "firestore".to_string());
+        Self::from_name(&name)
+    }
+}
+
+thread_local! {
+    static STORE: Store = Store::from_config();
+}
+
+/// Retorna o backend ativo; a escolha é feita uma única vez por sessão.
+pub fn current() -> Store {
+    STORE.with(Clone::clone)
+}
+
+impl NoteStore for Store {
+    async fn create(&self, note: &Note) -> Result<String, ServiceError> {
+        match self {
+            Store::Firestore(s) => s.create(note).await,
+            Store::Http(s) => s.create(note).await,
+            Store::Memory(s) => s.create(note).await,
+            Store::LocalStorage(s) => s.create(note).await,
+        }
+    }
+
+    async fn get(&self, note_id: &str) -> Result<Option<Note>, ServiceError> {
+        match self {
+            Store::Firestore(s) => s.get(note_id).await,
+            Store::Http(s) => s.get(note_id).await,
+            Store::Memory(s) => s.get(note_id).await,
+            Store::LocalStorage(s) => s.get(note_id).await,
+        }
+    }
+
+    async fn update(&self, note: &Note) -> Result<Note, ServiceError> {
+        match self {
+            Store::Firestore(s) => s.update(note).await,
+            Store::Http(s) => s.update(note).await,
+            Store::Memory(s) => s.update(note).await,
+            Store::LocalStorage(s) => s.update(note).await,
+        }
+    }
+
+    async fn delete(&self, note_id: &str) -> Result<(), ServiceError> {
+        match self {
+            Store::Firestore(s) => s.delete(note_id).await,
+            Store::Http(s) => s.delete(note_id).await,
+            Store::Memory(s) => s.delete(note_id).await,
+            Store::LocalStorage(s) => s.delete(note_id).await,
+        }
+    }
+
+    async fn list(&self, query: &NoteQuery) -> Result<Vec<Note>, ServiceError> {
+        match self {
+            Store::Firestore(s) => s.list(query).await,
+            Store::Http(s) => s.list(query).await,
+            Store::Memory(s) => s.list(query).await,
+            Store::LocalStorage(s) => s.list(query).await,
+        }
+    }
+
+    fn subscribe(&self, query: &NoteQuery, on_change: ChangeHandler) -> Result<Subscription, ServiceError> {
+        match self {
+            Store::Firestore(s) => s.subscribe(query, on_change),
+            Store::Http(s) => s.subscribe(query, on_change),
+            Store::Memory(s) => s.subscribe(query, on_change),
+            Store::LocalStorage(s) => s.subscribe(query, on_change),
+        }
+    }
+
+    fn join_collab(&self, note_id: &str, on_message: CollabHandler) -> Result<CollabChannel, ServiceError> {
+        match self {
+            Store::Firestore(s) => s.join_collab(note_id, on_message),
+            Store::Http(s) => s.join_collab(note_id, on_message),
+            Store::Memory(s) => s.join_collab(note_id, on_message),
+            Store::LocalStorage(s) => s.join_collab(note_id, on_message),
+        }
+    }
+}
+
+impl NotebookStore for Store {
+    async fn create_notebook(&self, notebook: &Notebook) -> Result<String, ServiceError> {
+        match self {
+            Store::Firestore(s) => s.create_notebook(notebook).await,
+            Store::Http(s) => s.create_notebook(notebook).await,
+            Store::Memory(s) => s.create_notebook(notebook).await,
+            Store::LocalStorage(s) => s.create_notebook(notebook).await,
+        }
+    }
+
+    async fn update_notebook(&self, notebook: &Notebook) -> Result<(), ServiceError> {
+        match self {
+            Store::Firestore(s) => s.update_notebook(notebook).await,
+            Store::Http(s) => s.update_notebook(notebook).await,
+            Store::Memory(s) => s.update_notebook(notebook).await,
+            Store::LocalStorage(s) => s.update_notebook(notebook).await,
+        }
+    }
+
+    async fn delete_notebook(&self, notebook_id: &str) -> Result<(), ServiceError> {
+        match self {
+            Store::Firestore(s) => s.delete_notebook(notebook_id).await,
+            Store::Http(s) => s.delete_notebook(notebook_id).await,
+            Store::Memory(s) => s.delete_notebook(notebook_id).await,
+            Store::LocalStorage(s) => s.delete_notebook(notebook_id).await,
+        }
+    }
+
+    async fn list_notebooks(&self, user_id: &str) -> Result<Vec<Notebook>, ServiceError> {
+        match self {
+            Store::Firestore(s) => s.list_notebooks(user_id).await,
+            Store::Http(s) => s.list_notebooks(user_id).await,
+            Store::Memory(s) => s.list_notebooks(user_id).await,
+            Store::LocalStorage(s) => s.list_notebooks(user_id).await,
+        }
+    }
+
+    fn subscribe_notebooks(&self, user_id: &str, on_change: NotebooksHandler) -> Result<Subscription, ServiceError> {
+        match self {
+            Store::Firestore(s) => s.subscribe_notebooks(user_id, on_change),
+            Store::Http(s) => s.subscribe_notebooks(user_id, on_change),
+            Store::Memory(s) => s.subscribe_notebooks(user_id, on_change),
+            Store::LocalStorage(s) => s.subscribe_notebooks(user_id, on_change),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notepad_models::FixedClock;
+
+    fn notebook(id: &str, parent_id: Option<&str>) -> (String, Notebook) {
+        let mut notebook = Notebook::new(id.to_string(), "u1".to_string(), parent_id.map(str::to_string), 0);
+        notebook.id = Some(id.to_string());
+        (id.to_string(), notebook)
+    }
+
+    fn note(id: &str, notebook_id: Option<&str>) -> (String, Note) {
+        let mut note = Note::new_with_clock(&FixedClock(0), id.to_string(), String::new(),
+            "u1".to_string(), "Arial".to_string(), "black".to_string(), "white".to_string(), None);
+        note.id = Some(id.to_string());
+        note.notebook_id = notebook_id.map(str::to_string);
+        (id.to_string(), note)
+    }
+
+    #[test]
+    fn removed_notebook_hands_its_contents_to_the_parent() {
+        let mut notebooks = BTreeMap::from([
+            notebook("raiz", None),
+            notebook("velho", Some("raiz")),
+            notebook("filho", Some("velho")),
+        ]);
+        let mut notes = BTreeMap::from([
+            note("dentro", Some("velho")),
+            note("no-filho", Some("filho")),
+        ]);
+
+        let moved = remove_notebook(&mut notebooks, &mut notes, "velho").unwrap();
+        assert_eq!(moved.len(), 1);
+        assert_eq!(moved[0].id.as_deref(), Some("dentro"));
+
+        assert!(!notebooks.contains_key("velho"));
+        assert_eq!(notebooks["filho"].parent_id.as_deref(), Some("raiz"));
+        assert_eq!(notes["dentro"].notebook_id.as_deref(), Some("raiz"));
+        assert_eq!(notes["dentro"].revision, 1);
+        assert_eq!(notes["no-filho"].notebook_id.as_deref(), Some("filho"));
+        assert_eq!(notes["no-filho"].revision, 0);
+    }
+
+    #[test]
+    fn removing_a_top_level_notebook_leaves_contents_without_notebook() {
+        let mut notebooks = BTreeMap::from([notebook("raiz", None), notebook("filho", Some("raiz"))]);
+        let mut notes = BTreeMap::from([note("dentro", Some("raiz"))]);
+
+        remove_notebook(&mut notebooks, &mut notes, "raiz").unwrap();
+        assert_eq!(notebooks["filho"].parent_id, None);
+        assert_eq!(notes["dentro"].notebook_id, None);
+    }
+
+    #[test]
+    fn removing_a_missing_notebook_changes_nothing() {
+        let mut notebooks = BTreeMap::from([notebook("raiz", None)]);
+        let mut notes = BTreeMap::from([note("dentro", Some("raiz"))]);
+
+        assert!(remove_notebook(&mut notebooks, &mut notes, "nada").is_none());
+        assert_eq!(notebooks.len(), 1);
+        assert_eq!(notes["dentro"].revision, 0);
+    }
+}
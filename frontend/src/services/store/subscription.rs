@@ -0,0 +1,165 @@
+// src/services/store/subscription.rs
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+use notepad_models::{normalize_email, Note, Notebook};
+use crate::services::error::ServiceError;
+use super::{NoteStore, NotebookStore};
+
+/// Alteração em uma nota observada por uma assinatura.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NoteChange {
+    Added(Note),
+    Modified(Note),
+    Removed(String),
+}
+
+/// Notas acompanhadas por uma listagem ou assinatura.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NoteQuery {
+    /// Notas do usuário (`user_id`).
+    Owner(String),
+    /// Notas compartilhadas com o e-mail, como editor ou leitor.
+    SharedWith(String),
+}
+
+impl NoteQuery {
+    pub fn shared_with(email: &str) -> Self {
+        NoteQuery::SharedWith(normalize_email(email))
+    }
+
+    pub fn matches(&self, note: &Note) -> bool {
+        match self {
+            NoteQuery::Owner(uid) => note.user_id == *uid,
+            NoteQuery::SharedWith(email) => note.editors.contains(email) || note.viewers.contains(email),
+        }
+    }
+}
+
+pub type ChangeHandler = Rc<dyn Fn(Result<Vec<NoteChange>, ServiceError>)>;
+
+/// Assinatura ativa; cancelada ao ser descartada (`drop`).
+pub struct Subscription {
+    cancel: Option<Box<dyn FnOnce()>>,
+}
+
+impl Subscription {
+    pub fn new(cancel: impl FnOnce() + 'static) -> Self {
+        Self { cancel: Some(Box::new(cancel)) }
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if let Some(cancel) = self.cancel.take() {
+            cancel();
+        }
+    }
+}
+
+/// Ouvintes em memória para backends sem notificação do servidor
+/// (memória, localStorage, HTTP): as alterações feitas nesta aba são
+/// repassadas a quem assinou uma consulta que inclui a nota.
+#[derive(Clone, Default)]
+pub struct Listeners {
+    entries: Rc<RefCell<Vec<(u64, NoteQuery, ChangeHandler)>>>,
+    next_id: Rc<Cell<u64>>,
+}
+
+impl Listeners {
+    /// Registra o ouvinte e entrega a lista atual como `Added`.
+    pub fn subscribe<S>(&self, store: S, query: &NoteQuery, on_change: ChangeHandler) -> Subscription
+    where
+        S: NoteStore + 'static,
+    {
+        let subscription = self.add(query, on_change.clone());
+
+        let query = query.clone();
+        spawn_local(async move {
+            let initial = store.list(&query).await
+                .map(|notes| notes.into_iter().map(NoteChange::Added).collect());
+            on_change(initial);
+        });
+
+        subscription
+    }
+
+    /// Registra o ouvinte sem entregar a lista atual.
+    pub fn add(&self, query: &NoteQuery, on_change: ChangeHandler) -> Subscription {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        self.entries.borrow_mut().push((id, query.clone(), on_change));
+
+        let entries = self.entries.clone();
+        Subscription::new(move || entries.borrow_mut().retain(|(i, _, _)| *i != id))
+    }
+
+    /// Uma nota alterada que deixou de atender a consulta (por exemplo, o
+    /// compartilhamento foi removido) chega a esse ouvinte como `Removed`.
+    pub fn notify(&self, change: NoteChange) {
+        // copia os handlers: um ouvinte pode cancelar a assinatura durante a chamada
+        let deliveries: Vec<(ChangeHandler, NoteChange)> = self.entries.borrow()
+            .iter()
+            .filter_map(|(_, query, handler)| {
+                let change = match &change {
+                    NoteChange::Added(n) if !query.matches(n) => return None,
+                    NoteChange::Modified(n) if !query.matches(n) => NoteChange::Removed(n.id.clone()?),
+                    other => other.clone(),
+                };
+                Some((handler.clone(), change))
+            })
+            .collect();
+        for (handler, change) in deliveries {
+            handler(Ok(vec![change]));
+        }
+    }
+}
+
+/// Recebe a lista inteira de cadernos do usuário a cada alteração.
+pub type NotebooksHandler = Rc<dyn Fn(Result<Vec<Notebook>, ServiceError>)>;
+
+/// Ouvintes de cadernos para os backends sem notificação do servidor. Como
+/// há poucos cadernos, cada alteração relê a lista e a entrega inteira.
+#[derive(Clone, Default)]
+pub struct NotebookListeners {
+    entries: Rc<RefCell<Vec<(u64, String, NotebooksHandler)>>>,
+    next_id: Rc<Cell<u64>>,
+}
+
+impl NotebookListeners {
+    pub fn subscribe<S>(&self, store: S, user_id: &str, on_change: NotebooksHandler) -> Subscription
+    where
+        S: NotebookStore + 'static,
+    {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        self.entries.borrow_mut().push((id, user_id.to_string(), on_change.clone()));
+
+        let user_id = user_id.to_string();
+        spawn_local(async move {
+            on_change(store.list_notebooks(&user_id).await);
+        });
+
+        let entries = self.entries.clone();
+        Subscription::new(move || entries.borrow_mut().retain(|(i, _, _)| *i != id))
+    }
+
+    /// Relê os cadernos de cada ouvinte depois de uma alteração.
+    pub fn notify<S>(&self, store: S)
+    where
+        S: NotebookStore + 'static,
+    {
+        let entries: Vec<(String, NotebooksHandler)> = self.entries.borrow()
+            .iter()
+            .map(|(_, uid, handler)| (uid.clone(), handler.clone()))
+            .collect();
+        if entries.is_empty() {
+            return;
+        }
+        spawn_local(async move {
+            for (user_id, handler) in entries {
+                handler(store.list_notebooks(&user_id).await);
+            }
+        });
+    }
+}
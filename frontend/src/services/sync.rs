@@ -0,0 +1,548 @@
+// src/services/sync.rs
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::rc::Rc;
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::Serializer;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::spawn_local;
+use notepad_models::Note;
+use crate::services::auth::AuthService;
+use crate::services::error::ServiceError;
+use crate::services::idb::{self, Idb};
+use crate::services::store::{self, ChangeHandler, Listeners, NoteChange, NoteQuery, NoteStore, Subscription};
+
+/// Prefixo dos ids de notas criadas offline, até receberem o id definitivo.
+const TEMP_PREFIX: &str = "offline-";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SyncStatus {
+    Synced,
+    Syncing,
+    /// Sem conexão, ou o backend não respondeu ao reenviar.
+    Offline,
+    /// Alguma operação pendente foi recusada pelo backend (e continua no
+    /// outbox) ou o outbox não pôde ser lido.
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SyncState {
+    pub status: SyncStatus,
+    pub pending: usize,
+    /// Operações pendentes que o backend recusou.
+    pub rejected: usize,
+}
+
+/// Operação guardada no outbox, uma por nota: edições seguidas da mesma nota
+/// substituem a anterior.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+enum OutboxOp {
+    Create { note: Note },
+    Update { note: Note },
+    Delete { note_id: String },
+}
+
+/// Resultado do envio de uma operação do outbox.
+#[derive(Debug, PartialEq)]
+enum Sent {
+    /// Nota criada no backend com esse id.
+    Created(String),
+    /// Nota gravada, com a nova revisão.
+    Updated(Box<Note>),
+    Done,
+}
+
+type StatusHandler = Rc<dyn Fn(SyncState)>;
+/// Recebe o id provisório e o id definitivo de uma nota criada offline.
+type RemapHandler = Rc<dyn Fn(&str, &str)>;
+
+#[derive(Default)]
+struct SyncInner {
+    pending: RefCell<BTreeSet<String>>,
+    syncing: Cell<bool>,
+    failed: Cell<bool>,
+    /// Operações recusadas pelo backend e o motivo; ficam no outbox até
+    /// serem aceitas num reenvio ou descartadas pelo usuário.
+    rejected: RefCell<BTreeMap<String, String>>,
+    /// Id definitivo das notas criadas offline que já foram enviadas.
+    remapped: RefCell<HashMap<String, String>>,
+    changes: Listeners,
+    status_listeners: RefCell<Vec<(u64, StatusHandler)>>,
+    remap_listeners: RefCell<Vec<(u64, RemapHandler)>>,
+    next_listener: Cell<u64>,
+}
+
+thread_local! {
+    static SYNC: Rc<SyncInner> = Rc::new(SyncInner::default());
+}
+
+fn inner() -> Rc<SyncInner> {
+    SYNC.with(Rc::clone)
+}
+
+fn change_id(change: &NoteChange) -> Option<&str> {
+    match change {
+        NoteChange::Added(n) | NoteChange::Modified(n) => n.id.as_deref(),
+        NoteChange::Removed(id) => Some(id),
+    }
+}
+
+/// Cache local (IndexedDB) e fila de envio para uso offline.
+pub struct SyncService;
+
+impl SyncService {
+    /// Carrega o outbox e reenvia as operações sempre que a conexão voltar.
+    pub fn start() {
+        if let Some(window) = web_sys::window() {
+            let on_online = Closure::<dyn FnMut()>::new(|| spawn_local(Self::replay()));
+            let on_offline = Closure::<dyn FnMut()>::new(Self::notify_status);
+            let _ = window.add_event_listener_with_callback("online", on_online.as_ref().unchecked_ref());
+            let _ = window.add_event_listener_with_callback("offline", on_offline.as_ref().unchecked_ref());
+            // os ouvintes valem enquanto a página estiver aberta
+            on_online.forget();
+            on_offline.forget();
+        }
+
+        spawn_local(async {
+            match Idb::entries(idb::OUTBOX).await {
+                Ok(entries) => {
+                    inner().pending.replace(entries.into_iter().map(|(key, _)| key).collect());
+                }
+                Err(e) => web_sys::console::error_1(&format!("Erro ao ler o outbox: {:?}", e).into()),
+            }
+            Self::replay().await;
+        });
+    }
+
+    pub fn is_online() -> bool {
+        web_sys::window().map(|w| w.navigator().on_line()).unwrap_or(true)
+    }
+
+    /// A nota tem uma edição ainda não enviada (ou foi criada offline).
+    pub fn is_pending(note_id: &str) -> bool {
+        note_id.starts_with(TEMP_PREFIX) || inner().pending.borrow().contains(note_id)
+    }
+
+    /// Id com que a nota está gravada no backend: o definitivo, se `note_id`
+    /// é o provisório de uma nota criada offline e já enviada.
+    pub fn resolve_id(note_id: &str) -> String {
+        inner().remapped.borrow().get(note_id).cloned().unwrap_or_else(|| note_id.to_string())
+    }
+
+    pub fn state() -> SyncState {
+        let inner = inner();
+        let pending = inner.pending.borrow().len();
+        let rejected = inner.rejected.borrow().len();
+        let status = if inner.syncing.get() {
+            SyncStatus::Syncing
+        } else if !Self::is_online() {
+            SyncStatus::Offline
+        } else if rejected > 0 {
+            SyncStatus::Error
+        } else if pending > 0 {
+            SyncStatus::Offline
+        } else if inner.failed.get() {
+            SyncStatus::Error
+        } else {
+            SyncStatus::Synced
+        };
+        SyncState { status, pending, rejected }
+    }
+
+    /// Motivos das operações pendentes recusadas pelo backend.
+    pub fn rejected() -> Vec<String> {
+        inner().rejected.borrow().values().cloned().collect()
+    }
+
+    pub fn subscribe_status(on_change: impl Fn(SyncState) + 'static) -> Subscription {
+        let inner = inner();
+        let id = inner.next_listener.get();
+        inner.next_listener.set(id + 1);
+        inner.status_listeners.borrow_mut().push((id, Rc::new(on_change)));
+
+        Subscription::new(move || inner.status_listeners.borrow_mut().retain(|(i, _)| *i != id))
+    }
+
+    /// Avisa quando uma nota criada offline recebe o id definitivo, para
+    /// quem a tem aberta passar a usá-lo.
+    pub fn subscribe_remap(on_remap: impl Fn(&str, &str) + 'static) -> Subscription {
+        let inner = inner();
+        let id = inner.next_listener.get();
+        inner.next_listener.set(id + 1);
+        inner.remap_listeners.borrow_mut().push((id, Rc::new(on_remap)));
+
+        Subscription::new(move || inner.remap_listeners.borrow_mut().retain(|(i, _)| *i != id))
+    }
+
+    fn notify_status() {
+        let state = Self::state();
+        let listeners: Vec<_> = inner().status_listeners.borrow().iter().map(|(_, l)| l.clone()).collect();
+        for listener in listeners {
+            listener(state);
+        }
+    }
+
+    /// Assinatura offline-first: entrega as notas do cache, depois as do
+    /// backend (gravando-as no cache) e as edições feitas offline nesta aba.
+    pub fn subscribe(query: &NoteQuery, on_change: ChangeHandler) -> Subscription {
+        let local = inner().changes.add(query, on_change.clone());
+        let got_remote = Rc::new(Cell::new(false));
+
+        {
+            let on_change = on_change.clone();
+            let got_remote = got_remote.clone();
+            let query = query.clone();
+            spawn_local(async move {
+                match Self::cached_notes(&query).await {
+                    Ok(notes) if !got_remote.get() && !notes.is_empty() => {
+                        on_change(Ok(notes.into_iter().map(NoteChange::Added).collect()));
+                    }
+                    Ok(_) => {}
+                    Err(e) => web_sys::console::error_1(&format!("Erro ao ler o cache: {:?}", e).into()),
+                }
+            });
+        }
+
+        let remote_handler: ChangeHandler = {
+            let on_change = on_change.clone();
+            let query = query.clone();
+            Rc::new(move |changes| {
+                let changes = match changes {
+                    Ok(changes) => changes,
+                    Err(e) => return on_change(Err(e)),
+                };
+                let first = !got_remote.replace(true);
+
+                // a versão local de uma nota pendente vale mais que a do servidor
+                let changes: Vec<NoteChange> = changes.into_iter()
+                    .filter(|c| change_id(c).is_none_or(|id| !Self::is_pending(id)))
+                    .collect();
+
+                let cached = changes.clone();
+                let on_change_stale = on_change.clone();
+                let query = query.clone();
+                spawn_local(async move {
+                    if first && Self::is_online() {
+                        Self::drop_stale(&query, &cached, on_change_stale).await;
+                    }
+                    Self::cache_changes(&cached).await;
+                });
+                on_change(Ok(changes));
+            })
+        };
+
+        let remote = store::current().subscribe(query, remote_handler)
+            .map_err(|e| on_change(Err(e)))
+            .ok();
+
+        Subscription::new(move || {
+            drop(remote);
+            drop(local);
+        })
+    }
+
+    /// Remove do cache notas que não vieram na primeira resposta do backend.
+    async fn drop_stale(query: &NoteQuery, live: &[NoteChange], on_change: ChangeHandler) {
+        let Ok(cached) = Self::cached_notes(query).await else { return };
+        let live: HashSet<&str> = live.iter().filter_map(change_id).collect();
+        let stale: Vec<NoteChange> = cached.into_iter()
+            .filter_map(|n| n.id)
+            .filter(|id| !live.contains(id.as_str()) && !Self::is_pending(id))
+            .map(NoteChange::Removed)
+            .collect();
+        if !stale.is_empty() {
+            Self::cache_changes(&stale).await;
+            on_change(Ok(stale));
+        }
+    }
+
+    async fn cached_notes(query: &NoteQuery) -> Result<Vec<Note>, ServiceError> {
+        let entries = Idb::entries(idb::NOTES).await?;
+        Ok(entries.into_iter()
+            .filter_map(|(_, value)| serde_wasm_bindgen::from_value::<Note>(value).ok())
+            .filter(|n| query.matches(n))
+            .collect())
+    }
+
+    async fn cache_changes(changes: &[NoteChange]) {
+        for change in changes {
+            let result = match change {
+                NoteChange::Added(note) | NoteChange::Modified(note) => Self::cache_put(note).await,
+                NoteChange::Removed(id) => Idb::delete(idb::NOTES, id).await,
+            };
+            if let Err(e) = result {
+                web_sys::console::error_1(&format!("Erro ao gravar o cache: {:?}", e).into());
+            }
+        }
+    }
+
+    async fn cache_put(note: &Note) -> Result<(), ServiceError> {
+        let Some(id) = note.id.as_deref() else { return Ok(()) };
+        let value = note.serialize(&Serializer::json_compatible())
+            .map_err(|e| ServiceError::Serialization(e.to_string()))?;
+        Idb::put(idb::NOTES, id, &value).await
+    }
+
+    pub async fn queue_create(note: &Note) -> Result<String, ServiceError> {
+        let now = js_sys::Date::now() as u64;
+        let rand = (js_sys::Math::random() * 1e9) as u64;
+        let temp_id = format!("{}{:x}-{:x}", TEMP_PREFIX, now, rand);
+
+        let mut note = note.clone();
+        note.id = Some(temp_id.clone());
+        Self::write_op(&temp_id, &OutboxOp::Create { note: note.clone() }).await?;
+        Self::cache_changes(&[NoteChange::Added(note.clone())]).await;
+        inner().changes.notify(NoteChange::Added(note));
+        Ok(temp_id)
+    }
+
+    pub async fn queue_update(note: &Note) -> Result<Note, ServiceError> {
+        let note_id = note.id.clone().ok_or(ServiceError::NotFound)?;
+        // nota criada offline ainda não existe no backend
+        let op = if note_id.starts_with(TEMP_PREFIX) {
+            OutboxOp::Create { note: note.clone() }
+        } else {
+            OutboxOp::Update { note: note.clone() }
+        };
+        Self::write_op(&note_id, &op).await?;
+        Self::cache_changes(&[NoteChange::Modified(note.clone())]).await;
+        inner().changes.notify(NoteChange::Modified(note.clone()));
+        Ok(note.clone())
+    }
+
+    pub async fn queue_delete(note_id: &str) -> Result<(), ServiceError> {
+        if note_id.starts_with(TEMP_PREFIX) {
+            Self::clear_op(note_id).await?;
+        } else {
+            Self::write_op(note_id, &OutboxOp::Delete { note_id: note_id.to_string() }).await?;
+        }
+        let removed = NoteChange::Removed(note_id.to_string());
+        Self::cache_changes(std::slice::from_ref(&removed)).await;
+        inner().changes.notify(removed);
+        Ok(())
+    }
+
+    async fn write_op(key: &str, op: &OutboxOp) -> Result<(), ServiceError> {
+        let value = op.serialize(&Serializer::json_compatible())
+            .map_err(|e| ServiceError::Serialization(e.to_string()))?;
+        Idb::put(idb::OUTBOX, key, &value).await?;
+        inner().pending.borrow_mut().insert(key.to_string());
+        Self::notify_status();
+        Ok(())
+    }
+
+    async fn clear_op(key: &str) -> Result<(), ServiceError> {
+        Idb::delete(idb::OUTBOX, key).await?;
+        inner().pending.borrow_mut().remove(key);
+        inner().rejected.borrow_mut().remove(key);
+        Self::notify_status();
+        Ok(())
+    }
+
+    /// Tira do outbox as operações recusadas e volta a mostrar a versão do
+    /// backend dessas notas.
+    pub async fn discard_rejected() {
+        let keys: Vec<String> = inner().rejected.borrow().keys().cloned().collect();
+        let store = store::current();
+        for key in keys {
+            if let Err(e) = Self::clear_op(&key).await {
+                web_sys::console::error_1(&format!("Erro ao limpar o outbox: {:?}", e).into());
+                continue;
+            }
+            let change = if key.starts_with(TEMP_PREFIX) {
+                NoteChange::Removed(key)
+            } else {
+                match store.get(&key).await {
+                    Ok(Some(note)) => NoteChange::Modified(note),
+                    Ok(None) => NoteChange::Removed(key),
+                    Err(_) => continue,
+                }
+            };
+            Self::cache_changes(std::slice::from_ref(&change)).await;
+            inner().changes.notify(change);
+        }
+    }
+
+    /// A nota criada offline com `temp_id` foi gravada no backend com o id
+    /// `server_id`. Uma edição feita enquanto a criação era enviada passa a
+    /// ser uma atualização da nota criada. Retorna se alguma foi enfileirada.
+    async fn created(temp_id: &str, sent: &Note, server_id: String) -> bool {
+        let mut note = sent.clone();
+        note.id = Some(server_id.clone());
+
+        let latest = Idb::get(idb::OUTBOX, temp_id).await.ok().flatten()
+            .and_then(|value| serde_wasm_bindgen::from_value::<OutboxOp>(value).ok());
+        let mut queued = false;
+        if let Some(OutboxOp::Create { note: edited }) = latest.filter(|op| match op {
+            OutboxOp::Create { note } => note != sent,
+            _ => false,
+        }) {
+            let edited = Note { id: Some(server_id.clone()), revision: note.revision, ..edited };
+            match Self::write_op(&server_id, &OutboxOp::Update { note: edited.clone() }).await {
+                Ok(()) => queued = true,
+                Err(e) => web_sys::console::error_1(&format!("Erro ao gravar o outbox: {:?}", e).into()),
+            }
+            note = edited;
+        }
+        let _ = Self::clear_op(temp_id).await;
+
+        let inner = inner();
+        inner.remapped.borrow_mut().insert(temp_id.to_string(), server_id.clone());
+        let listeners: Vec<_> = inner.remap_listeners.borrow().iter().map(|(_, l)| l.clone()).collect();
+        for listener in listeners {
+            listener(temp_id, &server_id);
+        }
+        let changes = [NoteChange::Removed(temp_id.to_string()), NoteChange::Added(note)];
+        Self::cache_changes(&changes).await;
+        for change in changes {
+            inner.changes.notify(change);
+        }
+        queued
+    }
+
+    /// Envia uma operação do outbox ao backend.
+    async fn send(store: &impl NoteStore, op: &OutboxOp) -> Result<Sent, ServiceError> {
+        match op {
+            OutboxOp::Create { note } => {
+                let mut note = note.clone();
+                note.id = None;
+                store.create(&note).await.map(Sent::Created)
+            }
+            OutboxOp::Update { note } => match store.update(note).await {
+                // outro dispositivo salvou enquanto estávamos offline:
+                // a versão local vira uma nova nota em vez de se perder
+                Err(ServiceError::Conflict) => {
+                    let mut copy = note.clone();
+                    copy.id = None;
+                    copy.revision = 0;
+                    copy.title = format!("{} (conflito)", note.title);
+                    // a cópia é de quem editou, e não é compartilhada
+                    if let Some(user) = AuthService::get_current_user() {
+                        copy.user_id = user.uid;
+                        copy.owner_email = user.email;
+                    }
+                    copy.editors.clear();
+                    copy.viewers.clear();
+                    copy.shared_with.clear();
+                    store.create(&copy).await.map(|_| Sent::Done)
+                }
+                other => other.map(|note| Sent::Updated(Box::new(note))),
+            },
+            OutboxOp::Delete { note_id } => match store.delete(note_id).await {
+                Err(ServiceError::NotFound) => Ok(Sent::Done),
+                other => other.map(|_| Sent::Done),
+            },
+        }
+    }
+
+    /// Envia o outbox ao backend; para no primeiro erro de rede. Operações
+    /// recusadas continuam no outbox e aparecem em `rejected`.
+    pub async fn replay() {
+        let inner = inner();
+        if inner.syncing.get() {
+            return;
+        }
+        inner.failed.set(false);
+        if !Self::is_online() || inner.pending.borrow().is_empty() {
+            Self::notify_status();
+            return;
+        }
+        inner.syncing.set(true);
+        Self::notify_status();
+
+        let entries = match Idb::entries(idb::OUTBOX).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                web_sys::console::error_1(&format!("Erro ao ler o outbox: {:?}", e).into());
+                inner.failed.set(true);
+                Vec::new()
+            }
+        };
+
+        let store = store::current();
+        let mut requeued = false;
+        for (key, value) in entries {
+            let op: OutboxOp = match serde_wasm_bindgen::from_value(value) {
+                Ok(op) => op,
+                Err(e) => {
+                    web_sys::console::error_1(&format!("Operação pendente inválida: {}", e).into());
+                    let _ = Self::clear_op(&key).await;
+                    continue;
+                }
+            };
+
+            let result = Self::send(&store, &op).await;
+
+            match (result, &op) {
+                (Ok(Sent::Created(server_id)), OutboxOp::Create { note }) => {
+                    requeued |= Self::created(&key, note, server_id).await;
+                }
+                // a assinatura ignora as alterações de notas pendentes, então
+                // a nova revisão é repassada daqui
+                (Ok(Sent::Updated(stored)), _) => {
+                    let change = NoteChange::Modified(*stored);
+                    Self::cache_changes(std::slice::from_ref(&change)).await;
+                    inner.changes.notify(change);
+                    let _ = Self::clear_op(&key).await;
+                }
+                (Ok(_), _) => {
+                    let _ = Self::clear_op(&key).await;
+                }
+                (Err(ServiceError::Network(_)), _) => break,
+                (Err(e), _) => {
+                    web_sys::console::error_1(&format!("Operação pendente recusada ({}): {}", key, e).into());
+                    inner.rejected.borrow_mut().insert(key, e.to_string());
+                }
+            }
+        }
+
+        inner.syncing.set(false);
+        Self::notify_status();
+        // edições feitas durante o envio de uma criação
+        if requeued {
+            spawn_local(Self::replay());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::Future;
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+    use notepad_models::FixedClock;
+    use crate::services::store::MemoryStore;
+
+    /// O `MemoryStore` responde sem esperar nada: uma chamada a `poll` basta.
+    fn now<F: Future>(future: F) -> F::Output {
+        match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("o MemoryStore não deveria esperar"),
+        }
+    }
+
+    #[test]
+    fn replayed_update_carries_the_new_revision() {
+        let store = MemoryStore::default();
+        let note = Note::new_with_clock(&FixedClock(0), "Título".to_string(), "offline".to_string(),
+            "u1".to_string(), "Arial".to_string(), "black".to_string(), "white".to_string(), None);
+        let id = now(store.create(&note)).unwrap();
+        let queued = now(store.get(&id)).unwrap().unwrap();
+
+        let Ok(Sent::Updated(stored)) = now(SyncService::send(&store, &OutboxOp::Update { note: queued.clone() })) else {
+            panic!("a atualização deveria ser gravada");
+        };
+        assert_eq!(stored.revision, queued.revision + 1);
+
+        // salvar de novo sobre a versão repassada não é um conflito
+        let mut again = *stored;
+        again.content = "online".to_string();
+        assert!(now(store.update(&again)).is_ok());
+        // sobre a versão enfileirada seria
+        assert_eq!(now(store.update(&queued)), Err(ServiceError::Conflict));
+    }
+}
@@ -0,0 +1,238 @@
+// src/text/crdt.rs
+use automerge::transaction::{CommitOptions, Transactable};
+use automerge::{ActorId, AutoCommit, Change, ChangeHash, Cursor, CursorPosition, ObjId, ObjType, ReadDoc, ScalarValue, TextEncoding, Value, ROOT};
+use notepad_models::CollabChange;
+use crate::services::error::ServiceError;
+
+/// Conteúdo de uma nota como documento automerge (CRDT): edições feitas ao
+/// mesmo tempo em clientes diferentes convergem para o mesmo texto.
+///
+/// As posições são em unidades UTF-16, as mesmas de `selectionStart` e
+/// `selectionEnd` de um `<textarea>`.
+pub struct CollabDoc {
+    doc: AutoCommit,
+}
+
+impl CollabDoc {
+    /// Documento vazio; o texto aparece quando a change inicial é aplicada.
+    pub fn new() -> Self {
+        let mut doc = AutoCommit::new_with_encoding(TextEncoding::Utf16CodeUnit);
+        doc.set_actor(ActorId::random());
+        Self { doc }
+    }
+
+    /// Documento inicial de uma nota que ainda não tem changes.
+    ///
+    /// A change inicial é determinística (autor derivado do id, data zero):
+    /// dois clientes que criam o documento ao mesmo tempo a partir do mesmo
+    /// conteúdo geram a mesma change, em vez de duplicar o texto.
+    pub fn seed(note_id: &str, content: &str, revision: u64) -> Result<(Self, Vec<CollabChange>), ServiceError> {
+        let mut doc = AutoCommit::new_with_encoding(TextEncoding::Utf16CodeUnit)
+            .with_actor(ActorId::from(note_id.as_bytes()));
+        let text = doc.put_object(ROOT, "content", ObjType::Text).map_err(backend_error)?;
+        doc.splice_text(&text, 0, 0, content).map_err(backend_error)?;
+        doc.put(ROOT, "revision", revision).map_err(backend_error)?;
+        doc.commit_with(CommitOptions::default().with_time(0));
+        doc.set_actor(ActorId::random());
+
+        let mut seeded = Self { doc };
+        let changes = seeded.all_changes();
+        Ok((seeded, changes))
+    }
+
+    /// Se a change inicial já foi recebida.
+    pub fn is_ready(&self) -> bool {
+        self.text_obj().is_some()
+    }
+
+    fn text_obj(&self) -> Option<ObjId> {
+        match self.doc.get(ROOT, "content") {
+            Ok(Some((Value::Object(ObjType::Text), id))) => Some(id),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.text_obj()
+            .and_then(|obj| self.doc.text(obj).ok())
+            .unwrap_or_default()
+    }
+
+    /// Última revisão da nota gravada por algum participante.
+    pub fn revision(&self) -> u64 {
+        match self.doc.get(ROOT, "revision") {
+            Ok(Some((Value::Scalar(v), _))) => match v.as_ref() {
+                ScalarValue::Uint(n) => *n,
+                ScalarValue::Int(n) => (*n).max(0) as u64,
+                _ => 0,
+            },
+            _ => 0,
+        }
+    }
+
+    /// Aplica changes recebidas; retorna `true` se o texto mudou.
+    /// Changes repetidas são ignoradas e as que chegam antes das suas
+    /// dependências ficam guardadas até elas chegarem.
+    pub fn apply(&mut self, changes: &[CollabChange]) -> Result<bool, ServiceError> {
+        let before = self.text();
+        let decoded = changes.iter()
+            .map(|c| Change::from_bytes(c.data.clone())
+                .map_err(|e| ServiceError::Serialization(format!("change {} inválida: {}", c.hash, e))))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.doc.apply_changes(decoded).map_err(backend_error)?;
+        Ok(self.text() != before)
+    }
+
+    /// Substitui o texto pelo novo valor (como uma sequência mínima de
+    /// inserções e remoções) e retorna as changes a enviar.
+    pub fn set_text(&mut self, text: &str) -> Vec<CollabChange> {
+        match self.text_obj() {
+            Some(obj) if self.text() != text => {
+                let heads = self.doc.get_heads();
+                let _ = self.doc.update_text(&obj, text);
+                self.changes_since(&heads)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn set_revision(&mut self, revision: u64) -> Vec<CollabChange> {
+        if self.revision() >= revision {
+            return Vec::new();
+        }
+        let heads = self.doc.get_heads();
+        let _ = self.doc.put(ROOT, "revision", revision);
+        self.changes_since(&heads)
+    }
+
+    /// Fecha a transação aberta e retorna as changes feitas depois de `heads`.
+    /// (`get_heads` também fecha a transação, por isso é lido antes da edição.)
+    fn changes_since(&mut self, heads: &[ChangeHash]) -> Vec<CollabChange> {
+        self.doc.commit();
+        self.doc.get_changes(heads).into_iter().map(to_collab).collect()
+    }
+
+    /// Todas as changes do documento, para reenviar após uma reconexão.
+    pub fn all_changes(&mut self) -> Vec<CollabChange> {
+        self.doc.get_changes(&[]).into_iter().map(to_collab).collect()
+    }
+
+    /// Cursor estável para a posição `index` do texto.
+    pub fn cursor(&self, index: usize) -> Option<String> {
+        let obj = self.text_obj()?;
+        // depois do último caractere não há um caractere para acompanhar
+        let position = if index >= self.doc.length(&obj) {
+            CursorPosition::End
+        } else {
+            CursorPosition::Index(index)
+        };
+        self.doc.get_cursor(obj, position, None).ok().map(|c| c.to_string())
+    }
+
+    /// Posição atual de um cursor criado por `cursor`.
+    pub fn cursor_index(&self, cursor: &str) -> Option<usize> {
+        let obj = self.text_obj()?;
+        let cursor = Cursor::try_from(cursor).ok()?;
+        self.doc.get_cursor_position(obj, &cursor, None).ok()
+    }
+}
+
+fn backend_error(e: automerge::AutomergeError) -> ServiceError {
+    ServiceError::Backend(format!("automerge: {}", e))
+}
+
+fn to_collab(change: &Change) -> CollabChange {
+    CollabChange {
+        hash: change.hash().to_string(),
+        data: change.raw_bytes().to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Dois clientes com a mesma change inicial, como depois de entrar no canal.
+    fn two_clients(content: &str) -> (CollabDoc, CollabDoc) {
+        let (a, seed) = CollabDoc::seed("nota-1", content, 0).unwrap();
+        let mut b = CollabDoc::new();
+        assert!(!b.is_ready());
+        assert!(b.apply(&seed).unwrap());
+        assert!(b.is_ready());
+        (a, b)
+    }
+
+    #[test]
+    fn concurrent_edits_converge() {
+        let (mut a, mut b) = two_clients("meio\n");
+        let from_a = a.set_text("início\nmeio\n");
+        let from_b = b.set_text("meio\nfim\n");
+
+        assert!(a.apply(&from_b).unwrap());
+        assert!(b.apply(&from_a).unwrap());
+        assert_eq!(a.text(), "início\nmeio\nfim\n");
+        assert_eq!(a.text(), b.text());
+    }
+
+    #[test]
+    fn concurrent_edits_at_same_place_keep_both() {
+        let (mut a, mut b) = two_clients("ab");
+        let from_a = a.set_text("aXb");
+        let from_b = b.set_text("aYb");
+        a.apply(&from_b).unwrap();
+        b.apply(&from_a).unwrap();
+        assert_eq!(a.text(), b.text());
+        assert!(a.text().contains('X') && a.text().contains('Y'));
+    }
+
+    #[test]
+    fn repeated_and_out_of_order_changes() {
+        let (mut a, mut b) = two_clients("um");
+        let first = a.set_text("um dois");
+        let second = a.set_text("um dois três");
+        // a segunda chega antes da sua dependência e espera por ela
+        assert!(!b.apply(&second).unwrap());
+        assert!(b.apply(&first).unwrap());
+        assert_eq!(b.text(), "um dois três");
+        assert!(!b.apply(&first).unwrap());
+    }
+
+    #[test]
+    fn simultaneous_seeds_do_not_duplicate_text() {
+        let (mut a, seed_a) = CollabDoc::seed("nota-1", "olá", 3).unwrap();
+        let (mut b, seed_b) = CollabDoc::seed("nota-1", "olá", 3).unwrap();
+        assert_eq!(seed_a, seed_b);
+        a.apply(&seed_b).unwrap();
+        b.apply(&seed_a).unwrap();
+        assert_eq!(a.text(), "olá");
+        assert_eq!(b.text(), "olá");
+        assert_eq!(a.revision(), 3);
+    }
+
+    #[test]
+    fn revision_only_moves_forward() {
+        let (mut a, mut b) = two_clients("x");
+        let changes = a.set_revision(2);
+        assert!(a.set_revision(1).is_empty());
+        b.apply(&changes).unwrap();
+        assert_eq!(b.revision(), 2);
+    }
+
+    #[test]
+    fn cursor_follows_remote_insertions_in_utf16() {
+        let (mut a, mut b) = two_clients("ação fim");
+        // antes de "fim": "ação " tem 5 unidades UTF-16
+        let cursor = b.cursor(5).unwrap();
+        let changes = a.set_text("😀 ação fim");
+        b.apply(&changes).unwrap();
+        // o emoji ocupa duas unidades, mais o espaço
+        assert_eq!(b.cursor_index(&cursor), Some(8));
+    }
+
+    #[test]
+    fn invalid_change_is_a_serialization_error() {
+        let mut doc = CollabDoc::new();
+        let bad = CollabChange { hash: "abc".to_string(), data: vec![1, 2, 3] };
+        assert!(matches!(doc.apply(&[bad]), Err(ServiceError::Serialization(_))));
+    }
+}
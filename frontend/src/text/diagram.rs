@@ -0,0 +1,471 @@
+// src/text/diagram.rs
+use std::collections::HashMap;
+use crate::text::markdown::escape_html;
+
+/// Linguagens de bloco cercado desenhadas como diagrama.
+pub const LANGUAGES: &[&str] = &["mermaid", "flowchart"];
+
+/// Elementos SVG gerados (liberados na sanitização do Markdown).
+pub const TAGS: &[&str] = &["svg", "g", "rect", "circle", "polygon", "line", "text"];
+/// Atributos dos elementos SVG gerados.
+pub const ATTRIBUTES: &[&str] = &[
+    "xmlns", "viewBox", "width", "height", "x", "y", "x1", "y1", "x2", "y2", "cx", "cy", "r", "rx",
+    "points", "fill", "stroke", "stroke-width", "stroke-dasharray", "text-anchor", "dominant-baseline",
+    "font-size", "font-family",
+];
+
+const NODE_HEIGHT: f64 = 40.0;
+const CHAR_WIDTH: f64 = 8.0;
+const NODE_PADDING: f64 = 24.0;
+const MIN_NODE_WIDTH: f64 = 60.0;
+/// Espaço entre camadas e entre nós da mesma camada.
+const RANK_GAP: f64 = 60.0;
+const NODE_GAP: f64 = 30.0;
+const MARGIN: f64 = 10.0;
+const ARROW_SIZE: f64 = 8.0;
+
+const STROKE: &str = "#4a5568";
+const NODE_FILL: &str = "#edf2f7";
+const FONT: &str = "sans-serif";
+
+pub fn is_diagram(language: &str) -> bool {
+    LANGUAGES.contains(&language.trim().to_ascii_lowercase().as_str())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    TopDown,
+    BottomUp,
+    LeftRight,
+    RightLeft,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    Rect,
+    Round,
+    Circle,
+    Diamond,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stroke {
+    Solid,
+    Dotted,
+    Thick,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    label: String,
+    shape: Shape,
+}
+
+#[derive(Debug, Clone)]
+struct Edge {
+    from: usize,
+    to: usize,
+    label: Option<String>,
+    arrow: bool,
+    stroke: Stroke,
+}
+
+/// Fluxograma no formato do Mermaid (`graph TD`, `A[Início] --> B{Ok?}`,
+/// `B -->|Sim| C`...).
+#[derive(Debug, Default)]
+struct Flowchart {
+    direction: Option<Direction>,
+    ids: HashMap<String, usize>,
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+}
+
+/// Desenha o diagrama em SVG; erros de sintaxe viram um aviso seguido do
+/// código original.
+pub fn to_svg(src: &str) -> String {
+    match parse(src) {
+        Ok(chart) if !chart.nodes.is_empty() => render(&chart),
+        Ok(_) => error("diagrama vazio", src),
+        Err(e) => error(&e, src),
+    }
+}
+
+fn error(message: &str, src: &str) -> String {
+    format!(
+        "<blockquote><p>⚠️ Diagrama: {}</p></blockquote><pre><code>{}</code></pre>\n",
+        escape_html(message),
+        escape_html(src)
+    )
+}
+
+fn parse(src: &str) -> Result<Flowchart, String> {
+    let mut chart = Flowchart::default();
+    let statements = src.lines()
+        .flat_map(|line| line.split(';'))
+        .map(str::trim)
+        .filter(|s| !s.is_empty() && !s.starts_with("%%"));
+    for (i, statement) in statements.enumerate() {
+        if i == 0 {
+            let mut words = statement.split_whitespace();
+            if !matches!(words.next(), Some("graph" | "flowchart")) {
+                return Err("só fluxogramas (graph ou flowchart) são suportados".to_string());
+            }
+            chart.direction = Some(match words.next().unwrap_or("TD") {
+                "TD" | "TB" => Direction::TopDown,
+                "BT" => Direction::BottomUp,
+                "LR" => Direction::LeftRight,
+                "RL" => Direction::RightLeft,
+                other => return Err(format!("direção desconhecida: {}", other)),
+            });
+            continue;
+        }
+        let keyword = statement.split_whitespace().next().unwrap_or_default();
+        // estilos e subgrafos não mudam o desenho
+        if matches!(keyword, "classDef" | "class" | "style" | "linkStyle" | "subgraph" | "end" | "click") {
+            continue;
+        }
+        chart.statement(statement)?;
+    }
+    Ok(chart)
+}
+
+impl Flowchart {
+    /// `A --> B --> C`: nós ligados em sequência.
+    fn statement(&mut self, statement: &str) -> Result<(), String> {
+        let mut rest = statement;
+        let mut previous = self.node(&mut rest)?;
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                return Ok(());
+            }
+            let (label, arrow, stroke) = edge(&mut rest)
+                .ok_or_else(|| format!("ligação inválida perto de \"{}\"", rest))?;
+            rest = rest.trim_start();
+            let next = self.node(&mut rest)?;
+            self.edges.push(Edge { from: previous, to: next, label, arrow, stroke });
+            previous = next;
+        }
+    }
+
+    /// Nó com forma opcional: `id`, `id[texto]`, `id(texto)`, `id((texto))`, `id{texto}`.
+    fn node(&mut self, rest: &mut &str) -> Result<usize, String> {
+        let len = rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len());
+        if len == 0 {
+            return Err(format!("nó esperado perto de \"{}\"", rest));
+        }
+        let id = rest[..len].to_string();
+        *rest = &rest[len..];
+        let shape = [("((", "))", Shape::Circle), ("(", ")", Shape::Round), ("[", "]", Shape::Rect), ("{", "}", Shape::Diamond)]
+            .into_iter()
+            .find(|(open, _, _)| rest.starts_with(open));
+        let defined = match shape {
+            Some((open, close, shape)) => {
+                let end = rest[open.len()..].find(close)
+                    .ok_or_else(|| format!("\"{}\" sem \"{}\"", open, close))?;
+                let label = rest[open.len()..open.len() + end].trim().trim_matches('"').to_string();
+                *rest = &rest[open.len() + end + close.len()..];
+                Some(Node { label, shape })
+            }
+            None => None,
+        };
+        let index = match self.ids.get(&id) {
+            Some(&index) => index,
+            None => {
+                self.nodes.push(Node { label: id.clone(), shape: Shape::Rect });
+                self.ids.insert(id, self.nodes.len() - 1);
+                self.nodes.len() - 1
+            }
+        };
+        // a forma pode ser dada em qualquer menção ao nó
+        if let Some(node) = defined {
+            self.nodes[index] = node;
+        }
+        Ok(index)
+    }
+}
+
+/// Ligação no começo de `rest`: `-->`, `---`, `-.->`, `==>`, com texto em
+/// `-->|texto|` ou `-- texto -->`.
+fn edge(rest: &mut &str) -> Option<(Option<String>, bool, Stroke)> {
+    const OPERATORS: [(&str, bool, Stroke); 6] = [
+        ("-.->", true, Stroke::Dotted),
+        ("-.-", false, Stroke::Dotted),
+        ("==>", true, Stroke::Thick),
+        ("===", false, Stroke::Thick),
+        ("-->", true, Stroke::Solid),
+        ("---", false, Stroke::Solid),
+    ];
+    let mut label = None;
+    let found = OPERATORS.iter().find(|(op, _, _)| rest.starts_with(op));
+    let (arrow, stroke) = match found {
+        Some(&(op, arrow, stroke)) => {
+            *rest = &rest[op.len()..];
+            (arrow, stroke)
+        }
+        None => {
+            // `-- texto -->`: o texto vai até o operador de fechamento
+            let open = ["--", "==", "-."].into_iter().find(|o| rest.starts_with(o))?;
+            let body = &rest[open.len()..];
+            let (end, &(op, arrow, stroke)) = OPERATORS.iter()
+                .filter_map(|o| body.find(o.0).map(|i| (i, o)))
+                .min_by_key(|(i, _)| *i)?;
+            label = Some(body[..end].trim().to_string());
+            *rest = &body[end + op.len()..];
+            (arrow, stroke)
+        }
+    };
+    if let Some(body) = rest.strip_prefix('|') {
+        let end = body.find('|')?;
+        label = Some(body[..end].trim().to_string());
+        *rest = &body[end + 1..];
+    }
+    Some((label.filter(|l| !l.is_empty()), arrow, stroke))
+}
+
+/// Camada de cada nó: a distância do início pelo caminho mais longo, sem
+/// contar as ligações que voltam (ciclos).
+fn ranks(chart: &Flowchart) -> Vec<usize> {
+    let n = chart.nodes.len();
+    let mut outgoing = vec![Vec::new(); n];
+    for edge in &chart.edges {
+        outgoing[edge.from].push(edge.to);
+    }
+    // ordem topológica por busca em profundidade, ignorando as ligações de volta
+    let (mut state, mut order) = (vec![0u8; n], Vec::with_capacity(n));
+    let mut forward = vec![Vec::new(); n];
+    for start in 0..n {
+        if state[start] != 0 {
+            continue;
+        }
+        let mut stack = vec![(start, 0)];
+        state[start] = 1;
+        while let Some((node, next)) = stack.pop() {
+            if let Some(&child) = outgoing[node].get(next) {
+                stack.push((node, next + 1));
+                match state[child] {
+                    0 => {
+                        forward[node].push(child);
+                        state[child] = 1;
+                        stack.push((child, 0));
+                    }
+                    2 => forward[node].push(child),
+                    _ => {} // volta para um nó na pilha: ciclo
+                }
+            } else {
+                state[node] = 2;
+                order.push(node);
+            }
+        }
+    }
+    let mut rank = vec![0; n];
+    for &node in order.iter().rev() {
+        for &child in &forward[node] {
+            rank[child] = rank[child].max(rank[node] + 1);
+        }
+    }
+    rank
+}
+
+fn node_size(node: &Node) -> (f64, f64) {
+    let width = (node.label.chars().count() as f64 * CHAR_WIDTH + NODE_PADDING).max(MIN_NODE_WIDTH);
+    match node.shape {
+        Shape::Circle => (width, width),
+        Shape::Diamond => (width * 1.4, NODE_HEIGHT * 1.5),
+        _ => (width, NODE_HEIGHT),
+    }
+}
+
+/// Centro de cada nó e o tamanho total do desenho.
+fn layout(chart: &Flowchart) -> (Vec<(f64, f64)>, f64, f64) {
+    let direction = chart.direction.unwrap_or(Direction::TopDown);
+    let horizontal = matches!(direction, Direction::LeftRight | Direction::RightLeft);
+    let rank = ranks(chart);
+    let layers = rank.iter().max().map_or(0, |r| r + 1);
+    let mut members: Vec<Vec<usize>> = vec![Vec::new(); layers];
+    for (node, &r) in rank.iter().enumerate() {
+        members[r].push(node);
+    }
+    // ordena cada camada pela posição média de quem aponta para o nó
+    for r in 1..layers {
+        let previous: HashMap<usize, usize> = members[r - 1].iter().enumerate().map(|(i, &n)| (n, i)).collect();
+        let weight = |node: usize| {
+            let parents: Vec<f64> = chart.edges.iter()
+                .filter(|e| e.to == node)
+                .filter_map(|e| previous.get(&e.from).map(|&i| i as f64))
+                .collect();
+            if parents.is_empty() { f64::MAX } else { parents.iter().sum::<f64>() / parents.len() as f64 }
+        };
+        members[r].sort_by(|&a, &b| weight(a).total_cmp(&weight(b)));
+    }
+
+    let sizes: Vec<(f64, f64)> = chart.nodes.iter().map(node_size).collect();
+    // (ao longo da camada, na direção do fluxo)
+    let along = |i: usize| if horizontal { sizes[i].1 } else { sizes[i].0 };
+    let across = |i: usize| if horizontal { sizes[i].0 } else { sizes[i].1 };
+    let layer_lengths: Vec<f64> = members.iter()
+        .map(|m| m.iter().map(|&i| along(i)).sum::<f64>() + NODE_GAP * m.len().saturating_sub(1) as f64)
+        .collect();
+    let layer_depths: Vec<f64> = members.iter()
+        .map(|m| m.iter().map(|&i| across(i)).fold(0.0, f64::max))
+        .collect();
+    let length = layer_lengths.iter().copied().fold(0.0, f64::max);
+    let depth = layer_depths.iter().sum::<f64>() + RANK_GAP * layers.saturating_sub(1) as f64;
+
+    let mut centers = vec![(0.0, 0.0); chart.nodes.len()];
+    let mut offset = 0.0;
+    for (r, layer) in members.iter().enumerate() {
+        let mut position = (length - layer_lengths[r]) / 2.0;
+        for &node in layer {
+            let a = position + along(node) / 2.0;
+            let mut d = offset + layer_depths[r] / 2.0;
+            if matches!(direction, Direction::BottomUp | Direction::RightLeft) {
+                d = depth - d;
+            }
+            centers[node] = if horizontal { (d, a) } else { (a, d) };
+            position += along(node) + NODE_GAP;
+        }
+        offset += layer_depths[r] + RANK_GAP;
+    }
+    let (width, height) = if horizontal { (depth, length) } else { (length, depth) };
+    let centers = centers.into_iter().map(|(x, y)| (x + MARGIN, y + MARGIN)).collect();
+    (centers, width + 2.0 * MARGIN, height + 2.0 * MARGIN)
+}
+
+/// Ponto da borda do nó na direção de `toward` (a caixa do nó serve para
+/// todas as formas).
+fn boundary(center: (f64, f64), size: (f64, f64), toward: (f64, f64)) -> (f64, f64) {
+    let (dx, dy) = (toward.0 - center.0, toward.1 - center.1);
+    if dx == 0.0 && dy == 0.0 {
+        return center;
+    }
+    let scale = ((size.0 / 2.0) / dx.abs()).min((size.1 / 2.0) / dy.abs());
+    (center.0 + dx * scale, center.1 + dy * scale)
+}
+
+fn render(chart: &Flowchart) -> String {
+    let (centers, width, height) = layout(chart);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {w:.0} {h:.0}\" width=\"{w:.0}\" height=\"{h:.0}\" font-family=\"{f}\" font-size=\"14\">",
+        w = width, h = height, f = FONT
+    );
+    for edge in &chart.edges {
+        let (from, to) = (centers[edge.from], centers[edge.to]);
+        let start = boundary(from, node_size(&chart.nodes[edge.from]), to);
+        let end = boundary(to, node_size(&chart.nodes[edge.to]), from);
+        let dash = if edge.stroke == Stroke::Dotted { " stroke-dasharray=\"4 4\"" } else { "" };
+        let stroke_width = if edge.stroke == Stroke::Thick { 3 } else { 1 };
+        svg.push_str(&format!(
+            "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{}\" stroke-width=\"{}\"{}></line>",
+            start.0, start.1, end.0, end.1, STROKE, stroke_width, dash
+        ));
+        if edge.arrow {
+            let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+            let len = (dx * dx + dy * dy).sqrt().max(1.0);
+            let (ux, uy) = (dx / len, dy / len);
+            let base = (end.0 - ux * ARROW_SIZE, end.1 - uy * ARROW_SIZE);
+            let half = ARROW_SIZE / 2.0;
+            svg.push_str(&format!(
+                "<polygon points=\"{:.1},{:.1} {:.1},{:.1} {:.1},{:.1}\" fill=\"{}\"></polygon>",
+                end.0, end.1,
+                base.0 - uy * half, base.1 + ux * half,
+                base.0 + uy * half, base.1 - ux * half,
+                STROKE
+            ));
+        }
+        if let Some(label) = &edge.label {
+            let (mx, my) = ((start.0 + end.0) / 2.0, (start.1 + end.1) / 2.0);
+            let w = label.chars().count() as f64 * CHAR_WIDTH * 0.85 + 8.0;
+            svg.push_str(&format!(
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"18\" fill=\"white\"></rect>\
+                 <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" dominant-baseline=\"central\" font-size=\"12\">{}</text>",
+                mx - w / 2.0, my - 9.0, w, mx, my, escape_html(label)
+            ));
+        }
+    }
+    for (node, &(x, y)) in chart.nodes.iter().zip(&centers) {
+        let (w, h) = node_size(node);
+        let shape = match node.shape {
+            Shape::Rect | Shape::Round => format!(
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"{}\" fill=\"{}\" stroke=\"{}\"></rect>",
+                x - w / 2.0, y - h / 2.0, w, h, if node.shape == Shape::Round { 12 } else { 2 }, NODE_FILL, STROKE
+            ),
+            Shape::Circle => format!(
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"{}\" stroke=\"{}\"></circle>",
+                x, y, w / 2.0, NODE_FILL, STROKE
+            ),
+            Shape::Diamond => format!(
+                "<polygon points=\"{:.1},{:.1} {:.1},{:.1} {:.1},{:.1} {:.1},{:.1}\" fill=\"{}\" stroke=\"{}\"></polygon>",
+                x, y - h / 2.0, x + w / 2.0, y, x, y + h / 2.0, x - w / 2.0, y, NODE_FILL, STROKE
+            ),
+        };
+        svg.push_str(&format!(
+            "<g>{}<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text></g>",
+            shape, x, y, escape_html(&node.label)
+        ));
+    }
+    svg.push_str("</svg>");
+    format!("<p>{}</p>\n", svg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_error(html: &str) -> bool {
+        html.starts_with("<blockquote>")
+    }
+
+    #[test]
+    fn draws_nodes_and_edges() {
+        let svg = to_svg("graph LR\nA[Início] -->|sim| B((Fim))\nB -.-> A");
+        assert!(svg.contains("<svg"));
+        assert!(svg.contains(">Início</text>"));
+        assert!(svg.contains("<circle"));
+        assert!(svg.contains("stroke-dasharray"));
+        assert!(svg.contains(">sim</text>"));
+    }
+
+    #[test]
+    fn escapes_labels() {
+        let svg = to_svg("graph TD\nA[<b> & \"x\" ok] -->|a<b| B{1 & 2}");
+        assert!(!is_error(&svg));
+        assert!(svg.contains(">&lt;b&gt; &amp; &quot;x&quot; ok</text>"));
+        assert!(svg.contains(">a&lt;b</text>"));
+        assert!(svg.contains(">1 &amp; 2</text>"));
+        assert!(!svg.contains("<b>"));
+    }
+
+    #[test]
+    fn empty_chart_is_an_error() {
+        assert!(to_svg("").contains("diagrama vazio"));
+        assert!(to_svg("graph TD\n%% só comentário").contains("diagrama vazio"));
+    }
+
+    #[test]
+    fn malformed_charts_show_the_source_escaped() {
+        let html = to_svg("sequenceDiagram\nA->>B: <oi>");
+        assert!(html.contains("só fluxogramas"));
+        assert!(html.contains("A-&gt;&gt;B: &lt;oi&gt;"));
+
+        assert!(to_svg("graph <X>").contains("direção desconhecida: &lt;X&gt;"));
+        assert!(to_svg("graph TD\nA[sem fim").contains("&quot;[&quot; sem &quot;]&quot;"));
+        assert!(to_svg("graph TD\nA -> B").contains("ligação inválida perto de &quot;-&gt; B&quot;"));
+        assert!(to_svg("graph TD\n--> B").contains("nó esperado"));
+        assert!(to_svg("graph TD\nA -->|sem fim B").contains("ligação inválida"));
+    }
+
+    #[test]
+    fn cycles_are_drawn() {
+        let svg = to_svg("flowchart TD; A --> B; B --> C; C --> A");
+        assert!(!is_error(&svg));
+        assert_eq!(svg.matches("<polygon").count(), 3);
+    }
+
+    #[test]
+    fn recognizes_languages() {
+        assert!(is_diagram(" Mermaid "));
+        assert!(is_diagram("flowchart"));
+        assert!(!is_diagram("rust"));
+    }
+}
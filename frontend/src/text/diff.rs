@@ -0,0 +1,173 @@
+// src/text/diff.rs
+use similar::udiff::UnifiedHunkHeader;
+use similar::{ChangeTag, TextDiff};
+
+/// Se a linha existe nas duas versões, só na antiga ou só na nova.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffTag {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Trecho de uma linha; `emphasized` marca as palavras que mudaram dentro
+/// de uma linha alterada.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffSpan {
+    pub text: String,
+    pub emphasized: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffLine {
+    pub tag: DiffTag,
+    /// Números das linhas (a partir de 1) na versão antiga e na nova.
+    pub old_number: Option<usize>,
+    pub new_number: Option<usize>,
+    pub spans: Vec<DiffSpan>,
+}
+
+/// Alterações próximas com as linhas de contexto em volta, como um bloco
+/// `@@ -a,b +c,d @@` do diff unificado.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffHunk {
+    pub header: String,
+    pub lines: Vec<DiffLine>,
+}
+
+/// Diff unificado, linha a linha, de `old` para `new`, com `context` linhas
+/// iguais em volta de cada alteração. Linhas alteradas trazem as palavras
+/// que mudaram destacadas. Textos iguais não geram blocos.
+pub fn unified_diff(old: &str, new: &str, context: usize) -> Vec<DiffHunk> {
+    let diff = TextDiff::from_lines(old, new);
+    diff.grouped_ops(context)
+        .into_iter()
+        .map(|ops| {
+            let header = UnifiedHunkHeader::new(&ops).to_string();
+            let lines = ops.iter()
+                .flat_map(|op| diff.iter_inline_changes(op))
+                .map(|change| DiffLine {
+                    tag: match change.tag() {
+                        ChangeTag::Equal => DiffTag::Equal,
+                        ChangeTag::Delete => DiffTag::Delete,
+                        ChangeTag::Insert => DiffTag::Insert,
+                    },
+                    old_number: change.old_index().map(|i| i + 1),
+                    new_number: change.new_index().map(|i| i + 1),
+                    spans: change.iter_strings_lossy()
+                        .map(|(emphasized, text)| DiffSpan {
+                            text: text.trim_end_matches(['\n', '\r']).to_string(),
+                            emphasized,
+                        })
+                        .filter(|span| !span.text.is_empty())
+                        .collect(),
+                })
+                .collect();
+            DiffHunk { header, lines }
+        })
+        .collect()
+}
+
+/// Linhas inseridas e removidas de `old` para `new`.
+pub fn line_stats(old: &str, new: &str) -> (usize, usize) {
+    TextDiff::from_lines(old, new)
+        .iter_all_changes()
+        .fold((0, 0), |(inserted, deleted), change| match change.tag() {
+            ChangeTag::Insert => (inserted + 1, deleted),
+            ChangeTag::Delete => (inserted, deleted + 1),
+            ChangeTag::Equal => (inserted, deleted),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(line: &DiffLine) -> String {
+        line.spans.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    /// Um dos lados do diff, remontado das linhas iguais e das do lado `tag`.
+    fn side(hunks: &[DiffHunk], tag: DiffTag) -> String {
+        hunks.iter()
+            .flat_map(|h| &h.lines)
+            .filter(|l| l.tag == DiffTag::Equal || l.tag == tag)
+            .map(|l| text(l) + "\n")
+            .collect()
+    }
+
+    #[test]
+    fn equal_texts_have_no_hunks() {
+        assert!(unified_diff("um\ndois\n", "um\ndois\n", 3).is_empty());
+        assert_eq!(line_stats("um\ndois\n", "um\ndois\n"), (0, 0));
+    }
+
+    #[test]
+    fn inserted_lines_are_numbered_only_in_the_new_version() {
+        let hunks = unified_diff("um\ntrês\n", "um\ndois\ntrês\n", 1);
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].header, "@@ -1,2 +1,3 @@");
+        let tags: Vec<_> = hunks[0].lines.iter().map(|l| l.tag).collect();
+        assert_eq!(tags, [DiffTag::Equal, DiffTag::Insert, DiffTag::Equal]);
+        let inserted = &hunks[0].lines[1];
+        assert_eq!((inserted.old_number, inserted.new_number), (None, Some(2)));
+        assert_eq!(text(inserted), "dois");
+        assert_eq!(line_stats("um\ntrês\n", "um\ndois\ntrês\n"), (1, 0));
+    }
+
+    #[test]
+    fn deleted_lines_are_numbered_only_in_the_old_version() {
+        let hunks = unified_diff("um\ndois\ntrês\n", "um\ntrês\n", 1);
+        assert_eq!(hunks.len(), 1);
+        let deleted = &hunks[0].lines[1];
+        assert_eq!(deleted.tag, DiffTag::Delete);
+        assert_eq!((deleted.old_number, deleted.new_number), (Some(2), None));
+        assert_eq!(text(deleted), "dois");
+        // a linha seguinte é a mesma nas duas versões, com números diferentes
+        let after = &hunks[0].lines[2];
+        assert_eq!((after.tag, after.old_number, after.new_number), (DiffTag::Equal, Some(3), Some(2)));
+        assert_eq!(line_stats("um\ndois\ntrês\n", "um\ntrês\n"), (0, 1));
+    }
+
+    #[test]
+    fn changed_lines_emphasize_only_the_changed_words() {
+        let hunks = unified_diff("um\ndois três\nquatro\n", "um\ndois TRÊS\nquatro\n", 1);
+        let lines = &hunks[0].lines;
+        assert_eq!(lines[1].tag, DiffTag::Delete);
+        assert_eq!(lines[1].spans, [
+            DiffSpan { text: "dois ".to_string(), emphasized: false },
+            DiffSpan { text: "três".to_string(), emphasized: true },
+        ]);
+        assert_eq!(lines[2].tag, DiffTag::Insert);
+        assert_eq!(lines[2].spans, [
+            DiffSpan { text: "dois ".to_string(), emphasized: false },
+            DiffSpan { text: "TRÊS".to_string(), emphasized: true },
+        ]);
+        // linhas iguais não têm destaque nem a quebra de linha
+        assert_eq!(lines[0].spans, [DiffSpan { text: "um".to_string(), emphasized: false }]);
+    }
+
+    #[test]
+    fn distant_changes_get_separate_hunks() {
+        let hunks = unified_diff("a\nb\nc\nd\ne\nf\ng\n", "a\nX\nc\nd\ne\nf\n", 1);
+        let headers: Vec<_> = hunks.iter().map(|h| h.header.as_str()).collect();
+        assert_eq!(headers, ["@@ -1,3 +1,3 @@", "@@ -6,2 +6 @@"]);
+    }
+
+    #[test]
+    fn restoring_an_older_version_round_trips() {
+        let older = "# Lista\nleite\npão\n";
+        let current = "# Lista\nleite integral\npão\novos\n";
+
+        // com contexto suficiente, o diff contém as duas versões inteiras
+        let hunks = unified_diff(older, current, 10);
+        assert_eq!(side(&hunks, DiffTag::Delete), older);
+        assert_eq!(side(&hunks, DiffTag::Insert), current);
+
+        // restaurar desfaz exatamente o que o diff mostrou
+        let restored = side(&hunks, DiffTag::Delete);
+        assert!(unified_diff(older, &restored, 3).is_empty());
+        let (inserted, deleted) = line_stats(older, current);
+        assert_eq!(line_stats(current, &restored), (deleted, inserted));
+    }
+}
@@ -0,0 +1,493 @@
+// src/text/format.rs
+
+/// Texto e seleção (índices de byte) depois de aplicar uma formatação.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Formatações Markdown da barra do editor.
+///
+/// As de trecho (negrito, itálico...) envolvem a seleção e as de bloco
+/// (títulos, listas, citação) mudam o começo de cada linha tocada por ela;
+/// aplicar de novo desfaz.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Bold,
+    Italic,
+    Strikethrough,
+    Code,
+    /// Nível 1 a 6.
+    Heading(u8),
+    BulletList,
+    NumberedList,
+    TaskList,
+    Quote,
+    CodeBlock,
+    Link,
+    Table,
+}
+
+/// Botões da barra, na ordem em que aparecem.
+pub const TOOLBAR: [Format; 14] = [
+    Format::Bold,
+    Format::Italic,
+    Format::Strikethrough,
+    Format::Heading(1),
+    Format::Heading(2),
+    Format::Heading(3),
+    Format::BulletList,
+    Format::NumberedList,
+    Format::TaskList,
+    Format::Quote,
+    Format::Code,
+    Format::CodeBlock,
+    Format::Link,
+    Format::Table,
+];
+
+const TABLE_TEMPLATE: &str = "| Coluna 1 | Coluna 2 |\n| --- | --- |\n|  |  |\n";
+const LINK_PLACEHOLDER: &str = "texto";
+const URL_PLACEHOLDER: &str = "https://";
+
+impl Format {
+    /// Texto curto do botão.
+    pub fn icon(self) -> String {
+        match self {
+            Format::Bold => "B".to_string(),
+            Format::Italic => "I".to_string(),
+            Format::Strikethrough => "S".to_string(),
+            Format::Code => "</>".to_string(),
+            Format::Heading(level) => format!("H{}", level),
+            Format::BulletList => "•".to_string(),
+            Format::NumberedList => "1.".to_string(),
+            Format::TaskList => "☑".to_string(),
+            Format::Quote => "❝".to_string(),
+            Format::CodeBlock => "{ }".to_string(),
+            Format::Link => "🔗".to_string(),
+            Format::Table => "▦".to_string(),
+        }
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            Format::Bold => "Negrito".to_string(),
+            Format::Italic => "Itálico".to_string(),
+            Format::Strikethrough => "Tachado".to_string(),
+            Format::Code => "Código".to_string(),
+            Format::Heading(level) => format!("Título {}", level),
+            Format::BulletList => "Lista".to_string(),
+            Format::NumberedList => "Lista numerada".to_string(),
+            Format::TaskList => "Lista de tarefas".to_string(),
+            Format::Quote => "Citação".to_string(),
+            Format::CodeBlock => "Bloco de código".to_string(),
+            Format::Link => "Link".to_string(),
+            Format::Table => "Tabela".to_string(),
+        }
+    }
+
+    /// Atalho mostrado na dica do botão (Ctrl ou ⌘ em todos).
+    pub fn shortcut(self) -> String {
+        match self {
+            Format::Bold => "Ctrl+B".to_string(),
+            Format::Italic => "Ctrl+I".to_string(),
+            Format::Strikethrough => "Ctrl+Shift+X".to_string(),
+            Format::Code => "Ctrl+E".to_string(),
+            Format::Heading(level) => format!("Ctrl+Alt+{}", level),
+            Format::BulletList => "Ctrl+Shift+8".to_string(),
+            Format::NumberedList => "Ctrl+Shift+7".to_string(),
+            Format::TaskList => "Ctrl+Shift+9".to_string(),
+            Format::Quote => "Ctrl+Shift+.".to_string(),
+            Format::CodeBlock => "Ctrl+Alt+C".to_string(),
+            Format::Link => "Ctrl+K".to_string(),
+            Format::Table => "Ctrl+Alt+T".to_string(),
+        }
+    }
+
+    /// Formatação do atalho com Ctrl/⌘; `code` é o `KeyboardEvent.code` (a
+    /// tecla física, para os números não dependerem do layout com Shift).
+    pub fn from_shortcut(code: &str, shift: bool, alt: bool) -> Option<Self> {
+        let format = match (code, shift, alt) {
+            ("KeyB", false, false) => Format::Bold,
+            ("KeyI", false, false) => Format::Italic,
+            ("KeyE", false, false) => Format::Code,
+            ("KeyK", false, false) => Format::Link,
+            ("KeyX", true, false) => Format::Strikethrough,
+            ("Digit7", true, false) => Format::NumberedList,
+            ("Digit8", true, false) => Format::BulletList,
+            ("Digit9", true, false) => Format::TaskList,
+            ("Period", true, false) => Format::Quote,
+            ("KeyC", false, true) => Format::CodeBlock,
+            ("KeyT", false, true) => Format::Table,
+            (digit, false, true) => {
+                let level = digit.strip_prefix("Digit")?.parse().ok()?;
+                if !(1..=6).contains(&level) {
+                    return None;
+                }
+                Format::Heading(level)
+            }
+            _ => return None,
+        };
+        Some(format)
+    }
+
+    /// Aplica a formatação à seleção `start..end` (índices de byte em `text`).
+    pub fn apply(self, text: &str, start: usize, end: usize) -> Edit {
+        let end = floor_char_boundary(text, end.min(text.len()));
+        let start = floor_char_boundary(text, start.min(end));
+        match self {
+            Format::Bold => wrap(text, start, end, "**"),
+            Format::Italic => wrap(text, start, end, "*"),
+            Format::Strikethrough => wrap(text, start, end, "~~"),
+            Format::Code => wrap(text, start, end, "`"),
+            Format::Heading(_) | Format::BulletList | Format::NumberedList | Format::TaskList | Format::Quote => {
+                prefix_lines(text, start, end, self)
+            }
+            Format::CodeBlock => code_block(text, start, end),
+            Format::Link => link(text, start, end),
+            Format::Table => table(text, start),
+        }
+    }
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Quantos `c` seguidos há no fim de `s`.
+fn trailing(s: &str, c: char) -> usize {
+    s.chars().rev().take_while(|&x| x == c).count()
+}
+
+fn leading(s: &str, c: char) -> usize {
+    s.chars().take_while(|&x| x == c).count()
+}
+
+/// Se uma sequência de `run` marcadores contém `marker`: `*` e `**` usam o
+/// mesmo caractere, então `***texto***` é negrito e itálico.
+fn has_marker(run: usize, marker: &str) -> bool {
+    match marker {
+        "*" => run % 2 == 1,
+        _ => run >= marker.len(),
+    }
+}
+
+/// Envolve a seleção com `marker`, ou remove se já estiver envolvida (por
+/// fora ou por dentro dela).
+fn wrap(text: &str, start: usize, end: usize, marker: &str) -> Edit {
+    let c = marker.chars().next().unwrap_or('*');
+    let m = marker.len();
+    let (before, selected, after) = (&text[..start], &text[start..end], &text[end..]);
+
+    if has_marker(trailing(before, c).min(leading(after, c)), marker) {
+        return Edit {
+            text: format!("{}{}{}", &before[..start - m], selected, &after[m..]),
+            start: start - m,
+            end: end - m,
+        };
+    }
+    let inner = trailing(selected, c).min(leading(selected, c));
+    if selected.len() >= 2 * m && selected.chars().any(|x| x != c) && has_marker(inner, marker) {
+        return Edit {
+            text: format!("{}{}{}", before, &selected[m..selected.len() - m], after),
+            start,
+            end: end - 2 * m,
+        };
+    }
+    Edit {
+        text: format!("{}{}{}{}{}", before, marker, selected, marker, after),
+        start: start + m,
+        end: end + m,
+    }
+}
+
+/// Início da linha que contém `start` e fim (sem a quebra) da que contém `end`.
+fn line_bounds(text: &str, start: usize, end: usize) -> (usize, usize) {
+    let line_start = text[..start].rfind('\n').map_or(0, |i| i + 1);
+    // seleção que termina logo depois de uma quebra não inclui a linha seguinte
+    let last = if end > start && text[..end].ends_with('\n') { end - 1 } else { end };
+    let line_end = text[last..].find('\n').map_or(text.len(), |i| last + i);
+    (line_start, line_end.max(line_start))
+}
+
+/// Tamanho do marcador de bloco no começo da linha (título, lista ou tarefa).
+fn block_prefix(line: &str) -> usize {
+    let hashes = leading(line, '#');
+    if (1..=6).contains(&hashes) && line[hashes..].starts_with(' ') {
+        return hashes + 1;
+    }
+    for task in ["- [ ] ", "- [x] ", "- [X] ", "* [ ] ", "* [x] ", "* [X] "] {
+        if line.starts_with(task) {
+            return task.len();
+        }
+    }
+    if line.starts_with("- ") || line.starts_with("* ") || line.starts_with("+ ") {
+        return 2;
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 && line[digits..].starts_with(". ") {
+        return digits + 2;
+    }
+    0
+}
+
+/// Se a linha já começa com o marcador desta formatação.
+fn has_prefix(line: &str, format: Format) -> bool {
+    let prefix = &line[..block_prefix(line)];
+    match format {
+        Format::Heading(level) => prefix.trim_end().len() == level as usize && prefix.starts_with('#'),
+        Format::BulletList => matches!(prefix, "- " | "* " | "+ "),
+        Format::NumberedList => prefix.ends_with(". ") && prefix.starts_with(|c: char| c.is_ascii_digit()),
+        Format::TaskList => prefix.len() == 6 && prefix.contains('['),
+        Format::Quote => line.starts_with('>'),
+        _ => false,
+    }
+}
+
+/// Títulos, listas e citação: troca o começo de cada linha da seleção.
+fn prefix_lines(text: &str, start: usize, end: usize, format: Format) -> Edit {
+    let (line_start, line_end) = line_bounds(text, start, end);
+    let block = &text[line_start..line_end];
+    let lines: Vec<&str> = block.split('\n').collect();
+    // linhas em branco no meio de uma seleção de várias linhas ficam como estão
+    let skip = |line: &str| lines.len() > 1 && line.trim().is_empty();
+    let remove = lines.iter().filter(|l| !skip(l)).all(|l| has_prefix(l, format));
+
+    let mut number = 0;
+    let mut deltas = Vec::with_capacity(lines.len());
+    let new_lines: Vec<String> = lines.iter()
+        .map(|&line| {
+            if skip(line) {
+                deltas.push((0, 0));
+                return line.to_string();
+            }
+            // (bytes tirados do começo, bytes acrescentados)
+            let (cut, added) = match (format, remove) {
+                (Format::Quote, true) => (if line.starts_with("> ") { 2 } else { 1 }, String::new()),
+                (Format::Quote, false) => (0, "> ".to_string()),
+                (_, true) => (block_prefix(line), String::new()),
+                (_, false) => {
+                    number += 1;
+                    let prefix = match format {
+                        Format::Heading(level) => format!("{} ", "#".repeat(level as usize)),
+                        Format::BulletList => "- ".to_string(),
+                        Format::NumberedList => format!("{}. ", number),
+                        _ => "- [ ] ".to_string(),
+                    };
+                    (block_prefix(line), prefix)
+                }
+            };
+            deltas.push((cut, added.len()));
+            format!("{}{}", added, &line[cut..])
+        })
+        .collect();
+
+    let new_block = new_lines.join("\n");
+    let shift = |index: usize, line: usize| {
+        let (cut, added) = deltas[line];
+        let line_offset = lines[..line].iter().map(|l| l.len() + 1).sum::<usize>();
+        let new_offset = new_lines[..line].iter().map(|l| l.len() + 1).sum::<usize>();
+        let column = (index - line_start - line_offset).saturating_sub(cut);
+        line_start + new_offset + added + column
+    };
+    let line_of = |index: usize| text[line_start..index.max(line_start)].matches('\n').count().min(lines.len() - 1);
+    let new_start = shift(start, line_of(start));
+    let new_end = shift(end.min(line_end), line_of(end.min(line_end))).max(new_start);
+    Edit {
+        text: format!("{}{}{}", &text[..line_start], new_block, &text[line_end..]),
+        start: new_start,
+        end: new_end,
+    }
+}
+
+/// Envolve as linhas da seleção com cercas ``` (ou remove as que já houver).
+fn code_block(text: &str, start: usize, end: usize) -> Edit {
+    let (line_start, line_end) = line_bounds(text, start, end);
+    let block = &text[line_start..line_end];
+    let fenced = block.contains('\n')
+        .then(|| block.strip_prefix("```").and_then(|b| b.strip_suffix("```")))
+        .flatten();
+    if let Some(inner) = fenced {
+        // tira a linha da cerca de abertura (com a linguagem) e a quebra final
+        let inner = inner.split_once('\n').map_or("", |(_, rest)| rest);
+        let inner = inner.strip_suffix('\n').unwrap_or(inner);
+        return Edit {
+            text: format!("{}{}{}", &text[..line_start], inner, &text[line_end..]),
+            start: line_start,
+            end: line_start + inner.len(),
+        };
+    }
+    let new = format!("```\n{}\n```", block);
+    let inner_start = line_start + 4;
+    Edit {
+        text: format!("{}{}{}", &text[..line_start], new, &text[line_end..]),
+        start: inner_start,
+        end: inner_start + block.len(),
+    }
+}
+
+/// `[texto](url)`, selecionando a parte que falta preencher.
+fn link(text: &str, start: usize, end: usize) -> Edit {
+    let selected = &text[start..end];
+    let is_url = selected.starts_with("http://") || selected.starts_with("https://");
+    let (label, url) = match (selected.is_empty(), is_url) {
+        (true, _) => (LINK_PLACEHOLDER, URL_PLACEHOLDER),
+        (false, true) => (LINK_PLACEHOLDER, selected),
+        (false, false) => (selected, URL_PLACEHOLDER),
+    };
+    let new = format!("[{}]({})", label, url);
+    let (sel_start, sel_len) = if selected.is_empty() || is_url {
+        (start + 1, label.len())
+    } else {
+        (start + label.len() + 3, url.len())
+    };
+    Edit {
+        text: format!("{}{}{}", &text[..start], new, &text[end..]),
+        start: sel_start,
+        end: sel_start + sel_len,
+    }
+}
+
+/// Modelo de tabela em um parágrafo próprio, com o primeiro cabeçalho
+/// selecionado.
+fn table(text: &str, at: usize) -> Edit {
+    let before = &text[..at];
+    let separator = if before.is_empty() || before.ends_with("\n\n") {
+        ""
+    } else if before.ends_with('\n') {
+        "\n"
+    } else {
+        "\n\n"
+    };
+    let header_start = at + separator.len() + 2;
+    Edit {
+        text: format!("{}{}{}{}", before, separator, TABLE_TEMPLATE, &text[at..]),
+        start: header_start,
+        end: header_start + "Coluna 1".len(),
+    }
+}
+
+/// Índice UTF-16 (como em `selectionStart` do JS) para índice de byte.
+pub fn utf16_to_byte(s: &str, target: usize) -> usize {
+    let mut utf16_count = 0;
+    for (byte_idx, ch) in s.char_indices() {
+        if utf16_count + ch.len_utf16() > target {
+            return byte_idx;
+        }
+        utf16_count += ch.len_utf16();
+    }
+    s.len()
+}
+
+/// Índice de byte para índice UTF-16.
+pub fn byte_to_utf16(s: &str, index: usize) -> usize {
+    s[..floor_char_boundary(s, index.min(s.len()))].encode_utf16().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(text: &str, start: usize, end: usize) -> Edit {
+        Edit { text: text.to_string(), start, end }
+    }
+
+    #[test]
+    fn heading_toggles_and_switches_level() {
+        assert_eq!(Format::Heading(2).apply("título", 0, 0), edit("## título", 3, 3));
+        assert_eq!(Format::Heading(2).apply("## título", 3, 3), edit("título", 0, 0));
+        assert_eq!(Format::Heading(1).apply("## título", 3, 3), edit("# título", 2, 2));
+    }
+
+    #[test]
+    fn heading_covers_every_selected_line() {
+        assert_eq!(Format::Heading(1).apply("a\nb", 0, 3), edit("# a\n# b", 2, 7));
+        // a seleção que termina na quebra não pega a linha seguinte
+        assert_eq!(Format::Heading(1).apply("a\nb", 0, 2).text, "# a\nb");
+    }
+
+    #[test]
+    fn lists_number_lines_and_skip_blank_ones() {
+        assert_eq!(Format::NumberedList.apply("a\n\nb", 0, 4).text, "1. a\n\n2. b");
+        assert_eq!(Format::BulletList.apply("- a\n- b", 0, 7), edit("a\nb", 0, 3));
+        assert_eq!(Format::BulletList.apply("1. um", 3, 3), edit("- um", 2, 2));
+    }
+
+    #[test]
+    fn task_list_replaces_other_markers() {
+        assert_eq!(Format::TaskList.apply("comprar pão", 0, 0), edit("- [ ] comprar pão", 6, 6));
+        assert_eq!(Format::TaskList.apply("- item", 2, 6), edit("- [ ] item", 6, 10));
+        assert_eq!(Format::TaskList.apply("- [x] feito\n- [ ] não", 0, 22).text, "feito\nnão");
+    }
+
+    #[test]
+    fn quote_toggles_each_line() {
+        assert_eq!(Format::Quote.apply("a\nb", 0, 3).text, "> a\n> b");
+        assert_eq!(Format::Quote.apply("> citação\n>sem espaço", 0, 24).text, "citação\nsem espaço");
+    }
+
+    #[test]
+    fn code_block_wraps_and_unwraps_lines() {
+        assert_eq!(Format::CodeBlock.apply("let x = 1;", 0, 10), edit("```\nlet x = 1;\n```", 4, 14));
+        let fenced = "```rust\nfn f() {}\n```";
+        assert_eq!(Format::CodeBlock.apply(fenced, 0, fenced.len()), edit("fn f() {}", 0, 9));
+        // seleção vazia: cerca em volta da linha do cursor
+        assert_eq!(Format::CodeBlock.apply("a\nção", 3, 3).text, "a\n```\nção\n```");
+    }
+
+    #[test]
+    fn link_selects_what_is_missing() {
+        assert_eq!(Format::Link.apply("", 0, 0), edit("[texto](https://)", 1, 6));
+        assert_eq!(Format::Link.apply("site", 0, 4), edit("[site](https://)", 7, 15));
+        assert_eq!(Format::Link.apply("https://a.b", 0, 11), edit("[texto](https://a.b)", 1, 6));
+        assert_eq!(Format::Link.apply("ação", 0, 6), edit("[ação](https://)", 9, 17));
+    }
+
+    #[test]
+    fn table_starts_a_paragraph() {
+        assert_eq!(Format::Table.apply("", 0, 0), edit(TABLE_TEMPLATE, 2, 10));
+        let after_text = Format::Table.apply("abc", 3, 3);
+        assert_eq!(after_text.text, format!("abc\n\n{}", TABLE_TEMPLATE));
+        assert_eq!(&after_text.text[after_text.start..after_text.end], "Coluna 1");
+        assert_eq!(Format::Table.apply("abc\n", 4, 4).text, format!("abc\n\n{}", TABLE_TEMPLATE));
+    }
+
+    #[test]
+    fn strikethrough_wraps_and_unwraps() {
+        assert_eq!(Format::Strikethrough.apply("riscar", 0, 6), edit("~~riscar~~", 2, 8));
+        assert_eq!(Format::Strikethrough.apply("~~riscar~~", 2, 8), edit("riscar", 0, 6));
+        assert_eq!(Format::Strikethrough.apply("~~riscar~~", 0, 10), edit("riscar", 0, 6));
+        assert_eq!(Format::Strikethrough.apply("", 0, 0), edit("~~~~", 2, 2));
+    }
+
+    #[test]
+    fn italic_inside_bold() {
+        assert_eq!(Format::Italic.apply("**x**", 2, 3), edit("***x***", 3, 4));
+        assert_eq!(Format::Italic.apply("***x***", 3, 4), edit("**x**", 2, 3));
+    }
+
+    #[test]
+    fn selection_inside_a_character_is_clamped() {
+        assert_eq!(Format::Bold.apply("olá mundo", 0, 4), edit("**olá** mundo", 2, 6));
+        // o byte 3 fica no meio do "á"
+        assert_eq!(Format::Bold.apply("olá", 0, 3), edit("**ol**á", 2, 4));
+    }
+
+    #[test]
+    fn utf16_offsets() {
+        let s = "a😀é";
+        let bytes: Vec<usize> = (0..6).map(|i| utf16_to_byte(s, i)).collect();
+        // o índice 2 cai entre as duas metades do emoji
+        assert_eq!(bytes, vec![0, 1, 1, 5, 7, 7]);
+        let units: Vec<usize> = [0, 1, 3, 5, 7, 100].iter().map(|&i| byte_to_utf16(s, i)).collect();
+        assert_eq!(units, vec![0, 1, 1, 3, 4, 4]);
+        for i in 0..=4 {
+            assert_eq!(byte_to_utf16(s, utf16_to_byte(s, i)), if i == 2 { 1 } else { i });
+        }
+    }
+}
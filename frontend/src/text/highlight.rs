@@ -0,0 +1,355 @@
+// src/text/highlight.rs
+use crate::text::markdown::escape_html;
+
+/// Classes dos trechos destacados (liberadas na sanitização do Markdown).
+pub const CLASSES: [&str; 7] = [
+    "hl-keyword",
+    "hl-string",
+    "hl-comment",
+    "hl-number",
+    "hl-literal",
+    "hl-type",
+    "hl-function",
+];
+
+/// Regras de uma linguagem para o destaque: basta separar comentários,
+/// textos, números e palavras, sem analisar a gramática.
+struct Language {
+    /// Classe do bloco `<code>`.
+    class: &'static str,
+    aliases: &'static [&'static str],
+    keywords: &'static [&'static str],
+    literals: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    quotes: &'static [char],
+    /// Palavras sem diferença entre maiúsculas e minúsculas (SQL).
+    ignore_case: bool,
+    /// Palavras com inicial maiúscula são tipos.
+    capitalized_types: bool,
+}
+
+const C_LINE_COMMENTS: &[&str] = &["//"];
+const C_BLOCK_COMMENT: Option<(&str, &str)> = Some(("/*", "*/"));
+
+const LANGUAGES: &[Language] = &[
+    Language {
+        class: "language-rust",
+        aliases: &["rust", "rs"],
+        keywords: &["as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+            "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+            "self", "Self", "static", "struct", "super", "trait", "type", "unsafe", "use", "where", "while"],
+        literals: &["true", "false", "None", "Some", "Ok", "Err"],
+        line_comments: C_LINE_COMMENTS,
+        block_comment: C_BLOCK_COMMENT,
+        quotes: &['"', '\''],
+        ignore_case: false,
+        capitalized_types: true,
+    },
+    Language {
+        class: "language-javascript",
+        aliases: &["javascript", "js", "jsx", "typescript", "ts", "tsx"],
+        keywords: &["async", "await", "break", "case", "catch", "class", "const", "continue", "default", "delete",
+            "do", "else", "export", "extends", "finally", "for", "from", "function", "if", "import", "in",
+            "instanceof", "interface", "let", "new", "of", "return", "static", "switch", "this", "throw", "try",
+            "type", "typeof", "var", "void", "while", "yield"],
+        literals: &["true", "false", "null", "undefined", "NaN"],
+        line_comments: C_LINE_COMMENTS,
+        block_comment: C_BLOCK_COMMENT,
+        quotes: &['"', '\'', '`'],
+        ignore_case: false,
+        capitalized_types: true,
+    },
+    Language {
+        class: "language-python",
+        aliases: &["python", "py"],
+        keywords: &["and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif",
+            "else", "except", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "nonlocal",
+            "not", "or", "pass", "raise", "return", "try", "while", "with", "yield"],
+        literals: &["True", "False", "None"],
+        line_comments: &["#"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        ignore_case: false,
+        capitalized_types: true,
+    },
+    Language {
+        class: "language-go",
+        aliases: &["go", "golang"],
+        keywords: &["break", "case", "chan", "const", "continue", "default", "defer", "else", "fallthrough", "for",
+            "func", "go", "goto", "if", "import", "interface", "map", "package", "range", "return", "select",
+            "struct", "switch", "type", "var"],
+        literals: &["true", "false", "nil", "iota"],
+        line_comments: C_LINE_COMMENTS,
+        block_comment: C_BLOCK_COMMENT,
+        quotes: &['"', '\'', '`'],
+        ignore_case: false,
+        capitalized_types: false,
+    },
+    Language {
+        class: "language-java",
+        aliases: &["java", "kotlin", "kt", "csharp", "cs"],
+        keywords: &["abstract", "break", "case", "catch", "class", "const", "continue", "default", "do", "else",
+            "enum", "extends", "final", "finally", "for", "fun", "if", "implements", "import", "interface", "new",
+            "override", "package", "private", "protected", "public", "return", "static", "switch", "this", "throw",
+            "throws", "try", "using", "val", "var", "void", "while"],
+        literals: &["true", "false", "null"],
+        line_comments: C_LINE_COMMENTS,
+        block_comment: C_BLOCK_COMMENT,
+        quotes: &['"', '\''],
+        ignore_case: false,
+        capitalized_types: true,
+    },
+    Language {
+        class: "language-c",
+        aliases: &["c", "cpp", "c++", "h", "hpp"],
+        keywords: &["auto", "break", "case", "char", "class", "const", "continue", "default", "delete", "do",
+            "double", "else", "enum", "extern", "float", "for", "if", "include", "inline", "int", "long",
+            "namespace", "new", "private", "public", "return", "short", "signed", "sizeof", "static", "struct",
+            "switch", "template", "typedef", "union", "unsigned", "using", "void", "while"],
+        literals: &["true", "false", "NULL", "nullptr"],
+        line_comments: C_LINE_COMMENTS,
+        block_comment: C_BLOCK_COMMENT,
+        quotes: &['"', '\''],
+        ignore_case: false,
+        capitalized_types: false,
+    },
+    Language {
+        class: "language-sql",
+        aliases: &["sql"],
+        keywords: &["add", "alter", "and", "as", "asc", "by", "create", "delete", "desc", "distinct", "drop",
+            "from", "group", "having", "in", "index", "inner", "insert", "into", "is", "join", "key", "left",
+            "like", "limit", "not", "on", "or", "order", "outer", "primary", "references", "right", "select",
+            "set", "table", "union", "update", "values", "where", "with"],
+        literals: &["true", "false", "null"],
+        line_comments: &["--"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['\'', '"'],
+        ignore_case: true,
+        capitalized_types: false,
+    },
+    Language {
+        class: "language-bash",
+        aliases: &["bash", "sh", "shell", "zsh", "console"],
+        keywords: &["case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if", "in",
+            "local", "return", "then", "until", "while"],
+        literals: &["true", "false"],
+        line_comments: &["#"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        ignore_case: false,
+        capitalized_types: false,
+    },
+    Language {
+        class: "language-json",
+        aliases: &["json"],
+        keywords: &[],
+        literals: &["true", "false", "null"],
+        line_comments: &[],
+        block_comment: None,
+        quotes: &['"'],
+        ignore_case: false,
+        capitalized_types: false,
+    },
+];
+
+/// Classes `language-…` das linguagens reconhecidas.
+pub fn language_classes() -> impl Iterator<Item = &'static str> {
+    LANGUAGES.iter().map(|l| l.class)
+}
+
+fn find_language(name: &str) -> Option<&'static Language> {
+    let name = name.trim().to_ascii_lowercase();
+    LANGUAGES.iter().find(|l| l.aliases.contains(&name.as_str()))
+}
+
+/// Classe do bloco de código (`js` vira `language-javascript`), se a
+/// linguagem for reconhecida.
+pub fn language_class(name: &str) -> Option<&'static str> {
+    find_language(name).map(|l| l.class)
+}
+
+/// Código destacado em HTML (já escapado), com um `span` por trecho;
+/// `None` se a linguagem não for reconhecida.
+pub fn highlight(language: &str, code: &str) -> Option<String> {
+    let lang = find_language(language)?;
+    let mut out = String::with_capacity(code.len() * 2);
+    let mut rest = code;
+    while let Some(c) = rest.chars().next() {
+        let (len, class) = if let Some(len) = comment_len(lang, rest) {
+            (len, Some("hl-comment"))
+        } else if lang.quotes.contains(&c) && !(c == '\'' && is_lifetime(lang, rest)) {
+            (string_len(rest, c), Some("hl-string"))
+        } else if c.is_ascii_digit() {
+            (rest.find(|x: char| !(x.is_ascii_alphanumeric() || x == '_' || x == '.')).unwrap_or(rest.len()), Some("hl-number"))
+        } else if c.is_alphabetic() || c == '_' {
+            let len = rest.find(|x: char| !(x.is_alphanumeric() || x == '_')).unwrap_or(rest.len());
+            (len, word_class(lang, &rest[..len], rest[len..].starts_with('(')))
+        } else {
+            (c.len_utf8(), None)
+        };
+        push_span(&mut out, &rest[..len], class);
+        rest = &rest[len..];
+    }
+    Some(out)
+}
+
+fn comment_len(lang: &Language, rest: &str) -> Option<usize> {
+    if lang.line_comments.iter().any(|m| rest.starts_with(m)) {
+        return Some(rest.find('\n').unwrap_or(rest.len()));
+    }
+    let (open, close) = lang.block_comment?;
+    rest.starts_with(open).then(|| {
+        rest[open.len()..].find(close).map_or(rest.len(), |i| open.len() + i + close.len())
+    })
+}
+
+/// Até a aspa de fechamento sem escape (ou o fim da linha, se faltar).
+fn string_len(rest: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (i, c) in rest.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '\n' if quote != '`' => return i,
+            c if c == quote => return i + c.len_utf8(),
+            _ => {}
+        }
+    }
+    rest.len()
+}
+
+/// `'a` em Rust é um lifetime, não o começo de um caractere.
+fn is_lifetime(lang: &Language, rest: &str) -> bool {
+    if lang.class != "language-rust" {
+        return false;
+    }
+    let mut chars = rest.chars().skip(1);
+    !matches!((chars.next(), chars.next()), (Some('\\'), _) | (Some(_), Some('\'')))
+}
+
+fn word_class(lang: &Language, word: &str, called: bool) -> Option<&'static str> {
+    let matches = |list: &[&str]| if lang.ignore_case {
+        list.iter().any(|k| k.eq_ignore_ascii_case(word))
+    } else {
+        list.contains(&word)
+    };
+    if matches(lang.keywords) {
+        Some("hl-keyword")
+    } else if matches(lang.literals) {
+        Some("hl-literal")
+    } else if called {
+        Some("hl-function")
+    } else if lang.capitalized_types && word.starts_with(|c: char| c.is_uppercase()) {
+        Some("hl-type")
+    } else {
+        None
+    }
+}
+
+fn push_span(out: &mut String, text: &str, class: Option<&str>) {
+    if let Some(class) = class {
+        out.push_str("<span class=\"");
+        out.push_str(class);
+        out.push_str("\">");
+    }
+    out.push_str(&escape_html(text));
+    if class.is_some() {
+        out.push_str("</span>");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_html_inside_and_outside_spans() {
+        assert_eq!(
+            highlight("js", "a<b && \"<i>\"").unwrap(),
+            "a&lt;b &amp;&amp; <span class=\"hl-string\">&quot;&lt;i&gt;&quot;</span>",
+        );
+        assert_eq!(
+            highlight("rust", "// <script>").unwrap(),
+            "<span class=\"hl-comment\">// &lt;script&gt;</span>",
+        );
+    }
+
+    #[test]
+    fn rust_lifetimes_are_not_char_literals() {
+        assert_eq!(
+            highlight("rust", "&'a str").unwrap(),
+            "&amp;'a str",
+        );
+        assert_eq!(
+            highlight("rust", "'x'").unwrap(),
+            "<span class=\"hl-string\">'x'</span>",
+        );
+        assert_eq!(
+            highlight("rust", r"'\n'").unwrap(),
+            r#"<span class="hl-string">'\n'</span>"#,
+        );
+        // fora do Rust, a aspa simples sempre abre um texto
+        assert_eq!(
+            highlight("python", "'a b'").unwrap(),
+            "<span class=\"hl-string\">'a b'</span>",
+        );
+    }
+
+    #[test]
+    fn unterminated_strings_stop_at_the_end_of_the_line() {
+        assert_eq!(
+            highlight("python", "x = \"aberto\ny").unwrap(),
+            "x = <span class=\"hl-string\">&quot;aberto</span>\ny",
+        );
+        // template strings do JavaScript atravessam linhas
+        assert_eq!(
+            highlight("js", "`a\nb").unwrap(),
+            "<span class=\"hl-string\">`a\nb</span>",
+        );
+        assert_eq!(
+            highlight("js", r#""a\"b""#).unwrap(),
+            r#"<span class="hl-string">&quot;a\&quot;b&quot;</span>"#,
+        );
+    }
+
+    #[test]
+    fn unterminated_block_comments_run_to_the_end() {
+        assert_eq!(
+            highlight("c", "x /* sem fim\nint y;").unwrap(),
+            "x <span class=\"hl-comment\">/* sem fim\nint y;</span>",
+        );
+        assert_eq!(
+            highlight("c", "/* a */int").unwrap(),
+            "<span class=\"hl-comment\">/* a */</span><span class=\"hl-keyword\">int</span>",
+        );
+    }
+
+    #[test]
+    fn sql_keywords_ignore_case() {
+        assert_eq!(
+            highlight("SQL", "Select * FROM t where NULL").unwrap(),
+            "<span class=\"hl-keyword\">Select</span> * <span class=\"hl-keyword\">FROM</span> t \
+             <span class=\"hl-keyword\">where</span> <span class=\"hl-literal\">NULL</span>",
+        );
+        // nas outras linguagens a caixa importa
+        assert_eq!(highlight("python", "IF").unwrap(), "<span class=\"hl-type\">IF</span>");
+    }
+
+    #[test]
+    fn words_numbers_and_calls() {
+        assert_eq!(
+            highlight("rs", "let v = Vec::new(42);").unwrap(),
+            "<span class=\"hl-keyword\">let</span> v = <span class=\"hl-type\">Vec</span>::\
+             <span class=\"hl-function\">new</span>(<span class=\"hl-number\">42</span>);",
+        );
+    }
+
+    #[test]
+    fn unknown_languages_are_not_highlighted() {
+        assert_eq!(highlight("brainfuck", "+++"), None);
+        assert_eq!(highlight("", "x"), None);
+        assert_eq!(language_class(" JS "), Some("language-javascript"));
+        assert_eq!(language_class("cobol"), None);
+    }
+}
@@ -0,0 +1,334 @@
+// src/text/markdown.rs
+use std::collections::{HashMap, HashSet};
+use pulldown_cmark::{html, CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use crate::text::{diagram, highlight, math, wiki};
+
+thread_local! {
+    /// O `ammonia` padrão, mais as classes do destaque de código e os
+    /// elementos de fórmulas (MathML) e diagramas (SVG).
+    static SANITIZER: ammonia::Builder<'static> = sanitizer();
+}
+
+/// Bloco de primeiro nível do documento (parágrafo, título, lista...) já
+/// convertido em HTML, ainda não sanitizado.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    /// Linha do texto (a partir de 0) onde o bloco começa.
+    pub line: usize,
+    pub html: String,
+}
+
+/// Extensões do Markdown usadas em todo o app.
+pub fn options() -> Options {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+    options.insert(Options::ENABLE_MATH);
+    options
+}
+
+/// Converte o Markdown bloco a bloco, guardando onde cada um começa no texto
+/// para sincronizar a rolagem. O documento é lido inteiro (referências de
+/// links e notas de rodapé continuam valendo entre blocos), mas cada bloco
+/// vira um HTML próprio, que só precisa ser sanitizado de novo se mudar.
+/// Os `[[links]]` apontam para as `notes`.
+pub fn blocks(src: &str, notes: &[wiki::NoteRef]) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut events = Vec::new();
+    let mut depth = 0usize;
+    let mut block_start = 0;
+    // linhas contadas até `counted`, para não recontar o texto a cada bloco
+    let (mut counted, mut line) = (0, 0);
+    let mut tasks = 0;
+    for (event, range) in Parser::new_ext(src, options()).into_offset_iter() {
+        let event = match event {
+            Event::TaskListMarker(checked) => {
+                tasks += 1;
+                task_checkbox(tasks - 1, checked)
+            }
+            event => event,
+        };
+        if depth == 0 {
+            block_start = range.start;
+        }
+        match event {
+            Event::Start(_) => depth += 1,
+            Event::End(_) => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        events.push(event);
+        if depth == 0 {
+            line += src[counted..block_start.max(counted)].matches('\n').count();
+            counted = block_start.max(counted);
+            let mut out = String::new();
+            html::push_html(&mut out, render_extensions(wiki::render_links(events.drain(..), notes), true).into_iter());
+            blocks.push(Block { line, html: out });
+        }
+    }
+    blocks
+}
+
+/// Extensões sobre o HTML do pulldown-cmark: fórmulas (`$...$` e `$$...$$`)
+/// em MathML, blocos de código destacados por linguagem e, com `diagrams`,
+/// blocos `mermaid` desenhados em SVG (sem eles, ficam como código).
+pub fn render_extensions<'a>(events: impl IntoIterator<Item = Event<'a>>, diagrams: bool) -> Vec<Event<'a>> {
+    let mut out = Vec::new();
+    // linguagem e código do bloco cercado sendo lido
+    let mut block: Option<(String, String)> = None;
+    for event in events {
+        match (&mut block, event) {
+            (None, Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)))) => {
+                let language = info.split_whitespace().next().unwrap_or_default();
+                let handled = (diagrams && diagram::is_diagram(language))
+                    || highlight::language_class(language).is_some();
+                if handled {
+                    block = Some((language.to_string(), String::new()));
+                } else {
+                    out.push(Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))));
+                }
+            }
+            (Some((_, code)), Event::Text(text)) => code.push_str(&text),
+            (Some((language, code)), Event::End(TagEnd::CodeBlock)) => {
+                let html = if diagrams && diagram::is_diagram(language) {
+                    diagram::to_svg(code)
+                } else {
+                    let class = highlight::language_class(language).unwrap_or_default();
+                    let body = highlight::highlight(language, code).unwrap_or_default();
+                    format!("<pre><code class=\"{}\">{}</code></pre>\n", class, body)
+                };
+                out.push(Event::Html(html.into()));
+                block = None;
+            }
+            (Some(_), _) => {}
+            (None, Event::InlineMath(tex)) => out.push(Event::InlineHtml(math::to_mathml(&tex, false).into())),
+            (None, Event::DisplayMath(tex)) => out.push(Event::InlineHtml(math::to_mathml(&tex, true).into())),
+            (None, event) => out.push(event),
+        }
+    }
+    out
+}
+
+/// Caixa de uma tarefa (`- [ ]`), marcada com a posição dela entre as
+/// tarefas do documento para que o clique saiba qual linha alterar.
+pub fn task_checkbox(index: usize, checked: bool) -> Event<'static> {
+    let checked = if checked { " checked" } else { "" };
+    Event::InlineHtml(format!("<input type=\"checkbox\" data-task=\"{}\"{}>", index, checked).into())
+}
+
+/// As caixas de tarefa de `events` numeradas a partir de `first` (um trecho
+/// que não começa no início da nota).
+pub fn task_checkboxes<'a>(events: impl IntoIterator<Item = Event<'a>>, first: usize) -> impl Iterator<Item = Event<'a>> {
+    let mut next = first;
+    events.into_iter().map(move |event| match event {
+        Event::TaskListMarker(checked) => {
+            next += 1;
+            task_checkbox(next - 1, checked)
+        }
+        event => event,
+    })
+}
+
+/// Posição de cada `[ ]`/`[x]` de tarefa no texto e se está marcada, na
+/// ordem do documento (só as que o Markdown reconhece como tarefa).
+fn task_markers(src: &str) -> Vec<(usize, bool)> {
+    Parser::new_ext(src, options()).into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::TaskListMarker(checked) => Some((range.start, checked)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Tarefas que existem antes da posição `offset` do texto.
+pub fn tasks_before(src: &str, offset: usize) -> usize {
+    task_markers(src).iter().filter(|&&(start, _)| start < offset).count()
+}
+
+/// O texto com a tarefa `index` marcada ou desmarcada; `None` se ela não
+/// existir.
+pub fn toggle_task(src: &str, index: usize) -> Option<String> {
+    let &(start, checked) = task_markers(src).get(index)?;
+    // a marca fica entre os colchetes: `[ ]`, `[x]` ou `[X]`
+    let mark = start + 1;
+    if src.as_bytes().get(start) != Some(&b'[') || !src.is_char_boundary(mark + 1) {
+        return None;
+    }
+    let mut out = String::with_capacity(src.len());
+    out.push_str(&src[..mark]);
+    out.push(if checked { ' ' } else { 'x' });
+    out.push_str(&src[mark + 1..]);
+    Some(out)
+}
+
+/// Tarefas concluídas e total; `None` se o texto não tiver tarefas.
+pub fn task_progress(src: &str) -> Option<(usize, usize)> {
+    // evita ler as notas que não podem ter tarefas
+    if !src.contains('[') {
+        return None;
+    }
+    let markers = task_markers(src);
+    let done = markers.iter().filter(|&&(_, checked)| checked).count();
+    (!markers.is_empty()).then_some((done, markers.len()))
+}
+
+/// Escapa `&`, `<`, `>` e `"` para inserir texto no HTML gerado, dentro de
+/// elementos ou de atributos entre aspas.
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn sanitizer() -> ammonia::Builder<'static> {
+    let mut classes: HashMap<&str, HashSet<&str>> = HashMap::new();
+    classes.insert("span", highlight::CLASSES.into_iter().collect());
+    classes.insert("code", highlight::language_classes().collect());
+    classes.insert("a", ["wiki-link", "wiki-missing"].into_iter().collect());
+    let mut builder = ammonia::Builder::default();
+    builder.allowed_classes(classes)
+        .add_tags(math::TAGS)
+        .add_tags(diagram::TAGS)
+        // caixas das tarefas: só `checkbox`, qualquer outro tipo vira ele
+        .add_tags(["input"])
+        .add_tag_attributes("input", ["checked", "data-task"])
+        .set_tag_attribute_value("input", "type", "checkbox")
+        .add_tag_attributes("a", ["data-note"]);
+    for &tag in math::TAGS {
+        builder.add_tag_attributes(tag, math::ATTRIBUTES);
+    }
+    for &tag in diagram::TAGS {
+        builder.add_tag_attributes(tag, diagram::ATTRIBUTES);
+    }
+    builder
+}
+
+/// Remove do HTML gerado tudo o que não for formatação segura; passam só as
+/// classes do destaque de código, os elementos de fórmulas e diagramas (sem
+/// scripts, eventos nem links), as caixas das tarefas e os `[[links]]`.
+pub fn sanitize(html: &str) -> String {
+    SANITIZER.with(|sanitizer| sanitizer.clean(html).to_string())
+}
+
+/// Interpolação linear entre pontos `(x, y)` ordenados por `x`, fora deles
+/// o valor do ponto mais próximo. Converte linha do texto em posição da
+/// visualização (e, com os pares invertidos, o contrário).
+pub fn interpolate(points: &[(f64, f64)], x: f64) -> f64 {
+    let Some(&(first_x, first_y)) = points.first() else { return 0.0 };
+    if x <= first_x {
+        return first_y;
+    }
+    for pair in points.windows(2) {
+        let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+        if x <= x1 {
+            return if x1 > x0 { y0 + (y1 - y0) * (x - x0) / (x1 - x0) } else { y1 };
+        }
+    }
+    points.last().map_or(0.0, |&(_, y)| y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TASKS: &str = "- [ ] comprar pão\n- [x] lavar louça\n  - [X] aninhada\n  - [ ] outra\n";
+
+    #[test]
+    fn progress_counts_all_markers() {
+        assert_eq!(task_progress(TASKS), Some((2, 4)));
+        assert_eq!(task_progress("sem tarefas [link](x)"), None);
+        assert_eq!(task_progress("texto"), None);
+    }
+
+    #[test]
+    fn toggle_flips_each_marker() {
+        assert_eq!(toggle_task(TASKS, 0).unwrap(), TASKS.replacen("- [ ] comprar", "- [x] comprar", 1));
+        assert_eq!(toggle_task(TASKS, 1).unwrap(), TASKS.replacen("[x] lavar", "[ ] lavar", 1));
+        // `[X]` maiúsculo também está marcada
+        assert_eq!(toggle_task(TASKS, 2).unwrap(), TASKS.replacen("[X]", "[ ]", 1));
+        assert_eq!(toggle_task(TASKS, 3).unwrap(), TASKS.replacen("[ ] outra", "[x] outra", 1));
+        assert_eq!(toggle_task(TASKS, 4), None);
+    }
+
+    #[test]
+    fn code_blocks_have_no_tasks() {
+        let src = "```\n- [ ] no código\n```\n\n    - [ ] indentado\n\n- [ ] real\n";
+        assert_eq!(task_progress(src), Some((0, 1)));
+        assert_eq!(toggle_task(src, 0).unwrap(), src.replacen("- [ ] real", "- [x] real", 1));
+        assert_eq!(task_progress("`- [ ] inline`"), None);
+    }
+
+    #[test]
+    fn tasks_before_numbers_snippets() {
+        let offset = TASKS.find("  - [X]").unwrap();
+        assert_eq!(tasks_before(TASKS, 0), 0);
+        assert_eq!(tasks_before(TASKS, offset), 2);
+        assert_eq!(tasks_before(TASKS, TASKS.len()), 4);
+
+        // o trecho a partir de `offset` continua a numeração da nota
+        let snippet = &TASKS[offset..];
+        let html: String = task_checkboxes(Parser::new_ext(snippet, options()), tasks_before(TASKS, offset))
+            .filter_map(|event| match event {
+                Event::InlineHtml(html) => Some(html.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(html, "<input type=\"checkbox\" data-task=\"2\" checked><input type=\"checkbox\" data-task=\"3\">");
+    }
+
+    #[test]
+    fn blocks_number_tasks_across_blocks() {
+        let html: String = blocks("- [ ] a\n\ntexto\n\n- [x] b\n", &[]).into_iter().map(|b| b.html).collect();
+        assert!(html.contains("data-task=\"0\">"));
+        assert!(html.contains("data-task=\"1\" checked>"));
+    }
+
+    #[test]
+    fn sanitize_keeps_only_the_allowed_classes_and_attributes() {
+        assert_eq!(
+            sanitize("<pre><code class=\"language-rust\"><span class=\"hl-keyword\">fn</span></code></pre>"),
+            "<pre><code class=\"language-rust\"><span class=\"hl-keyword\">fn</span></code></pre>",
+        );
+        assert_eq!(
+            sanitize("<span class=\"hl-string evil\">x</span><code class=\"hl-string\">y</code>"),
+            "<span class=\"hl-string\">x</span><code class=\"\">y</code>",
+        );
+        assert_eq!(
+            sanitize("<p onclick=\"alert(1)\" style=\"color: red\" class=\"destaque\">oi</p>"),
+            "<p>oi</p>",
+        );
+    }
+
+    #[test]
+    fn sanitize_keeps_mathml_and_svg_without_scripts() {
+        let math = "<math display=\"block\"><mfrac><mi>a</mi><mn>2</mn></mfrac></math>";
+        assert_eq!(sanitize(math), math);
+        assert_eq!(
+            sanitize("<math onload=\"alert(1)\"><mi style=\"x\">a</mi></math>"),
+            "<math><mi>a</mi></math>",
+        );
+
+        let svg = "<svg viewBox=\"0 0 10 10\"><rect x=\"1\" y=\"1\" width=\"8\" height=\"8\" fill=\"none\"></rect></svg>";
+        assert_eq!(sanitize(svg), svg);
+        assert_eq!(
+            sanitize("<svg onload=\"alert(1)\"><script>alert(2)</script><text x=\"1\" onclick=\"f()\">t</text></svg>"),
+            "<svg><text x=\"1\">t</text></svg>",
+        );
+    }
+
+    #[test]
+    fn escape_html_covers_text_and_attributes() {
+        assert_eq!(escape_html("<a href=\"x\">&</a>"), "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;");
+        assert_eq!(escape_html("&amp; já escapado"), "&amp;amp; já escapado");
+        assert_eq!(escape_html("'ação'"), "'ação'");
+    }
+}
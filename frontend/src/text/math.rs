@@ -0,0 +1,427 @@
+// src/text/math.rs
+use crate::text::markdown::escape_html;
+
+/// Elementos MathML gerados (liberados na sanitização do Markdown).
+pub const TAGS: &[&str] = &[
+    "math", "mrow", "mi", "mn", "mo", "mtext", "mspace", "msub", "msup", "msubsup", "mfrac", "msqrt",
+    "mroot", "munder", "mover", "munderover", "mtable", "mtr", "mtd", "merror", "mstyle",
+];
+/// Atributos dos elementos MathML gerados.
+pub const ATTRIBUTES: &[&str] = &["xmlns", "display", "mathvariant", "stretchy", "largeop", "accent", "width"];
+
+const GREEK: &[(&str, &str)] = &[
+    ("alpha", "α"), ("beta", "β"), ("gamma", "γ"), ("delta", "δ"), ("epsilon", "ϵ"), ("varepsilon", "ε"),
+    ("zeta", "ζ"), ("eta", "η"), ("theta", "θ"), ("vartheta", "ϑ"), ("iota", "ι"), ("kappa", "κ"),
+    ("lambda", "λ"), ("mu", "μ"), ("nu", "ν"), ("xi", "ξ"), ("pi", "π"), ("rho", "ρ"), ("sigma", "σ"),
+    ("tau", "τ"), ("upsilon", "υ"), ("phi", "ϕ"), ("varphi", "φ"), ("chi", "χ"), ("psi", "ψ"),
+    ("omega", "ω"), ("Gamma", "Γ"), ("Delta", "Δ"), ("Theta", "Θ"), ("Lambda", "Λ"), ("Xi", "Ξ"),
+    ("Pi", "Π"), ("Sigma", "Σ"), ("Phi", "Φ"), ("Psi", "Ψ"), ("Omega", "Ω"),
+];
+
+const OPERATORS: &[(&str, &str)] = &[
+    ("pm", "±"), ("mp", "∓"), ("times", "×"), ("div", "÷"), ("cdot", "⋅"), ("ast", "∗"), ("circ", "∘"),
+    ("leq", "≤"), ("le", "≤"), ("geq", "≥"), ("ge", "≥"), ("neq", "≠"), ("ne", "≠"), ("approx", "≈"),
+    ("equiv", "≡"), ("sim", "∼"), ("propto", "∝"), ("in", "∈"), ("notin", "∉"), ("subset", "⊂"),
+    ("subseteq", "⊆"), ("supset", "⊃"), ("cup", "∪"), ("cap", "∩"), ("setminus", "∖"), ("forall", "∀"),
+    ("exists", "∃"), ("neg", "¬"), ("land", "∧"), ("lor", "∨"), ("to", "→"), ("rightarrow", "→"),
+    ("leftarrow", "←"), ("Rightarrow", "⇒"), ("Leftarrow", "⇐"), ("leftrightarrow", "↔"),
+    ("Leftrightarrow", "⇔"), ("iff", "⟺"), ("implies", "⟹"), ("mapsto", "↦"), ("partial", "∂"),
+    ("nabla", "∇"), ("infty", "∞"), ("emptyset", "∅"), ("ldots", "…"), ("cdots", "⋯"), ("vdots", "⋮"),
+    ("ddots", "⋱"), ("langle", "⟨"), ("rangle", "⟩"), ("lfloor", "⌊"), ("rfloor", "⌋"), ("lceil", "⌈"),
+    ("rceil", "⌉"), ("mid", "∣"), ("perp", "⊥"), ("angle", "∠"), ("degree", "°"), ("{", "{"), ("}", "}"),
+    ("|", "‖"),
+];
+
+/// Operadores grandes: em bloco, limites ficam embaixo e em cima.
+const BIG_OPERATORS: &[(&str, &str)] = &[
+    ("sum", "∑"), ("prod", "∏"), ("coprod", "∐"), ("int", "∫"), ("iint", "∬"), ("iiint", "∭"),
+    ("oint", "∮"), ("bigcup", "⋃"), ("bigcap", "⋂"),
+];
+
+const FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh",
+    "log", "ln", "lg", "exp", "det", "dim", "gcd", "deg", "arg", "ker",
+];
+/// Funções com limites embaixo em bloco (`\lim_{x \to 0}`).
+const LIMIT_FUNCTIONS: &[&str] = &["lim", "max", "min", "sup", "inf", "limsup", "liminf"];
+
+const SPACES: &[(&str, &str)] = &[
+    (",", "0.167em"), (":", "0.222em"), (";", "0.278em"), (" ", "0.25em"), ("quad", "1em"),
+    ("qquad", "2em"), ("!", "-0.167em"),
+];
+
+/// Converte uma fórmula LaTeX (o subconjunto usado em notas: frações, raízes,
+/// índices, letras gregas, operadores e matrizes) em MathML. Comandos
+/// desconhecidos aparecem marcados em vermelho, sem interromper o resto.
+pub fn to_mathml(tex: &str, display: bool) -> String {
+    let mut parser = MathParser { src: tex, pos: 0, display };
+    let body = parser.sequence(None);
+    format!(
+        "<math xmlns=\"http://www.w3.org/1998/Math/MathML\" display=\"{}\"><mrow>{}</mrow></math>",
+        if display { "block" } else { "inline" },
+        body
+    )
+}
+
+struct MathParser<'a> {
+    src: &'a str,
+    pos: usize,
+    display: bool,
+}
+
+/// Um item da fórmula já convertido, e se ele aceita limites embaixo/em cima.
+struct Atom {
+    mathml: String,
+    limits: bool,
+}
+
+impl Atom {
+    fn new(mathml: String) -> Self {
+        Self { mathml, limits: false }
+    }
+}
+
+impl<'a> MathParser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    /// Itens até `end` (`}`, `\right`, `\end`...) ou o fim do texto.
+    fn sequence(&mut self, end: Option<&str>) -> String {
+        let mut out = String::new();
+        loop {
+            self.skip_spaces();
+            if self.rest().is_empty() || end.is_some_and(|e| self.rest().starts_with(e)) {
+                break;
+            }
+            // separadores de matriz são tratados por quem chamou
+            if end == Some("\\end") && (self.rest().starts_with('&') || self.rest().starts_with("\\\\")) {
+                break;
+            }
+            if let Some(atom) = self.atom() {
+                out.push_str(&self.scripts(atom));
+            }
+        }
+        out
+    }
+
+    /// Índices `_` e `^` depois de um item.
+    fn scripts(&mut self, base: Atom) -> String {
+        let (mut sub, mut sup) = (None, None);
+        loop {
+            self.skip_spaces();
+            match self.peek() {
+                Some('_') if sub.is_none() => {
+                    self.bump();
+                    sub = Some(self.argument());
+                }
+                Some('^') if sup.is_none() => {
+                    self.bump();
+                    sup = Some(self.argument());
+                }
+                Some('\'') if sup.is_none() => {
+                    let mut primes = String::new();
+                    while self.peek() == Some('\'') {
+                        self.bump();
+                        primes.push('′');
+                    }
+                    sup = Some(format!("<mo>{}</mo>", primes));
+                }
+                _ => break,
+            }
+        }
+        let under = base.limits && self.display;
+        let base = base.mathml;
+        match (sub, sup) {
+            (None, None) => base,
+            (Some(b), None) if under => format!("<munder>{}{}</munder>", base, b),
+            (None, Some(t)) if under => format!("<mover>{}{}</mover>", base, t),
+            (Some(b), Some(t)) if under => format!("<munderover>{}{}{}</munderover>", base, b, t),
+            (Some(b), None) => format!("<msub>{}{}</msub>", base, b),
+            (None, Some(t)) => format!("<msup>{}{}</msup>", base, t),
+            (Some(b), Some(t)) => format!("<msubsup>{}{}{}</msubsup>", base, b, t),
+        }
+    }
+
+    /// Argumento de um comando: um grupo `{...}` ou um único item.
+    fn argument(&mut self) -> String {
+        self.skip_spaces();
+        if self.peek() == Some('{') {
+            self.bump();
+            let inner = self.sequence(Some("}"));
+            self.bump();
+            return format!("<mrow>{}</mrow>", inner);
+        }
+        match self.atom() {
+            Some(atom) => atom.mathml,
+            None => "<mrow></mrow>".to_string(),
+        }
+    }
+
+    /// Texto cru entre chaves (`\text{...}`, `\begin{...}`).
+    fn raw_group(&mut self) -> String {
+        self.skip_spaces();
+        if self.peek() != Some('{') {
+            return String::new();
+        }
+        self.bump();
+        let mut depth = 1;
+        let start = self.pos;
+        while let Some(c) = self.bump() {
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return self.src[start..self.pos - 1].to_string();
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.src[start..].to_string()
+    }
+
+    fn atom(&mut self) -> Option<Atom> {
+        let c = self.bump()?;
+        let atom = match c {
+            '\\' => return Some(self.command()),
+            '{' => {
+                let inner = self.sequence(Some("}"));
+                self.bump();
+                Atom::new(format!("<mrow>{}</mrow>", inner))
+            }
+            // fechamento sem abertura: ignorado
+            '}' => return None,
+            c if c.is_ascii_digit() || c == '.' => {
+                let start = self.pos - 1;
+                while self.peek().is_some_and(|d| d.is_ascii_digit() || d == '.') {
+                    self.bump();
+                }
+                Atom::new(format!("<mn>{}</mn>", escape_html(&self.src[start..self.pos])))
+            }
+            c if c.is_alphabetic() => Atom::new(format!("<mi>{}</mi>", escape_html(&c.to_string()))),
+            '(' | ')' | '[' | ']' | '|' => Atom::new(format!("<mo stretchy=\"false\">{}</mo>", c)),
+            '-' => Atom::new("<mo>−</mo>".to_string()),
+            '~' => Atom::new("<mspace width=\"0.25em\"></mspace>".to_string()),
+            c => Atom::new(format!("<mo>{}</mo>", escape_html(&c.to_string()))),
+        };
+        Some(atom)
+    }
+
+    fn command(&mut self) -> Atom {
+        // `\\`, `\,`, `\{`... têm um só caractere não alfabético
+        let name = match self.peek() {
+            Some(c) if !c.is_ascii_alphabetic() => {
+                self.bump();
+                c.to_string()
+            }
+            _ => {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+                    self.bump();
+                }
+                self.src[start..self.pos].to_string()
+            }
+        };
+        let lookup = |table: &[(&str, &'static str)]| table.iter().find(|(n, _)| *n == name).map(|(_, s)| *s);
+
+        if let Some(letter) = lookup(GREEK) {
+            return Atom::new(format!("<mi>{}</mi>", letter));
+        }
+        if let Some(op) = lookup(OPERATORS) {
+            return Atom::new(format!("<mo>{}</mo>", escape_html(op)));
+        }
+        if let Some(op) = lookup(BIG_OPERATORS) {
+            return Atom { mathml: format!("<mo largeop=\"true\">{}</mo>", op), limits: true };
+        }
+        if let Some(width) = lookup(SPACES) {
+            return Atom::new(format!("<mspace width=\"{}\"></mspace>", width));
+        }
+        if FUNCTIONS.contains(&name.as_str()) {
+            return Atom::new(format!("<mi mathvariant=\"normal\">{}</mi>", name));
+        }
+        if LIMIT_FUNCTIONS.contains(&name.as_str()) {
+            return Atom { mathml: format!("<mi mathvariant=\"normal\">{}</mi>", name), limits: true };
+        }
+        match name.as_str() {
+            "frac" | "dfrac" | "tfrac" => {
+                let num = self.argument();
+                let den = self.argument();
+                Atom::new(format!("<mfrac>{}{}</mfrac>", num, den))
+            }
+            "sqrt" => {
+                self.skip_spaces();
+                if self.peek() == Some('[') {
+                    self.bump();
+                    let index = self.sequence(Some("]"));
+                    self.bump();
+                    let radicand = self.argument();
+                    Atom::new(format!("<mroot>{}<mrow>{}</mrow></mroot>", radicand, index))
+                } else {
+                    Atom::new(format!("<msqrt>{}</msqrt>", self.argument()))
+                }
+            }
+            "text" | "textrm" | "mbox" | "operatorname" => {
+                let text = self.raw_group();
+                let tag = if name == "operatorname" { "mi mathvariant=\"normal\"" } else { "mtext" };
+                let close = if name == "operatorname" { "mi" } else { "mtext" };
+                Atom::new(format!("<{}>{}</{}>", tag, escape_html(&text), close))
+            }
+            "mathbf" | "mathrm" | "mathit" | "mathbb" | "mathcal" | "boldsymbol" => {
+                let variant = match name.as_str() {
+                    "mathbf" | "boldsymbol" => "bold",
+                    "mathrm" => "normal",
+                    "mathit" => "italic",
+                    "mathbb" => "double-struck",
+                    _ => "script",
+                };
+                let inner = self.argument();
+                Atom::new(format!("<mstyle mathvariant=\"{}\">{}</mstyle>", variant, inner))
+            }
+            "overline" | "bar" | "hat" | "vec" | "dot" | "tilde" => {
+                let accent = match name.as_str() {
+                    "overline" | "bar" => "¯",
+                    "hat" => "^",
+                    "vec" => "→",
+                    "dot" => "˙",
+                    _ => "~",
+                };
+                let inner = self.argument();
+                Atom::new(format!("<mover accent=\"true\">{}<mo>{}</mo></mover>", inner, accent))
+            }
+            "left" | "right" | "big" | "Big" | "bigg" | "Bigg" => {
+                self.skip_spaces();
+                let delimiter = match self.peek() {
+                    Some('\\') => {
+                        self.bump();
+                        self.command().mathml
+                    }
+                    Some('.') => {
+                        self.bump();
+                        String::new()
+                    }
+                    Some(_) => self.atom().map(|a| a.mathml).unwrap_or_default(),
+                    None => String::new(),
+                };
+                Atom::new(delimiter.replace("stretchy=\"false\"", "stretchy=\"true\""))
+            }
+            "begin" => self.environment(),
+            // quebra de linha fora de matriz e `\end` sem `\begin`
+            "\\" | "end" => {
+                if name == "end" {
+                    self.raw_group();
+                }
+                Atom::new(String::new())
+            }
+            _ => Atom::new(format!("<merror><mtext>\\{}</mtext></merror>", escape_html(&name))),
+        }
+    }
+
+    /// `\begin{matrix}` e variantes (`pmatrix`, `bmatrix`, `vmatrix`, `cases`).
+    fn environment(&mut self) -> Atom {
+        let env = self.raw_group();
+        let mut rows = Vec::new();
+        let mut cells = Vec::new();
+        loop {
+            cells.push(self.sequence(Some("\\end")));
+            if self.rest().starts_with('&') {
+                self.bump();
+            } else if self.rest().starts_with("\\\\") {
+                self.pos += 2;
+                rows.push(std::mem::take(&mut cells));
+            } else {
+                // `\end{...}` ou fim do texto
+                if self.rest().starts_with("\\end") {
+                    self.pos += 4;
+                    self.raw_group();
+                }
+                break;
+            }
+        }
+        if cells.iter().any(|c| !c.is_empty()) || rows.is_empty() {
+            rows.push(cells);
+        }
+        let table = format!(
+            "<mtable>{}</mtable>",
+            rows.iter()
+                .map(|row| format!(
+                    "<mtr>{}</mtr>",
+                    row.iter().map(|cell| format!("<mtd>{}</mtd>", cell)).collect::<String>()
+                ))
+                .collect::<String>()
+        );
+        let (open, close) = match env.as_str() {
+            "pmatrix" => ("(", ")"),
+            "bmatrix" => ("[", "]"),
+            "Bmatrix" | "cases" => ("{", ""),
+            "vmatrix" => ("|", "|"),
+            "Vmatrix" => ("‖", "‖"),
+            _ => ("", ""),
+        };
+        let close = if env == "Bmatrix" { "}" } else { close };
+        let fence = |d: &str| if d.is_empty() { String::new() } else { format!("<mo>{}</mo>", d) };
+        Atom::new(format!("<mrow>{}{}{}</mrow>", fence(open), table, fence(close)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(tex: &str) -> String {
+        let mathml = to_mathml(tex, false);
+        let start = mathml.find("<mrow>").unwrap() + "<mrow>".len();
+        mathml[start..mathml.len() - "</mrow></math>".len()].to_string()
+    }
+
+    #[test]
+    fn wraps_in_math_element() {
+        assert!(to_mathml("x", true).contains("display=\"block\""));
+        assert!(to_mathml("x", false).contains("display=\"inline\""));
+        assert_eq!(body("\\frac{1}{\\alpha}"), "<mfrac><mrow><mn>1</mn></mrow><mrow><mi>α</mi></mrow></mfrac>");
+    }
+
+    #[test]
+    fn escapes_operators() {
+        assert_eq!(body("a < b"), "<mi>a</mi><mo>&lt;</mo><mi>b</mi>");
+        assert_eq!(body("a & b"), "<mi>a</mi><mo>&amp;</mo><mi>b</mi>");
+        assert_eq!(body("\""), "<mo>&quot;</mo>");
+    }
+
+    #[test]
+    fn escapes_text() {
+        assert_eq!(body("\\text{a < b & \"c\"}"), "<mtext>a &lt; b &amp; &quot;c&quot;</mtext>");
+        assert_eq!(body("\\operatorname{<f>}"), "<mi mathvariant=\"normal\">&lt;f&gt;</mi>");
+    }
+
+    #[test]
+    fn unknown_commands_are_marked_and_parsing_goes_on() {
+        assert_eq!(body("\\foo + 1"), "<merror><mtext>\\foo</mtext></merror><mo>+</mo><mn>1</mn>");
+        assert_eq!(body("\\<"), "<merror><mtext>\\&lt;</mtext></merror>");
+        assert_eq!(body("x\\"), "<mi>x</mi><merror><mtext>\\</mtext></merror>");
+    }
+
+    #[test]
+    fn unbalanced_groups_do_not_panic() {
+        assert_eq!(body("}x"), "<mi>x</mi>");
+        assert_eq!(body("\\frac{1"), "<mfrac><mrow><mn>1</mn></mrow><mrow></mrow></mfrac>");
+        assert!(body("\\begin{pmatrix} a & b \\\\ c").contains("<mtd><mi>c</mi></mtd>"));
+    }
+}
@@ -0,0 +1,169 @@
+// src/text/merge.rs
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+
+/// Resultado de uma mesclagem de três vias.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Merge {
+    pub text: String,
+    /// Quantidade de trechos alterados pelos dois lados de formas diferentes,
+    /// marcados no texto com `<<<<<<<` / `=======` / `>>>>>>>`.
+    pub conflicts: usize,
+}
+
+const MARK_LOCAL: &str = "<<<<<<< minha versão\n";
+const MARK_SPLIT: &str = "=======\n";
+const MARK_REMOTE: &str = ">>>>>>> versão remota\n";
+
+/// Trecho de `base` (linhas `start..end`) substituído por `lines` em um dos lados.
+struct Hunk<'a> {
+    start: usize,
+    end: usize,
+    lines: &'a [&'a str],
+}
+
+fn hunks<'a>(base: &[&str], side: &'a [&'a str]) -> Vec<Hunk<'a>> {
+    capture_diff_slices(Algorithm::Myers, base, side)
+        .into_iter()
+        .filter(|op| !matches!(op, DiffOp::Equal { .. }))
+        .map(|op| {
+            let (_, old, new) = op.as_tag_tuple();
+            Hunk { start: old.start, end: old.end, lines: &side[new] }
+        })
+        .collect()
+}
+
+/// Texto de um lado para o trecho `start..end` de `base`, aplicando seus hunks.
+fn side_text(base: &[&str], hunks: &[Hunk], start: usize, end: usize) -> String {
+    let mut out = String::new();
+    let mut pos = start;
+    for hunk in hunks {
+        out.extend(base[pos..hunk.start].iter().copied());
+        out.extend(hunk.lines.iter().copied());
+        pos = hunk.end;
+    }
+    out.extend(base[pos..end].iter().copied());
+    out
+}
+
+fn push_block(out: &mut String, block: &str) {
+    out.push_str(block);
+    if !block.is_empty() && !block.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+/// Mescla linha a linha as alterações de `local` e `remote` feitas sobre `base`.
+/// Trechos alterados só por um lado são aplicados; trechos que se sobrepõem
+/// (ou se tocam) e diferem viram conflitos marcados.
+pub fn merge3(base: &str, local: &str, remote: &str) -> Merge {
+    let base: Vec<&str> = base.split_inclusive('\n').collect();
+    let local: Vec<&str> = local.split_inclusive('\n').collect();
+    let remote: Vec<&str> = remote.split_inclusive('\n').collect();
+    let ours = hunks(&base, &local);
+    let theirs = hunks(&base, &remote);
+
+    let mut text = String::new();
+    let mut conflicts = 0;
+    let mut pos = 0;
+    let (mut i, mut j) = (0, 0);
+
+    while i < ours.len() || j < theirs.len() {
+        // começa pelo hunk mais cedo e absorve todos que o tocam
+        let start = match (ours.get(i), theirs.get(j)) {
+            (Some(a), Some(b)) => a.start.min(b.start),
+            (Some(a), None) => a.start,
+            (None, Some(b)) => b.start,
+            (None, None) => unreachable!(),
+        };
+        let mut end = start;
+        let (i0, j0) = (i, j);
+        loop {
+            if let Some(a) = ours.get(i).filter(|a| a.start <= end) {
+                end = end.max(a.end);
+                i += 1;
+            } else if let Some(b) = theirs.get(j).filter(|b| b.start <= end) {
+                end = end.max(b.end);
+                j += 1;
+            } else {
+                break;
+            }
+        }
+
+        text.extend(base[pos..start].iter().copied());
+        let (mine, other) = (&ours[i0..i], &theirs[j0..j]);
+        if other.is_empty() {
+            text.push_str(&side_text(&base, mine, start, end));
+        } else if mine.is_empty() {
+            text.push_str(&side_text(&base, other, start, end));
+        } else {
+            let mine = side_text(&base, mine, start, end);
+            let other = side_text(&base, other, start, end);
+            if mine == other {
+                text.push_str(&mine);
+            } else {
+                conflicts += 1;
+                text.push_str(MARK_LOCAL);
+                push_block(&mut text, &mine);
+                text.push_str(MARK_SPLIT);
+                push_block(&mut text, &other);
+                text.push_str(MARK_REMOTE);
+            }
+        }
+        pos = end;
+    }
+    text.extend(base[pos..].iter().copied());
+
+    Merge { text, conflicts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_changes_from_both_sides() {
+        let base = "um\ndois\ntrês\nquatro\n";
+        let local = "UM\ndois\ntrês\nquatro\n";
+        let remote = "um\ndois\ntrês\nQUATRO\n";
+        assert_eq!(merge3(base, local, remote), Merge { text: "UM\ndois\ntrês\nQUATRO\n".to_string(), conflicts: 0 });
+    }
+
+    #[test]
+    fn same_change_on_both_sides_is_not_a_conflict() {
+        let base = "a\nb\n";
+        let merged = merge3(base, "a\nB\n", "a\nB\n");
+        assert_eq!(merged, Merge { text: "a\nB\n".to_string(), conflicts: 0 });
+    }
+
+    #[test]
+    fn insertions_and_deletions_merge() {
+        let base = "a\nb\nc\n";
+        let merged = merge3(base, "novo\na\nb\nc\n", "a\nc\n");
+        assert_eq!(merged, Merge { text: "novo\na\nc\n".to_string(), conflicts: 0 });
+    }
+
+    #[test]
+    fn overlapping_edits_are_marked() {
+        let base = "título\nlinha\nfim\n";
+        let merged = merge3(base, "título\nminha linha\nfim\n", "título\nlinha remota\nfim\n");
+        assert_eq!(merged.conflicts, 1);
+        assert_eq!(
+            merged.text,
+            "título\n<<<<<<< minha versão\nminha linha\n=======\nlinha remota\n>>>>>>> versão remota\nfim\n",
+        );
+    }
+
+    #[test]
+    fn conflict_without_trailing_newline_keeps_markers_on_own_lines() {
+        let merged = merge3("a", "b", "c");
+        assert_eq!(merged.conflicts, 1);
+        assert_eq!(merged.text, "<<<<<<< minha versão\nb\n=======\nc\n>>>>>>> versão remota\n");
+    }
+
+    #[test]
+    fn unchanged_side_takes_the_other() {
+        let base = "x\ny\n";
+        assert_eq!(merge3(base, base, "z\n").text, "z\n");
+        assert_eq!(merge3(base, "", base).text, "");
+    }
+}
@@ -0,0 +1,11 @@
+// src/text/mod.rs
+pub mod crdt;
+pub mod diagram;
+pub mod diff;
+pub mod format;
+pub mod highlight;
+pub mod markdown;
+pub mod math;
+pub mod merge;
+pub mod search;
+pub mod wiki;
@@ -0,0 +1,209 @@
+// src/text/search.rs
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+
+/// Peso de um termo do título em relação a um do conteúdo.
+const TITLE_WEIGHT: u32 = 3;
+
+/// Índice invertido das notas (título e conteúdo) para a busca da barra
+/// lateral.
+///
+/// Os termos são normalizados por `fold` (minúsculas, sem acentos), então
+/// "ação" encontra "acao" e vice-versa. Cada termo da consulta casa com os
+/// termos que começam por ele, e a nota precisa conter todos.
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    /// termo → nota → peso
+    postings: BTreeMap<String, HashMap<String, u32>>,
+    /// termos de cada nota, para removê-los quando ela muda
+    terms: HashMap<String, Vec<String>>,
+}
+
+impl SearchIndex {
+    /// Indexa a nota, substituindo a versão anterior.
+    pub fn upsert(&mut self, id: &str, title: &str, content: &str) {
+        self.remove(id);
+        let mut weights: HashMap<String, u32> = HashMap::new();
+        for (_, term) in tokens(title) {
+            *weights.entry(term).or_default() += TITLE_WEIGHT;
+        }
+        for (_, term) in tokens(content) {
+            *weights.entry(term).or_default() += 1;
+        }
+        for (term, weight) in &weights {
+            self.postings.entry(term.clone()).or_default().insert(id.to_string(), *weight);
+        }
+        self.terms.insert(id.to_string(), weights.into_keys().collect());
+    }
+
+    pub fn remove(&mut self, id: &str) {
+        for term in self.terms.remove(id).unwrap_or_default() {
+            if let Some(notes) = self.postings.get_mut(&term) {
+                notes.remove(id);
+                if notes.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+    }
+
+    /// Ids das notas que contêm todos os termos da consulta, das mais
+    /// relevantes para as menos. Termos exatos valem mais que prefixos.
+    pub fn search(&self, query: &str) -> Vec<String> {
+        let terms = query_terms(query);
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        let mut scores: HashMap<&str, (usize, u32)> = HashMap::new();
+        for (i, term) in terms.iter().enumerate() {
+            let matches = self.postings.range(term.clone()..).take_while(|(k, _)| k.starts_with(term.as_str()));
+            for (key, notes) in matches {
+                let bonus = if key == term { 2 } else { 1 };
+                for (id, weight) in notes {
+                    let entry = scores.entry(id).or_insert((0, 0));
+                    // conta cada termo da consulta uma vez por nota
+                    if entry.0 == i {
+                        entry.0 = i + 1;
+                    }
+                    if entry.0 == i + 1 {
+                        entry.1 += weight * bonus;
+                    }
+                }
+            }
+        }
+
+        let mut hits: Vec<(&str, u32)> = scores.into_iter()
+            .filter(|(_, (matched, _))| *matched == terms.len())
+            .map(|(id, (_, score))| (id, score))
+            .collect();
+        hits.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        hits.into_iter().map(|(id, _)| id.to_string()).collect()
+    }
+}
+
+/// Termos normalizados e sem repetição da consulta.
+pub fn query_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for (_, term) in tokens(query) {
+        if !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+    terms
+}
+
+/// Trechos (em bytes) de `text` com palavras que começam por algum dos
+/// `terms`, para destacar o resultado da busca.
+pub fn match_ranges(text: &str, terms: &[String]) -> Vec<Range<usize>> {
+    if terms.is_empty() {
+        return Vec::new();
+    }
+    tokens(text)
+        .filter(|(_, word)| terms.iter().any(|t| word.starts_with(t.as_str())))
+        .map(|(range, _)| range)
+        .collect()
+}
+
+/// Palavras (letras e dígitos) de `text` com sua posição, já normalizadas.
+fn tokens(text: &str) -> impl Iterator<Item = (Range<usize>, String)> + '_ {
+    let mut chars = text.char_indices().peekable();
+    std::iter::from_fn(move || {
+        while chars.next_if(|(_, c)| !c.is_alphanumeric()).is_some() {}
+        let (start, _) = *chars.peek()?;
+        let mut end = start;
+        while let Some((i, c)) = chars.next_if(|(_, c)| c.is_alphanumeric()) {
+            end = i + c.len_utf8();
+        }
+        Some((start..end, fold(&text[start..end])))
+    })
+}
+
+/// Minúsculas sem acentos: "Coração" → "coracao".
+pub fn fold(word: &str) -> String {
+    word.chars()
+        .flat_map(char::to_lowercase)
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ã' | 'ä' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'í' | 'ì' | 'î' | 'ï' => 'i',
+            'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+            'ú' | 'ù' | 'û' | 'ü' => 'u',
+            'ç' => 'c',
+            'ñ' => 'n',
+            other => other,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(query: &str) -> Vec<String> {
+        query_terms(query)
+    }
+
+    #[test]
+    fn folds_case_and_accents() {
+        assert_eq!(fold("Coração"), "coracao");
+        assert_eq!(fold("ÁÉÍÓÚ Ñ"), "aeiou n");
+        assert_eq!(query_terms("Ação, acao  AÇÃO!"), ["acao"]);
+    }
+
+    #[test]
+    fn accents_match_both_ways() {
+        let mut index = SearchIndex::default();
+        index.upsert("1", "Receita de pão", "farinha e água");
+        index.upsert("2", "Lista", "comprar pao");
+        assert_eq!(index.search("pão"), ["1", "2"]);
+        assert_eq!(index.search("agua"), ["1"]);
+    }
+
+    #[test]
+    fn ranks_title_and_exact_terms_higher() {
+        let mut index = SearchIndex::default();
+        index.upsert("conteudo", "Outra", "rust");
+        index.upsert("titulo", "Rust", "");
+        index.upsert("prefixo", "Rustacean", "");
+        assert_eq!(index.search("rust"), ["titulo", "prefixo", "conteudo"]);
+    }
+
+    #[test]
+    fn requires_every_term_and_forgets_removed_notes() {
+        let mut index = SearchIndex::default();
+        index.upsert("1", "café", "com leite");
+        index.upsert("2", "café", "preto");
+        assert_eq!(index.search("cafe leite"), ["1"]);
+        assert!(index.search("cafe chá").is_empty());
+        assert!(index.search("  ").is_empty());
+
+        index.upsert("1", "chá", "");
+        assert_eq!(index.search("cafe"), ["2"]);
+        index.remove("2");
+        assert!(index.search("cafe").is_empty());
+        assert_eq!(index.search("cha"), ["1"]);
+    }
+
+    #[test]
+    fn match_ranges_are_char_boundaries_in_multibyte_text() {
+        let text = "São Paulo — Ação: coração 😀ação";
+        let ranges = match_ranges(text, &terms("acao sao"));
+        let words: Vec<&str> = ranges.iter().map(|r| &text[r.clone()]).collect();
+        assert_eq!(words, ["São", "Ação", "ação"]);
+        for range in &ranges {
+            assert!(text.is_char_boundary(range.start) && text.is_char_boundary(range.end));
+        }
+    }
+
+    #[test]
+    fn match_ranges_use_prefixes_only() {
+        let text = "coração e ação";
+        let words: Vec<&str> = match_ranges(text, &terms("cora"))
+            .into_iter()
+            .map(|r| &text[r])
+            .collect();
+        assert_eq!(words, ["coração"]);
+        assert!(match_ranges(text, &[]).is_empty());
+    }
+}
@@ -0,0 +1,233 @@
+// src/text/wiki.rs
+use std::ops::Range;
+use pulldown_cmark::{CowStr, Event, Parser, Tag, TagEnd};
+use crate::text::format::Edit;
+use crate::text::markdown::{self, escape_html};
+
+/// Sugestões mostradas no autocompletar de `[[`.
+pub const MAX_SUGGESTIONS: usize = 8;
+
+/// Nota que pode ser apontada por um `[[link]]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteRef {
+    pub id: String,
+    pub title: String,
+}
+
+/// `[[Título]]` ou `[[Título|texto]]` num trecho de texto.
+struct WikiLink<'a> {
+    range: Range<usize>,
+    title: &'a str,
+    label: &'a str,
+}
+
+/// Links completos de uma linha de texto, na ordem.
+fn find_links(text: &str) -> Vec<WikiLink<'_>> {
+    let mut links = Vec::new();
+    let mut pos = 0;
+    while let Some(open) = text[pos..].find("[[").map(|i| pos + i) {
+        let inner_start = open + 2;
+        let Some(close) = text[inner_start..].find("]]").map(|i| inner_start + i) else { break };
+        let inner = &text[inner_start..close];
+        // `[[a [[b]]`: vale o último `[[`
+        if let Some(nested) = inner.rfind("[[") {
+            pos = inner_start + nested;
+            continue;
+        }
+        let (title, label) = inner.split_once('|').unwrap_or((inner, inner));
+        if !title.trim().is_empty() && !inner.contains('\n') {
+            links.push(WikiLink { range: open..close + 2, title: title.trim(), label: label.trim() });
+        }
+        pos = close + 2;
+    }
+    links
+}
+
+/// A nota com esse título (sem diferença entre maiúsculas e minúsculas).
+pub fn resolve<'a>(notes: &'a [NoteRef], title: &str) -> Option<&'a NoteRef> {
+    let title = title.trim().to_lowercase();
+    notes.iter().find(|n| n.title.trim().to_lowercase() == title)
+}
+
+/// Junta os textos seguidos (o pulldown-cmark separa `[` e `]` em eventos
+/// próprios) fora de código e de links, e chama `on_text` com cada um; os
+/// outros eventos vão para `on_event`.
+fn text_runs<'a>(
+    events: impl IntoIterator<Item = Event<'a>>,
+    mut on_text: impl FnMut(String),
+    mut on_event: impl FnMut(Event<'a>),
+) {
+    let mut run = String::new();
+    // dentro de bloco de código ou de link, o texto fica como está
+    let mut literal = 0usize;
+    for event in events {
+        match event {
+            Event::Text(text) if literal == 0 => {
+                run.push_str(&text);
+                continue;
+            }
+            Event::Start(Tag::CodeBlock(_) | Tag::Link { .. } | Tag::Image { .. }) => literal += 1,
+            Event::End(TagEnd::CodeBlock | TagEnd::Link | TagEnd::Image) => literal = literal.saturating_sub(1),
+            _ => {}
+        }
+        if !run.is_empty() {
+            on_text(std::mem::take(&mut run));
+        }
+        on_event(event);
+    }
+    if !run.is_empty() {
+        on_text(run);
+    }
+}
+
+/// Troca os `[[Título]]` do texto por links para a nota (`data-note` com o
+/// id, que o clique usa para abrir); títulos sem nota ficam marcados.
+pub fn render_links<'a>(events: impl IntoIterator<Item = Event<'a>>, notes: &[NoteRef]) -> Vec<Event<'a>> {
+    let out = std::cell::RefCell::new(Vec::new());
+    text_runs(events, |text| {
+        let mut out = out.borrow_mut();
+        let mut pos = 0;
+        for link in find_links(&text) {
+            if link.range.start > pos {
+                out.push(Event::Text(CowStr::from(text[pos..link.range.start].to_string())));
+            }
+            let open = match resolve(notes, link.title) {
+                Some(note) => format!(
+                    "<a href=\"/dashboard/note/{0}\" class=\"wiki-link\" data-note=\"{0}\">",
+                    escape_html(&note.id)
+                ),
+                None => "<a class=\"wiki-link wiki-missing\" title=\"Nota não encontrada\">".to_string(),
+            };
+            out.push(Event::InlineHtml(open.into()));
+            out.push(Event::Text(CowStr::from(link.label.to_string())));
+            out.push(Event::InlineHtml(CowStr::Borrowed("</a>")));
+            pos = link.range.end;
+        }
+        if pos < text.len() {
+            out.push(Event::Text(CowStr::from(text[pos..].to_string())));
+        }
+    }, |event| out.borrow_mut().push(event));
+    out.into_inner()
+}
+
+/// Títulos apontados pelos `[[links]]` do texto (fora de código).
+pub fn links(src: &str) -> Vec<String> {
+    let mut titles = Vec::new();
+    // evita ler as notas que não podem ter links
+    if !src.contains("[[") {
+        return titles;
+    }
+    text_runs(Parser::new_ext(src, markdown::options()), |text| {
+        titles.extend(find_links(&text).into_iter().map(|l| l.title.to_string()));
+    }, drop);
+    titles
+}
+
+/// `[[` ainda aberto antes do cursor na mesma linha: onde ele começa e o
+/// que já foi digitado depois dele.
+pub fn open_link(text: &str, caret: usize) -> Option<(usize, &str)> {
+    let line_start = text[..caret].rfind('\n').map_or(0, |i| i + 1);
+    let start = line_start + text[line_start..caret].rfind("[[")?;
+    let query = &text[start + 2..caret];
+    (!query.contains(['[', ']', '|']) && query.chars().count() <= 100).then_some((start, query))
+}
+
+/// Notas cujo título contém `query`, as que começam com ele primeiro, sem a
+/// própria nota (`exclude`).
+pub fn suggestions<'a>(notes: &'a [NoteRef], query: &str, exclude: Option<&str>) -> Vec<&'a NoteRef> {
+    let query = query.trim().to_lowercase();
+    let mut found: Vec<(bool, &NoteRef)> = notes.iter()
+        .filter(|n| !n.title.trim().is_empty() && Some(n.id.as_str()) != exclude)
+        .filter_map(|n| {
+            let title = n.title.to_lowercase();
+            title.contains(&query).then(|| (!title.starts_with(&query), n))
+        })
+        .collect();
+    found.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.title.cmp(&b.1.title)));
+    found.into_iter().map(|(_, n)| n).take(MAX_SUGGESTIONS).collect()
+}
+
+/// Completa o `[[` aberto em `start` (digitado até `caret`) com o título;
+/// o cursor vai para depois do `]]`.
+pub fn complete(text: &str, start: usize, caret: usize, title: &str) -> Edit {
+    let rest = text[caret..].strip_prefix("]]").unwrap_or(&text[caret..]);
+    let link = format!("[[{}]]", title);
+    let end = start + link.len();
+    Edit { text: format!("{}{}{}", &text[..start], link, rest), start: end, end }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark::html;
+
+    fn notes() -> Vec<NoteRef> {
+        vec![
+            NoteRef { id: "1".to_string(), title: "Nota A".to_string() },
+            NoteRef { id: "2".to_string(), title: " Receitas ".to_string() },
+        ]
+    }
+
+    fn render(src: &str) -> String {
+        let mut out = String::new();
+        html::push_html(&mut out, render_links(Parser::new_ext(src, markdown::options()), &notes()).into_iter());
+        out
+    }
+
+    #[test]
+    fn last_opening_wins() {
+        let text = "[[a [[b]]";
+        let links = find_links(text);
+        assert_eq!(links.len(), 1);
+        assert_eq!((links[0].title, links[0].range.clone()), ("b", 4..9));
+    }
+
+    #[test]
+    fn label_after_pipe() {
+        let links = find_links("ver [[Título | rótulo]] e [[Outra]]");
+        let found: Vec<_> = links.iter().map(|l| (l.title, l.label)).collect();
+        assert_eq!(found, vec![("Título", "rótulo"), ("Outra", "Outra")]);
+        assert!(find_links("[[]] [[ |x]]").is_empty());
+    }
+
+    #[test]
+    fn resolve_ignores_case_and_spaces() {
+        assert_eq!(resolve(&notes(), "  NOTA a ").map(|n| n.id.as_str()), Some("1"));
+        assert_eq!(resolve(&notes(), "receitas").map(|n| n.id.as_str()), Some("2"));
+        assert!(resolve(&notes(), "Nota").is_none());
+    }
+
+    #[test]
+    fn renders_links_to_notes() {
+        let html = render("Veja [[nota a|a primeira]] e [[Sumida]].");
+        assert!(html.contains("<a href=\"/dashboard/note/1\" class=\"wiki-link\" data-note=\"1\">a primeira</a>"));
+        assert!(html.contains("<a class=\"wiki-link wiki-missing\" title=\"Nota não encontrada\">Sumida</a>"));
+    }
+
+    #[test]
+    fn code_and_links_stay_literal() {
+        let html = render("`[[Nota A]]`\n\n```\n[[Nota A]]\n```\n\n[ver [[Nota A]]](https://x.y)");
+        assert!(!html.contains("wiki-link"));
+        assert_eq!(html.matches("[[Nota A]]").count(), 3);
+        assert_eq!(links("[[a]] `[[b]]` [[c|d]]\n\n```\n[[e]]\n```"), vec!["a", "c"]);
+    }
+
+    #[test]
+    fn open_link_before_caret() {
+        let text = "abc [[no";
+        assert_eq!(open_link(text, text.len()), Some((4, "no")));
+        assert_eq!(open_link("[[a]] x", 7), None);
+        assert_eq!(open_link("[[a\nb", 5), None);
+        assert_eq!(open_link("[[a|b", 5), None);
+        assert_eq!(open_link("ção [[é", 10), Some((6, "é")));
+    }
+
+    #[test]
+    fn complete_moves_caret_after_link() {
+        assert_eq!(complete("ver [[no]] fim", 4, 8, "Nota"), Edit { text: "ver [[Nota]] fim".to_string(), start: 12, end: 12 });
+        assert_eq!(complete("ver [[no", 4, 8, "Nota"), Edit { text: "ver [[Nota]]".to_string(), start: 12, end: 12 });
+        let done = complete("é [[", 3, 5, "Ação");
+        assert_eq!(done.text, "é [[Ação]]");
+        assert_eq!((done.start, done.end), (done.text.len(), done.text.len()));
+    }
+}
@@ -0,0 +1,176 @@
+// src/acl.rs
+use std::fmt;
+use crate::note::{Note, User};
+
+/// Papel de um usuário em uma nota.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Owner,
+    Editor,
+    Viewer,
+}
+
+impl Role {
+    pub fn can_edit(self) -> bool {
+        matches!(self, Role::Owner | Role::Editor)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Role::Owner => "Dono",
+            Role::Editor => "Pode editar",
+            Role::Viewer => "Pode ver",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShareError {
+    InvalidEmail,
+    /// O dono já tem acesso total à nota.
+    Owner,
+}
+
+impl fmt::Display for ShareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShareError::InvalidEmail => write!(f, "E-mail inválido."),
+            ShareError::Owner => write!(f, "Você já é o dono desta nota."),
+        }
+    }
+}
+
+impl std::error::Error for ShareError {}
+
+/// E-mails são comparados sem diferenciar maiúsculas.
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+impl Note {
+    /// Papel do usuário na nota, ou `None` se ele não tem acesso.
+    pub fn role_of(&self, user: &User) -> Option<Role> {
+        if self.user_id == user.uid {
+            return Some(Role::Owner);
+        }
+        let email = normalize_email(&user.email);
+        if self.editors.contains(&email) {
+            Some(Role::Editor)
+        } else if self.viewers.contains(&email) {
+            Some(Role::Viewer)
+        } else {
+            None
+        }
+    }
+
+    /// Dá (ou troca) o acesso de `email`; `role` não pode ser `Owner`.
+    pub fn share(&mut self, email: &str, role: Role) -> Result<(), ShareError> {
+        let email = normalize_email(email);
+        if !email.contains('@') || email.starts_with('@') || email.ends_with('@') || role == Role::Owner {
+            return Err(ShareError::InvalidEmail);
+        }
+        if email == normalize_email(&self.owner_email) {
+            return Err(ShareError::Owner);
+        }
+        self.unshare(&email);
+        match role {
+            Role::Editor => self.editors.push(email),
+            _ => self.viewers.push(email),
+        }
+        self.sync_shared_with();
+        Ok(())
+    }
+
+    pub fn unshare(&mut self, email: &str) {
+        let email = normalize_email(email);
+        self.editors.retain(|e| *e != email);
+        self.viewers.retain(|e| *e != email);
+        self.sync_shared_with();
+    }
+
+    /// Pessoas com quem a nota foi compartilhada e seus papéis.
+    pub fn shares(&self) -> Vec<(String, Role)> {
+        self.editors.iter().map(|e| (e.clone(), Role::Editor))
+            .chain(self.viewers.iter().map(|e| (e.clone(), Role::Viewer)))
+            .collect()
+    }
+
+    /// Se dono e compartilhamentos são os mesmos nas duas versões; editores
+    /// podem alterar a nota, mas não quem tem acesso a ela.
+    pub fn same_acl(&self, other: &Note) -> bool {
+        self.user_id == other.user_id
+            && self.owner_email == other.owner_email
+            && self.editors == other.editors
+            && self.viewers == other.viewers
+    }
+
+    fn sync_shared_with(&mut self) {
+        self.shared_with = self.editors.iter().chain(&self.viewers).cloned().collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+
+    fn user(uid: &str, email: &str) -> User {
+        User { uid: uid.to_string(), email: email.to_string() }
+    }
+
+    fn owned_note() -> Note {
+        let mut note = Note::new_with_clock(&FixedClock(0), "t".to_string(), "c".to_string(), "dono".to_string(),
+            String::new(), String::new(), String::new(), None);
+        note.owner_email = "Dono@Example.com".to_string();
+        note
+    }
+
+    #[test]
+    fn role_of_owner_editor_viewer_and_stranger() {
+        let mut note = owned_note();
+        note.share("editora@example.com", Role::Editor).unwrap();
+        note.share("leitor@example.com", Role::Viewer).unwrap();
+
+        assert_eq!(note.role_of(&user("dono", "")), Some(Role::Owner));
+        assert_eq!(note.role_of(&user("u2", " Editora@Example.COM ")), Some(Role::Editor));
+        assert_eq!(note.role_of(&user("u3", "leitor@example.com")), Some(Role::Viewer));
+        assert_eq!(note.role_of(&user("u4", "outro@example.com")), None);
+        // o e-mail do dono não dá acesso a outra conta
+        assert_eq!(note.role_of(&user("u5", "dono@example.com")), None);
+        assert!(Role::Editor.can_edit() && !Role::Viewer.can_edit());
+    }
+
+    #[test]
+    fn share_changes_role_and_keeps_shared_with_in_sync() {
+        let mut note = owned_note();
+        note.share("ana@example.com", Role::Viewer).unwrap();
+        note.share("ANA@example.com", Role::Editor).unwrap();
+        assert_eq!(note.editors, ["ana@example.com"]);
+        assert!(note.viewers.is_empty());
+        assert_eq!(note.shared_with, ["ana@example.com"]);
+        assert_eq!(note.shares(), [("ana@example.com".to_string(), Role::Editor)]);
+
+        note.unshare("ana@example.com");
+        assert!(note.editors.is_empty() && note.shared_with.is_empty());
+    }
+
+    #[test]
+    fn share_rejects_invalid_targets() {
+        let mut note = owned_note();
+        assert_eq!(note.share("sem-arroba", Role::Editor), Err(ShareError::InvalidEmail));
+        assert_eq!(note.share("@example.com", Role::Editor), Err(ShareError::InvalidEmail));
+        assert_eq!(note.share("ana@example.com", Role::Owner), Err(ShareError::InvalidEmail));
+        assert_eq!(note.share("dono@example.com", Role::Viewer), Err(ShareError::Owner));
+        assert!(note.shared_with.is_empty());
+    }
+
+    #[test]
+    fn same_acl_detects_sharing_changes() {
+        let note = owned_note();
+        let mut changed = note.clone();
+        changed.content = "outro".to_string();
+        assert!(note.same_acl(&changed));
+        changed.share("ana@example.com", Role::Editor).unwrap();
+        assert!(!note.same_acl(&changed));
+    }
+}
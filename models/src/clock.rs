@@ -0,0 +1,44 @@
+// src/clock.rs
+
+/// Um minuto em milissegundos.
+pub(crate) const MINUTE_MS: i64 = 60 * 1000;
+
+/// Um dia em milissegundos.
+pub(crate) const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// Fonte de tempo em milissegundos desde a época Unix.
+///
+/// Injetável para que servidor, CLI e testes não dependam de `js_sys::Date`.
+pub trait Clock {
+    fn now_millis(&self) -> i64;
+}
+
+/// Relógio do sistema: `Date.now()` no navegador, `SystemTime` no nativo.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[cfg(target_arch = "wasm32")]
+    fn now_millis(&self) -> i64 {
+        js_sys::Date::now() as i64
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn now_millis(&self) -> i64 {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0)
+    }
+}
+
+/// Relógio parado num instante fixo, para testes determinísticos.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock(pub i64);
+
+impl Clock for FixedClock {
+    fn now_millis(&self) -> i64 {
+        self.0
+    }
+}
@@ -0,0 +1,41 @@
+// src/collab.rs
+use serde::{Deserialize, Serialize};
+
+/// Uma change do documento colaborativo (automerge), identificada pelo hash.
+///
+/// Os backends guardam as changes sem interpretá-las; reenviar uma change já
+/// conhecida não tem efeito.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CollabChange {
+    pub hash: String,
+    pub data: Vec<u8>,
+}
+
+/// Participante editando a nota e a posição do seu cursor.
+///
+/// `anchor` e `head` são cursores do automerge serializados: continuam
+/// apontando para o mesmo caractere quando o texto muda antes deles.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Presence {
+    pub session: String,
+    pub user_id: String,
+    pub email: String,
+    #[serde(default)]
+    pub anchor: Option<String>,
+    #[serde(default)]
+    pub head: Option<String>,
+    pub updated_at: i64,
+}
+
+/// Mensagem trocada no canal de edição colaborativa de uma nota.
+///
+/// Ao entrar no canal, a primeira mensagem `Changes` recebida contém todas as
+/// changes já gravadas (possivelmente nenhuma).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum CollabMessage {
+    Changes { changes: Vec<CollabChange> },
+    Presence(Presence),
+    Leave { session: String },
+}
@@ -0,0 +1,328 @@
+// src/history.rs
+use serde::{Deserialize, Serialize};
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+use crate::clock::{DAY_MS, MINUTE_MS};
+use crate::note::NoteHistory;
+
+/// Quantas versões anteriores de uma nota guardar.
+///
+/// As versões recentes ficam todas; as mais antigas são espaçadas (uma por
+/// dia e, depois, uma por semana, sempre a última do período) e, passado o
+/// limite, as mais antigas saem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetentionPolicy {
+    pub max_versions: usize,
+    /// Versões com menos dias que isso ficam todas.
+    pub keep_all_days: i64,
+    /// Até esta idade fica uma versão por dia; depois, uma por semana.
+    pub daily_days: i64,
+    /// Versões gravadas com menos minutos que isso entre si viram uma só
+    /// (a mais nova), para o salvamento automático não criar uma por pausa.
+    pub snapshot_minutes: i64,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self { max_versions: 50, keep_all_days: 1, daily_days: 30, snapshot_minutes: 10 }
+    }
+}
+
+impl RetentionPolicy {
+    /// Versões (da mais antiga para a mais nova) que continuam guardadas em `now`.
+    pub fn retain(&self, versions: Vec<NoteHistory>, now: i64) -> Vec<NoteHistory> {
+        let mut kept = Vec::new();
+        let mut last_period = None;
+        for version in versions.into_iter().rev() {
+            if kept.len() >= self.max_versions {
+                break;
+            }
+            let age = (now - version.updated_at).max(0);
+            let period = if age < self.keep_all_days * DAY_MS {
+                None
+            } else if age < self.daily_days * DAY_MS {
+                Some((1, version.updated_at.div_euclid(DAY_MS)))
+            } else {
+                Some((7, version.updated_at.div_euclid(7 * DAY_MS)))
+            };
+            // vindo da mais nova, a primeira de cada período é a que fica
+            if period.is_some() && period == last_period {
+                continue;
+            }
+            last_period = period;
+            kept.push(version);
+        }
+        kept.reverse();
+        kept
+    }
+}
+
+/// Versões anteriores de uma nota, gravadas como diferenças.
+///
+/// A primeira versão guardada é completa; cada uma das seguintes guarda só
+/// o que mudou no conteúdo em relação à anterior (linhas copiadas dela e
+/// texto inserido), a não ser que o conteúdo completo seja menor. Notas de
+/// esquemas anteriores à versão 6 têm todas as versões completas e continuam
+/// legíveis.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct History {
+    entries: Vec<StoredVersion>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredVersion {
+    title: String,
+    #[serde(alias = "updated_at")]
+    updated_at: i64,
+    /// Conteúdo completo; `None` quando a versão é guardada como `delta`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    delta: Vec<DeltaOp>,
+}
+
+/// Parte do conteúdo de uma versão, a partir da versão anterior.
+// Sem arrays dentro de arrays: o Firestore não aceita.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum DeltaOp {
+    /// `len` linhas da versão anterior, a partir da linha `from`.
+    Copy { from: usize, len: usize },
+    Insert(String),
+}
+
+/// Tamanho aproximado de uma cópia de linhas no JSON gravado.
+const COPY_COST: usize = 20;
+
+impl History {
+    pub fn new(versions: Vec<NoteHistory>) -> Self {
+        let mut previous: Option<String> = None;
+        let entries = versions.into_iter()
+            .map(|version| {
+                let (content, delta) = match previous.as_deref() {
+                    Some(base) => {
+                        let delta = encode(base, &version.content);
+                        if cost(&delta) < version.content.len() {
+                            (None, delta)
+                        } else {
+                            (Some(version.content.clone()), Vec::new())
+                        }
+                    }
+                    None => (Some(version.content.clone()), Vec::new()),
+                };
+                previous = Some(version.content);
+                StoredVersion { title: version.title, updated_at: version.updated_at, content, delta }
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// Todas as versões guardadas, completas, da mais antiga para a mais nova.
+    pub fn versions(&self) -> Vec<NoteHistory> {
+        let mut versions: Vec<NoteHistory> = Vec::with_capacity(self.entries.len());
+        for entry in &self.entries {
+            let content = match &entry.content {
+                Some(content) => content.clone(),
+                None => apply(versions.last().map_or("", |v| v.content.as_str()), &entry.delta),
+            };
+            versions.push(NoteHistory { title: entry.title.clone(), content, updated_at: entry.updated_at });
+        }
+        versions
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Acrescenta a versão e aplica a política de retenção.
+    pub fn push(&mut self, version: NoteHistory, policy: &RetentionPolicy) {
+        let now = version.updated_at;
+        let mut versions = self.versions();
+        versions.push(version);
+        *self = Self::new(policy.retain(versions, now));
+    }
+
+    /// Como `push`, mas a versão substitui a última guardada se as duas
+    /// estiverem a menos de `policy.snapshot_minutes` uma da outra.
+    pub fn record(&mut self, version: NoteHistory, policy: &RetentionPolicy) {
+        let now = version.updated_at;
+        let mut versions = self.versions();
+        if versions.last().is_some_and(|last| now - last.updated_at < policy.snapshot_minutes * MINUTE_MS) {
+            versions.pop();
+        }
+        versions.push(version);
+        *self = Self::new(policy.retain(versions, now));
+    }
+}
+
+fn encode(old: &str, new: &str) -> Vec<DeltaOp> {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let mut delta: Vec<DeltaOp> = Vec::new();
+    for op in capture_diff_slices(Algorithm::Myers, &old_lines, &new_lines) {
+        match op {
+            DiffOp::Equal { old_index, len, .. } => delta.push(DeltaOp::Copy { from: old_index, len }),
+            DiffOp::Delete { .. } => {}
+            DiffOp::Insert { new_index, new_len, .. } | DiffOp::Replace { new_index, new_len, .. } => {
+                let text = new_lines[new_index..new_index + new_len].concat();
+                match delta.last_mut() {
+                    Some(DeltaOp::Insert(previous)) => previous.push_str(&text),
+                    _ => delta.push(DeltaOp::Insert(text)),
+                }
+            }
+        }
+    }
+    delta
+}
+
+/// Reconstrói o conteúdo; cópias fora do texto anterior (dado corrompido)
+/// são ignoradas.
+fn apply(base: &str, delta: &[DeltaOp]) -> String {
+    let lines: Vec<&str> = base.split_inclusive('\n').collect();
+    let mut out = String::new();
+    for op in delta {
+        match op {
+            DeltaOp::Copy { from, len } => {
+                if let Some(copied) = lines.get(*from..from.saturating_add(*len)) {
+                    out.extend(copied.iter().copied());
+                }
+            }
+            DeltaOp::Insert(text) => out.push_str(text),
+        }
+    }
+    out
+}
+
+fn cost(delta: &[DeltaOp]) -> usize {
+    delta.iter()
+        .map(|op| match op {
+            DeltaOp::Copy { .. } => COPY_COST,
+            DeltaOp::Insert(text) => text.len(),
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(content: &str, updated_at: i64) -> NoteHistory {
+        NoteHistory { title: "Título".to_string(), content: content.to_string(), updated_at }
+    }
+
+    fn times(versions: &[NoteHistory]) -> Vec<i64> {
+        versions.iter().map(|v| v.updated_at).collect()
+    }
+
+    /// Conteúdo grande o bastante para a diferença valer mais que a cópia.
+    fn long_text(changed: &str) -> String {
+        let mut text: String = (0..20).map(|i| format!("linha {} com acentuação — ç\n", i)).collect();
+        text.push_str(changed);
+        text.push_str("\núltima linha sem quebra 🎉");
+        text
+    }
+
+    #[test]
+    fn record_round_trips_delta_versions() {
+        let policy = RetentionPolicy::default();
+        let contents = [long_text("começo"), long_text("meio 日本語"), long_text("fim ✓\nnova linha")];
+
+        let mut history = History::default();
+        for (i, content) in contents.iter().enumerate() {
+            history.record(version(content, i as i64 * DAY_MS), &policy);
+        }
+
+        let stored = &history.entries;
+        assert!(stored[0].content.is_some());
+        for entry in &stored[1..] {
+            assert!(entry.content.is_none());
+            assert!(entry.delta.iter().any(|op| matches!(op, DeltaOp::Copy { .. })));
+            assert!(entry.delta.iter().any(|op| matches!(op, DeltaOp::Insert(_))));
+        }
+        let versions: Vec<String> = history.versions().into_iter().map(|v| v.content).collect();
+        assert_eq!(versions, contents);
+    }
+
+    #[test]
+    fn delta_survives_json() {
+        let policy = RetentionPolicy::default();
+        let mut history = History::default();
+        history.record(version(&long_text("antes"), 0), &policy);
+        history.record(version(&long_text("depois ção"), DAY_MS), &policy);
+
+        let json = serde_json::to_string(&history).unwrap();
+        let read: History = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.versions(), history.versions());
+    }
+
+    #[test]
+    fn small_changes_are_stored_whole() {
+        let history = History::new(vec![version("a", 0), version("b", 1)]);
+        assert_eq!(history.entries[1].content.as_deref(), Some("b"));
+        assert_eq!(history.versions()[1].content, "b");
+    }
+
+    #[test]
+    fn copy_outside_previous_text_is_ignored() {
+        let delta = [DeltaOp::Copy { from: 1, len: 5 }, DeltaOp::Insert("é".to_string()), DeltaOp::Copy { from: 0, len: 1 }];
+        assert_eq!(apply("só\n", &delta), "ésó\n");
+    }
+
+    #[test]
+    fn retain_keeps_the_last_versions() {
+        let policy = RetentionPolicy { max_versions: 3, ..RetentionPolicy::default() };
+        let versions: Vec<_> = (0..5).map(|i| version("x", i * MINUTE_MS)).collect();
+        let kept = policy.retain(versions, 5 * MINUTE_MS);
+        assert_eq!(times(&kept), vec![2 * MINUTE_MS, 3 * MINUTE_MS, 4 * MINUTE_MS]);
+    }
+
+    #[test]
+    fn retain_spaces_old_versions_by_age() {
+        let policy = RetentionPolicy::default();
+        let now = 100 * DAY_MS;
+        let versions = vec![
+            // mesma semana, mais de 30 dias atrás: fica a mais nova
+            version("a", 50 * DAY_MS),
+            version("b", 52 * DAY_MS),
+            // mesmo dia, entre 1 e 30 dias atrás: fica a mais nova
+            version("c", 95 * DAY_MS + 60 * MINUTE_MS),
+            version("d", 95 * DAY_MS + 300 * MINUTE_MS),
+            // menos de um dia: ficam todas
+            version("e", now - 60 * MINUTE_MS),
+            version("f", now - 30 * MINUTE_MS),
+        ];
+        let kept: Vec<String> = policy.retain(versions, now).into_iter().map(|v| v.content).collect();
+        assert_eq!(kept, ["b", "d", "e", "f"]);
+    }
+
+    #[test]
+    fn record_coalesces_versions_within_snapshot_minutes() {
+        let policy = RetentionPolicy::default();
+        let mut history = History::default();
+        history.record(version("primeira", 0), &policy);
+        history.record(version("segunda", 10 * MINUTE_MS), &policy);
+        // a menos de 10 minutos da última guardada: cada uma substitui a anterior
+        history.record(version("rascunho", 15 * MINUTE_MS), &policy);
+        history.record(version("terceira", 24 * MINUTE_MS), &policy);
+        history.record(version("quarta", 34 * MINUTE_MS), &policy);
+
+        let versions = history.versions();
+        assert_eq!(times(&versions), vec![0, 24 * MINUTE_MS, 34 * MINUTE_MS]);
+        assert_eq!(versions[1].content, "terceira");
+    }
+
+    #[test]
+    fn push_never_coalesces() {
+        let policy = RetentionPolicy::default();
+        let mut history = History::default();
+        history.push(version("a", 0), &policy);
+        history.push(version("b", MINUTE_MS), &policy);
+        assert_eq!(history.len(), 2);
+    }
+}
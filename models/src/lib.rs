@@ -0,0 +1,20 @@
+// src/lib.rs
+//! Tipos compartilhados entre o frontend (wasm32) e o backend (nativo).
+
+pub mod acl;
+pub mod clock;
+pub mod collab;
+pub mod history;
+pub mod note;
+pub mod notebook;
+pub mod status;
+pub mod tags;
+
+pub use acl::{normalize_email, Role, ShareError};
+pub use clock::{Clock, FixedClock, SystemClock};
+pub use collab::{CollabChange, CollabMessage, Presence};
+pub use history::{History, RetentionPolicy};
+pub use note::{Note, NoteHistory, SchemaError, User, NOTE_SCHEMA_VERSION};
+pub use notebook::{Notebook, NotebookTree};
+pub use status::{NoteStatus, TRASH_RETENTION_DAYS};
+pub use tags::{normalize_tag, TagMatch};
@@ -0,0 +1,259 @@
+// src/note.rs
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use crate::clock::{Clock, SystemClock};
+use crate::history::History;
+
+/// Versão atual do formato de `Note` gravado nos backends.
+///
+/// Documentos sem o campo `schemaVersion` são da versão 0 (mapeamento manual
+/// anterior ao serde) e são migrados em `Note::upgrade`. A versão 2 adicionou
+/// o compartilhamento (`editors`, `viewers`), a versão 3 as `tags`, a
+/// versão 4 o caderno (`notebookId`), a versão 5 `pinned`, `archived` e
+/// `trashedAt` e a versão 6 o histórico gravado como diferenças (`History`).
+pub const NOTE_SCHEMA_VERSION: u32 = 6;
+
+// Os aliases em snake_case leem notas gravadas antes do `rename_all`
+// (localStorage e SQLite do backend).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Note {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub title: String,
+    pub content: String,
+    #[serde(alias = "user_id")]
+    pub user_id: String,
+    #[serde(alias = "created_at")]
+    pub created_at: i64,
+    #[serde(alias = "updated_at")]
+    pub updated_at: i64,
+    /// Versões anteriores; `History::versions` as reconstrói.
+    #[serde(default)]
+    pub history: History,
+    #[serde(default)]
+    pub font: String,
+    #[serde(default)]
+    pub background: String,
+    #[serde(default)]
+    pub color: String,
+    #[serde(default, alias = "font_size", skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<u8>,
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Incrementado pelo backend a cada gravação; uma atualização só é aceita
+    /// se partir da revisão atual (concorrência otimista).
+    #[serde(default)]
+    pub revision: u64,
+    /// E-mail do dono (`user_id`), mostrado a quem recebeu a nota.
+    #[serde(default)]
+    pub owner_email: String,
+    /// E-mails, em minúsculas, que podem editar a nota.
+    #[serde(default)]
+    pub editors: Vec<String>,
+    /// E-mails, em minúsculas, que só podem ler a nota.
+    #[serde(default)]
+    pub viewers: Vec<String>,
+    /// `editors` e `viewers` juntos, mantido por `share`/`unshare` para a
+    /// consulta "compartilhadas comigo" (`array-contains` no Firestore).
+    #[serde(default)]
+    pub shared_with: Vec<String>,
+    /// Etiquetas normalizadas por `normalize_tag`, na ordem em que foram
+    /// adicionadas.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Caderno do dono onde a nota está; `None` fica fora de cadernos.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notebook_id: Option<String>,
+    /// Mostrada no topo da lista.
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub archived: bool,
+    /// Quando a nota foi para a lixeira; `None` fora dela.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trashed_at: Option<i64>,
+}
+
+impl Note {
+    pub fn new(title: String, content: String, user_id: String, font: String, color: String, background: String, font_size: Option<u8>) -> Self {
+        Self::new_with_clock(&SystemClock, title, content, user_id, font, color, background, font_size)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_with_clock(clock: &impl Clock, title: String, content: String, user_id: String, font: String, color: String, background: String, font_size: Option<u8>) -> Self {
+        let timestamp = clock.now_millis();
+        Self {
+            id: None,
+            title: title.clone(),
+            content: content.clone(),
+            user_id,
+            created_at: timestamp,
+            updated_at: timestamp,
+            history: History::new(vec![NoteHistory{title, content, updated_at: timestamp}]),
+            font,
+            background,
+            color,
+            font_size,
+            schema_version: NOTE_SCHEMA_VERSION,
+            revision: 0,
+            owner_email: String::new(),
+            editors: Vec::new(),
+            viewers: Vec::new(),
+            shared_with: Vec::new(),
+            tags: Vec::new(),
+            notebook_id: None,
+            pinned: false,
+            archived: false,
+            trashed_at: None,
+        }
+    }
+
+    /// Valida a versão de um documento lido e o converte para a versão atual.
+    pub fn upgrade(mut self) -> Result<Self, SchemaError> {
+        if self.schema_version > NOTE_SCHEMA_VERSION {
+            return Err(SchemaError::UnsupportedVersion(self.schema_version));
+        }
+        if self.schema_version == 0 {
+            // v0 gravava cor/fonte vazias quando ausentes
+            if self.font.is_empty() { self.font = "Arial".to_string(); }
+            if self.color.is_empty() { self.color = "black".to_string(); }
+            if self.background.is_empty() { self.background = "white".to_string(); }
+        }
+        if self.schema_version < 6 {
+            // versões completas passam a ser gravadas como diferenças
+            self.history = History::new(self.history.versions());
+        }
+        self.schema_version = NOTE_SCHEMA_VERSION;
+        Ok(self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaError {
+    /// Documento gravado por uma versão mais nova do aplicativo.
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::UnsupportedVersion(v) => write!(
+                f, "versão {} do documento não suportada (máxima: {})", v, NOTE_SCHEMA_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct User {
+    pub uid: String,
+    pub email: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteHistory{
+    pub title: String,
+    pub content: String,
+    #[serde(alias = "updated_at")]
+    pub updated_at: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+
+    fn sample() -> Note {
+        Note::new_with_clock(
+            &FixedClock(1_700_000_000_000),
+            "Título".to_string(),
+            "conteúdo".to_string(),
+            "uid-1".to_string(),
+            "Arial".to_string(),
+            "black".to_string(),
+            "white".to_string(),
+            Some(14),
+        )
+    }
+
+    #[test]
+    fn new_uses_the_injected_clock() {
+        let note = sample();
+        assert_eq!(note.created_at, 1_700_000_000_000);
+        assert_eq!(note.updated_at, 1_700_000_000_000);
+        assert_eq!(note.schema_version, NOTE_SCHEMA_VERSION);
+        assert_eq!(note.history.versions(), vec![NoteHistory {
+            title: "Título".to_string(),
+            content: "conteúdo".to_string(),
+            updated_at: 1_700_000_000_000,
+        }]);
+    }
+
+    #[test]
+    fn serde_round_trip_is_camel_case() {
+        let mut note = sample();
+        note.id = Some("n1".to_string());
+        note.tags = vec!["receitas".to_string()];
+        note.notebook_id = Some("nb".to_string());
+        let json = serde_json::to_value(&note).unwrap();
+        assert_eq!(json["userId"], "uid-1");
+        assert_eq!(json["schemaVersion"], NOTE_SCHEMA_VERSION);
+        assert_eq!(json["notebookId"], "nb");
+        assert!(json.get("user_id").is_none());
+        assert!(json.get("trashedAt").is_none());
+        let back: Note = serde_json::from_value(json).unwrap();
+        assert_eq!(back, note);
+    }
+
+    #[test]
+    fn upgrades_v0_snake_case_document() {
+        let json = r#"{
+            "title": "Antiga",
+            "content": "b",
+            "user_id": "uid-1",
+            "created_at": 10,
+            "updated_at": 20,
+            "history": [
+                {"title": "Antiga", "content": "a", "updated_at": 10},
+                {"title": "Antiga", "content": "b", "updated_at": 20}
+            ],
+            "font": "",
+            "color": "",
+            "background": ""
+        }"#;
+        let note: Note = serde_json::from_str(json).unwrap();
+        assert_eq!(note.schema_version, 0);
+        let note = note.upgrade().unwrap();
+        assert_eq!(note.schema_version, NOTE_SCHEMA_VERSION);
+        assert_eq!(note.user_id, "uid-1");
+        assert_eq!((note.created_at, note.updated_at), (10, 20));
+        assert_eq!((note.font.as_str(), note.color.as_str(), note.background.as_str()), ("Arial", "black", "white"));
+        assert!(note.editors.is_empty() && note.tags.is_empty() && !note.pinned);
+        let contents: Vec<String> = note.history.versions().into_iter().map(|v| v.content).collect();
+        assert_eq!(contents, ["a", "b"]);
+    }
+
+    #[test]
+    fn upgrade_keeps_explicit_style_after_v0() {
+        let mut note = sample();
+        note.schema_version = 1;
+        note.font = String::new();
+        assert_eq!(note.upgrade().unwrap().font, "");
+    }
+
+    #[test]
+    fn rejects_newer_schema() {
+        let mut note = sample();
+        note.schema_version = NOTE_SCHEMA_VERSION + 1;
+        assert_eq!(note.upgrade(), Err(SchemaError::UnsupportedVersion(NOTE_SCHEMA_VERSION + 1)));
+    }
+
+    #[test]
+    fn missing_required_field_is_an_error() {
+        assert!(serde_json::from_str::<Note>(r#"{"title": "x", "content": ""}"#).is_err());
+    }
+}
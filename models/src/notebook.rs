@@ -0,0 +1,84 @@
+// src/notebook.rs
+use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+
+/// Caderno de notas; cadernos podem conter outros (`parent_id`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Notebook {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    pub user_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    pub created_at: i64,
+}
+
+impl Notebook {
+    pub fn new(name: String, user_id: String, parent_id: Option<String>, created_at: i64) -> Self {
+        Self { id: None, name, user_id, parent_id, created_at }
+    }
+}
+
+/// Consultas sobre a hierarquia de uma lista de cadernos.
+pub struct NotebookTree<'a> {
+    notebooks: &'a [Notebook],
+}
+
+impl<'a> NotebookTree<'a> {
+    pub fn new(notebooks: &'a [Notebook]) -> Self {
+        Self { notebooks }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&'a Notebook> {
+        self.notebooks.iter().find(|n| n.id.as_deref() == Some(id))
+    }
+
+    /// Filhos diretos de `parent` (raízes com `None`), em ordem alfabética.
+    /// Um caderno cujo pai não existe mais é tratado como raiz.
+    pub fn children(&self, parent: Option<&str>) -> Vec<&'a Notebook> {
+        let mut children: Vec<&Notebook> = self.notebooks.iter()
+            .filter(|n| match (n.parent_id.as_deref(), parent) {
+                (Some(p), Some(parent)) => p == parent,
+                (Some(p), None) => self.get(p).is_none(),
+                (None, parent) => parent.is_none(),
+            })
+            .collect();
+        children.sort_by_key(|n| n.name.to_lowercase());
+        children
+    }
+
+    /// Caminho da raiz até o caderno, inclusive.
+    pub fn path(&self, id: &str) -> Vec<&'a Notebook> {
+        let mut path = Vec::new();
+        let mut seen = HashSet::new();
+        let mut current = self.get(id);
+        while let Some(notebook) = current {
+            // um ciclo gravado por engano não pode travar a interface
+            if !seen.insert(notebook.id.clone()) {
+                break;
+            }
+            path.push(notebook);
+            current = notebook.parent_id.as_deref().and_then(|p| self.get(p));
+        }
+        path.reverse();
+        path
+    }
+
+    /// Ids do caderno e de todos os cadernos dentro dele.
+    pub fn subtree(&self, id: &str) -> HashSet<String> {
+        let mut ids = HashSet::from([id.to_string()]);
+        let mut pending = vec![id.to_string()];
+        while let Some(parent) = pending.pop() {
+            for child in self.children(Some(&parent)) {
+                if let Some(child_id) = &child.id {
+                    if ids.insert(child_id.clone()) {
+                        pending.push(child_id.clone());
+                    }
+                }
+            }
+        }
+        ids
+    }
+}
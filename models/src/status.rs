@@ -0,0 +1,106 @@
+// src/status.rs
+use crate::clock::DAY_MS;
+use crate::note::Note;
+
+/// Dias que uma nota fica na lixeira antes de ser excluída de vez.
+pub const TRASH_RETENTION_DAYS: i64 = 30;
+
+/// Onde a nota aparece: na lista principal, entre as arquivadas ou na lixeira.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NoteStatus {
+    #[default]
+    Active,
+    Archived,
+    Trashed,
+}
+
+impl Note {
+    /// Uma nota arquivada que vai para a lixeira volta arquivada ao ser
+    /// restaurada.
+    pub fn status(&self) -> NoteStatus {
+        if self.trashed_at.is_some() {
+            NoteStatus::Trashed
+        } else if self.archived {
+            NoteStatus::Archived
+        } else {
+            NoteStatus::Active
+        }
+    }
+
+    /// Quando a nota na lixeira deve ser excluída de vez.
+    pub fn purge_at(&self) -> Option<i64> {
+        self.trashed_at.map(|t| t + TRASH_RETENTION_DAYS * DAY_MS)
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.purge_at().is_some_and(|at| at <= now)
+    }
+
+    /// Dias inteiros (arredondados para cima) até a exclusão definitiva.
+    pub fn days_until_purge(&self, now: i64) -> Option<i64> {
+        self.purge_at().map(|at| ((at - now).max(0) + DAY_MS - 1) / DAY_MS)
+    }
+
+    /// Fixar, arquivar e mandar para a lixeira são decisões do dono.
+    pub fn same_status(&self, other: &Note) -> bool {
+        self.pinned == other.pinned
+            && self.archived == other.archived
+            && self.trashed_at == other.trashed_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, FixedClock};
+
+    const TRASHED: FixedClock = FixedClock(1_000_000);
+
+    fn trashed() -> Note {
+        let mut note = Note::new_with_clock(&FixedClock(0), "t".to_string(), String::new(), "u1".to_string(),
+            String::new(), String::new(), String::new(), None);
+        note.trashed_at = Some(TRASHED.now_millis());
+        note
+    }
+
+    /// Instante `offset` ms depois de a nota ir para a lixeira.
+    fn at(offset: i64) -> i64 {
+        TRASHED.now_millis() + offset
+    }
+
+    #[test]
+    fn status_follows_flags() {
+        let mut note = trashed();
+        note.archived = true;
+        assert_eq!(note.status(), NoteStatus::Trashed);
+        note.trashed_at = None;
+        assert_eq!(note.status(), NoteStatus::Archived);
+        note.archived = false;
+        assert_eq!(note.status(), NoteStatus::Active);
+        assert_eq!(note.purge_at(), None);
+        assert_eq!(note.days_until_purge(0), None);
+        assert!(!note.is_expired(i64::MAX));
+    }
+
+    #[test]
+    fn expires_exactly_at_the_retention_boundary() {
+        let note = trashed();
+        let limit = TRASH_RETENTION_DAYS * DAY_MS;
+        assert_eq!(note.purge_at(), Some(at(limit)));
+        assert!(!note.is_expired(at(limit - 1)));
+        assert!(note.is_expired(at(limit)));
+        assert!(note.is_expired(at(limit + DAY_MS)));
+    }
+
+    #[test]
+    fn days_until_purge_rounds_up() {
+        let note = trashed();
+        let limit = TRASH_RETENTION_DAYS * DAY_MS;
+        assert_eq!(note.days_until_purge(at(0)), Some(TRASH_RETENTION_DAYS));
+        assert_eq!(note.days_until_purge(at(1)), Some(TRASH_RETENTION_DAYS));
+        assert_eq!(note.days_until_purge(at(DAY_MS)), Some(TRASH_RETENTION_DAYS - 1));
+        assert_eq!(note.days_until_purge(at(limit - 1)), Some(1));
+        assert_eq!(note.days_until_purge(at(limit)), Some(0));
+        assert_eq!(note.days_until_purge(at(limit + DAY_MS)), Some(0));
+    }
+}
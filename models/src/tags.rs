@@ -0,0 +1,74 @@
+// src/tags.rs
+use crate::note::Note;
+
+/// Forma canônica de uma etiqueta: sem `#` inicial, em minúsculas e com
+/// espaços trocados por `-` ("#Receitas da Vó" → "receitas-da-vó").
+/// `None` se não sobrar nada.
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim().trim_start_matches('#').to_lowercase();
+    let tag = tag.split_whitespace().collect::<Vec<_>>().join("-");
+    (!tag.is_empty()).then_some(tag)
+}
+
+/// Como combinar várias etiquetas no filtro.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TagMatch {
+    /// A nota precisa ter todas as etiquetas.
+    #[default]
+    All,
+    /// Basta uma das etiquetas.
+    Any,
+}
+
+impl Note {
+    /// Sem etiquetas no filtro, toda nota passa.
+    pub fn matches_tags(&self, tags: &[String], mode: TagMatch) -> bool {
+        match mode {
+            TagMatch::All => tags.iter().all(|t| self.tags.contains(t)),
+            TagMatch::Any => tags.is_empty() || tags.iter().any(|t| self.tags.contains(t)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+
+    fn tagged(tags: &[&str]) -> Note {
+        let mut note = Note::new_with_clock(&FixedClock(0), "t".to_string(), String::new(), "u1".to_string(),
+            String::new(), String::new(), String::new(), None);
+        note.tags = tags.iter().map(|t| t.to_string()).collect();
+        note
+    }
+
+    fn filter(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn normalizes_tags() {
+        assert_eq!(normalize_tag("  #Receitas da  Vó "), Some("receitas-da-vó".to_string()));
+        assert_eq!(normalize_tag("TRABALHO"), Some("trabalho".to_string()));
+        assert_eq!(normalize_tag("##Ideias"), Some("ideias".to_string()));
+        assert_eq!(normalize_tag(" # "), None);
+        assert_eq!(normalize_tag(""), None);
+    }
+
+    #[test]
+    fn empty_filter_matches_every_note() {
+        for note in [tagged(&[]), tagged(&["casa"])] {
+            assert!(note.matches_tags(&[], TagMatch::All));
+            assert!(note.matches_tags(&[], TagMatch::Any));
+        }
+    }
+
+    #[test]
+    fn all_and_any() {
+        let note = tagged(&["casa", "compras"]);
+        assert!(note.matches_tags(&filter(&["casa", "compras"]), TagMatch::All));
+        assert!(!note.matches_tags(&filter(&["casa", "trabalho"]), TagMatch::All));
+        assert!(note.matches_tags(&filter(&["casa", "trabalho"]), TagMatch::Any));
+        assert!(!note.matches_tags(&filter(&["trabalho"]), TagMatch::Any));
+    }
+}